@@ -46,9 +46,10 @@ fn main() {
 
             term_seq_variants.push_str(
                 format!(
-                    "    {} = ffi::{},\n",
+                    "    {} = ffi::{} => \"{}\",\n",
                     value_name.to_case(Case::Pascal),
-                    const_name
+                    const_name,
+                    value_name.to_lowercase()
                 )
                 .as_str(),
             );
@@ -62,4 +63,195 @@ fn main() {
     )
     .join("src/term/term_seq.rs");
     std::fs::write(term_seq_path, term_seq_variants).unwrap();
+
+    // Generate typed wrappers for the `chafa_term_info_emit_*()` family:
+
+    let mut term_emitters = String::new();
+    let mut term_emitters_varargs = String::new();
+    let mut seq_dispatch = String::new();
+    let mut seq_dispatch_varargs = String::new();
+    // Argument structs, keyed by signature: (signature, struct name, variants using it).
+    let mut seq_args: Vec<(String, String, Vec<String>)> = Vec::new();
+
+    // Collapse whitespace so declarations can be matched regardless of how bindgen formatted them.
+    let flat_bindings = bindings
+        .to_string()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    const EMIT_PREFIX: &str = "pub fn chafa_term_info_emit_";
+
+    let mut rest = flat_bindings.as_str();
+    while let Some(pos) = rest.find(EMIT_PREFIX) {
+        rest = &rest[pos + EMIT_PREFIX.len()..];
+
+        let (Some(name_end), Some(params_end)) = (rest.find('('), rest.find(')')) else {
+            break;
+        };
+        let func_name = rest[..name_end].trim().to_string();
+        let params: Vec<(String, String)> = rest[name_end + 1..params_end]
+            .split(',')
+            .filter_map(|p| {
+                let (name, ty) = p.split_once(':')?;
+                Some((name.trim().to_string(), ty.trim().to_string()))
+            })
+            .collect();
+        rest = &rest[params_end..];
+
+        // `chafa_term_info_emit_seq()` and friends are generic; only the typed
+        // emitters take a destination buffer as their second parameter.
+        if params.len() < 2 || params[1].0 != "dest" {
+            continue;
+        }
+        let seq_name = format!("CHAFA_TERM_SEQ_{}", func_name.to_uppercase());
+        let variant_name = func_name.to_case(Case::Pascal);
+        let args = &params[2..];
+
+        if args.len() == 2 && args[0].1.ends_with("guint") && args[0].1.starts_with('*') {
+            term_emitters_varargs.push_str(
+                format!(
+                    "    emit_{} / emit_{}_to = ffi::chafa_term_info_emit_{}, \"{}\",\n",
+                    func_name, func_name, func_name, seq_name
+                )
+                .as_str(),
+            );
+            seq_dispatch_varargs
+                .push_str(format!("    {} => emit_{},\n", variant_name, func_name).as_str());
+            continue;
+        }
+
+        let rust_types: Option<Vec<&str>> = args
+            .iter()
+            .map(|(_, ty)| match ty.as_str() {
+                "guint" | "gunichar" => Some("u32"),
+                "guint8" => Some("u8"),
+                "guint16" => Some("u16"),
+                "gint" => Some("i32"),
+                _ => None,
+            })
+            .collect();
+
+        let Some(rust_types) = rust_types else {
+            println!(
+                "cargo:warning=Skipping `chafa_term_info_emit_{}`: unsupported argument types",
+                func_name
+            );
+            continue;
+        };
+
+        let rust_args: Vec<String> = args
+            .iter()
+            .zip(rust_types.iter())
+            .map(|((name, _), ty)| format!("{}: {}", name, ty))
+            .collect();
+        term_emitters.push_str(
+            format!(
+                "    emit_{}({}) / emit_{}_to = ffi::chafa_term_info_emit_{}, \"{}\",\n",
+                func_name,
+                rust_args.join(", "),
+                func_name,
+                func_name,
+                seq_name
+            )
+            .as_str(),
+        );
+
+        let signature = rust_args.join(", ");
+        match seq_args.iter_mut().find(|(sig, _, _)| *sig == signature) {
+            _ if signature.is_empty() => {}
+            Some((_, _, variants)) => variants.push(variant_name.clone()),
+            None => {
+                let struct_name = args_struct_name(&signature)
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(|| format!("{}Args", variant_name));
+                seq_args.push((signature, struct_name, vec![variant_name.clone()]));
+            }
+        }
+
+        let indexed_args: Vec<String> = rust_types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| format!("{}: {}", i, ty))
+            .collect();
+        seq_dispatch.push_str(
+            format!(
+                "    {}({}) => emit_{}({}),\n",
+                variant_name,
+                rust_types.len(),
+                func_name,
+                indexed_args.join(", ")
+            )
+            .as_str(),
+        );
+    }
+
+    let mut term_emit = String::new();
+    term_emit.push_str(GEN_STR);
+    term_emit.push_str("gen_emitters! {\n");
+    term_emit.push_str(&term_emitters);
+    term_emit.push_str("}\n\n");
+    term_emit.push_str("gen_emitters_varargs! {\n");
+    term_emit.push_str(&term_emitters_varargs);
+    term_emit.push_str("}\n\n");
+    term_emit.push_str("gen_seq_dispatch! {\n");
+    term_emit.push_str(&seq_dispatch);
+    term_emit.push_str("    ;\n");
+    term_emit.push_str(&seq_dispatch_varargs);
+    term_emit.push_str("}\n\n");
+    term_emit.push_str(GEN_STR);
+
+    let mut term_args = String::new();
+    term_args.push_str(GEN_STR);
+    term_args.push_str("gen_seq_args! {\n");
+    for (signature, struct_name, variants) in seq_args.iter() {
+        term_args.push_str(
+            format!(
+                "    {}({}) => [{}],\n",
+                struct_name,
+                signature,
+                variants.join(", ")
+            )
+            .as_str(),
+        );
+    }
+    term_args.push_str("}\n\n");
+    term_args.push_str(GEN_STR);
+
+    let term_args_path = std::path::Path::new(
+        &std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set"),
+    )
+    .join("src/term/term_args.rs");
+    std::fs::write(term_args_path, term_args).unwrap();
+
+    let term_emit_path = std::path::Path::new(
+        &std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set"),
+    )
+    .join("src/term/term_emit.rs");
+    std::fs::write(term_emit_path, term_emit).unwrap();
+}
+
+/// Names the argument struct for a family of sequences sharing an emitter signature. Signatures not listed here get a struct named after their first sequence.
+fn args_struct_name(signature: &str) -> Option<&'static str> {
+    Some(match signature {
+        "n: u32" => "Count",
+        "x: u32, y: u32" => "CursorPos",
+        "top: u32, bottom: u32" => "ScrollingRows",
+        "pen: u8" => "Pen",
+        "fg_pen: u8, bg_pen: u8" => "PenFgBg",
+        "r: u8, g: u8, b: u8" => "ColorDirect",
+        "fg_r: u8, fg_g: u8, fg_b: u8, bg_r: u8, bg_g: u8, bg_b: u8" => "ColorFgBgDirect",
+        "r: u16, g: u16, b: u16" => "DefaultColor",
+        "p1: u32, p2: u32, p3: u32" => "SixelParams",
+        "width: u32, height: u32" => "ImageSize",
+        "height_px: u32, width_px: u32" => "SizePx",
+        "height_cells: u32, width_cells: u32" => "SizeCells",
+        "bpp: u32, width_pixels: u32, height_pixels: u32, width_cells: u32, height_cells: u32" => {
+            "KittyImage"
+        }
+        "bpp: u32, width_pixels: u32, height_pixels: u32, width_cells: u32, height_cells: u32, id: u32" => {
+            "KittyVirtImage"
+        }
+        _ => return None,
+    })
 }
@@ -0,0 +1,165 @@
+/*
+ * Decoded animations made of full frames, and playback of them on a canvas.
+ */
+
+use crate::{ChafaError, Frame, misc};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "apng")]
+mod apng;
+mod control;
+#[cfg(feature = "gif")]
+mod gif;
+#[cfg(feature = "icc")]
+mod icc;
+mod kitty;
+mod player;
+mod raw_video;
+mod source;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "webp")]
+mod webp;
+pub use control::{PlaybackControl, Repeat};
+#[cfg(feature = "gif")]
+pub use gif::GifFrames;
+#[cfg(feature = "icc")]
+pub use icc::EmbeddedProfile;
+pub use kitty::*;
+pub use player::*;
+pub use raw_video::*;
+pub use source::*;
+#[cfg(feature = "video")]
+pub use video::VideoSource;
+#[cfg(feature = "webp")]
+pub use webp::WebPFrames;
+
+/// One fully composited frame of an animation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnimationFrame {
+    /// Pixel data in RGBA8Unassociated, covering the whole animation with rows of width * 4 bytes.
+    pub pixels: Vec<u8>,
+    /// How long the frame is shown before the next one.
+    pub delay: Duration,
+}
+
+/// A sequence of same-sized frames with their timing, ready to be drawn on a canvas one after the other.
+///
+/// Every frame holds the complete image as it should appear on screen, so formats that store partial updates (like GIF) are composited while decoding and playback never has to look at earlier frames.
+#[derive(Clone, Debug)]
+pub struct Animation {
+    width: i32,
+    height: i32,
+    // Shared so async playback can hand frames to the blocking pool without copying them; pushing to a shared list copies it first.
+    frames: Arc<Vec<AnimationFrame>>,
+    loop_count: Option<u32>,
+    #[cfg(feature = "icc")]
+    embedded_profile: EmbeddedProfile,
+}
+
+impl Animation {
+    /// Creates an empty animation that plays once.
+    /// # Parameters:
+    /// --- `width`: Width of every frame, in pixels;
+    /// --- `height`: Height of every frame, in pixels;
+    pub fn new(width: i32, height: i32) -> Result<Self, ChafaError> {
+        if width <= 0 || height <= 0 {
+            return Err(ChafaError::InvalidInput(format!(
+                "Animation size must be positive, got {}x{}",
+                width, height
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            frames: Arc::default(),
+            loop_count: Some(1),
+            #[cfg(feature = "icc")]
+            embedded_profile: EmbeddedProfile::None,
+        })
+    }
+
+    /// Appends a frame.
+    /// # Parameters:
+    /// --- `pixels`: RGBA8Unassociated pixel data of the whole frame, with rows of width * 4 bytes;
+    /// --- `delay`: How long the frame is shown;
+    pub fn push_frame(&mut self, pixels: Vec<u8>, delay: Duration) -> Result<(), ChafaError> {
+        let needed = self.width as usize * self.height as usize * 4;
+        if pixels.len() != needed {
+            return Err(ChafaError::InvalidInput(format!(
+                "Animation frame needs {} bytes of pixel data, got {}",
+                needed,
+                pixels.len()
+            )));
+        }
+        Arc::make_mut(&mut self.frames).push(AnimationFrame { pixels, delay });
+        Ok(())
+    }
+
+    /// Width of the animation, in pixels.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Height of the animation, in pixels.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the frames in playback order.
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    /// Returns the number of frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if the animation has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns how long one pass through all frames takes.
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.delay).sum()
+    }
+
+    /// Returns how many times the animation is meant to play in full, or None if it loops forever. Decoders fill this in from the file's metadata; it defaults to 1.
+    pub fn loop_count(&self) -> Option<u32> {
+        self.loop_count
+    }
+
+    /// Sets how many times the animation is meant to play in full, or None to loop forever.
+    pub fn set_loop_count(&mut self, loop_count: Option<u32>) {
+        self.loop_count = loop_count;
+    }
+
+    /// Creates a ChafaFrame holding a copy of the frame at index, e.g. for `Image::set_frame()`.
+    pub fn frame(&self, index: usize) -> Result<Frame, ChafaError> {
+        let frame = self.frames.get(index).ok_or_else(|| {
+            ChafaError::InvalidInput(format!(
+                "Frame index {} is out of range for {} frames",
+                index,
+                self.frames.len()
+            ))
+        })?;
+        Frame::new(
+            &frame.pixels,
+            misc::PixelType::RGBA8Unassociated,
+            self.width,
+            self.height,
+            self.width * 4,
+        )
+    }
+}
+
+/// Opens a file for one of the decoders, buffered since they all perform many small reads.
+#[cfg(any(feature = "apng", feature = "gif", feature = "webp"))]
+fn open_file(path: &std::path::Path) -> Result<std::io::BufReader<std::fs::File>, ChafaError> {
+    std::fs::File::open(path)
+        .map(std::io::BufReader::new)
+        .map_err(|e| ChafaError::InvalidInput(format!("Failed to open {}: {}", path.display(), e)))
+}
@@ -0,0 +1,134 @@
+use crate::{Animation, ChafaError};
+use png::{BlendOp, ColorType, Decoder, DisposeOp, Transformations};
+use std::io::{BufRead, Seek};
+use std::time::Duration;
+
+impl Animation {
+    /// Decodes a PNG, compositing the frames of an APNG over each other according to their blend and dispose operations. Plain PNGs yield a single frame with no delay.
+    ///
+    /// A default image that isn't part of the animation is skipped. Areas no frame has drawn on are transparent. The loop count is taken from the acTL chunk.
+    /// # Parameters:
+    /// --- `reader`: Source of the PNG data;
+    pub fn from_apng<R: BufRead + Seek>(reader: R) -> Result<Self, ChafaError> {
+        let mut decoder = Decoder::new(reader);
+        decoder
+            .set_transformations(Transformations::normalize_to_color8() | Transformations::ALPHA);
+        let mut reader = decoder.read_info().map_err(png_error)?;
+        #[cfg(feature = "icc")]
+        let icc_profile = reader.info().icc_profile.as_deref().map(<[u8]>::to_vec);
+
+        let (width, height) = (reader.info().width as usize, reader.info().height as usize);
+        let mut animation = Animation::new(width as i32, height as i32)?;
+        let size = reader.output_buffer_size().ok_or_else(|| {
+            ChafaError::InvalidInput(format!("PNG of {}x{} is too large", width, height))
+        })?;
+        let mut buf = vec![0u8; size];
+
+        let Some(control) = reader.info().animation_control else {
+            let info = reader.next_frame(&mut buf).map_err(png_error)?;
+            animation.push_frame(to_rgba(&buf, info.color_type), Duration::ZERO)?;
+            #[cfg(feature = "icc")]
+            animation.apply_embedded_profile(icc_profile.as_deref());
+            return Ok(animation);
+        };
+        animation.set_loop_count(match control.num_plays {
+            0 => None,
+            plays => Some(plays),
+        });
+        if reader.info().frame_control.is_none() {
+            reader.next_frame(&mut buf).map_err(png_error)?;
+        }
+
+        let mut screen = vec![0u8; width * height * 4];
+        for index in 0..control.num_frames {
+            let info = reader.next_frame(&mut buf).map_err(png_error)?;
+            let fc = reader.info().frame_control.ok_or_else(|| {
+                ChafaError::InvalidInput(String::from("APNG frame lacks an fcTL chunk"))
+            })?;
+            let left = fc.x_offset as usize;
+            let top = fc.y_offset as usize;
+            let columns = (fc.width as usize).min(width.saturating_sub(left));
+            let rows = (fc.height as usize).min(height.saturating_sub(top));
+            let pixels = to_rgba(&buf[..info.buffer_size()], info.color_type);
+            let previous = (fc.dispose_op == DisposeOp::Previous).then(|| screen.clone());
+
+            for y in 0..rows {
+                for x in 0..columns {
+                    let src = (y * fc.width as usize + x) * 4;
+                    let dst = ((top + y) * width + left + x) * 4;
+                    let px = &pixels[src..src + 4];
+                    match fc.blend_op {
+                        BlendOp::Source => screen[dst..dst + 4].copy_from_slice(px),
+                        BlendOp::Over => blend_over(&mut screen[dst..dst + 4], px),
+                    }
+                }
+            }
+
+            // A zero denominator means hundredths of a second.
+            let den = if fc.delay_den == 0 { 100 } else { fc.delay_den };
+            animation.push_frame(
+                screen.clone(),
+                Duration::from_secs_f64(f64::from(fc.delay_num) / f64::from(den)),
+            )?;
+
+            match fc.dispose_op {
+                // The first frame has nothing to go back to, so it's cleared instead.
+                DisposeOp::Previous if index > 0 => {
+                    if let Some(previous) = previous {
+                        screen = previous;
+                    }
+                }
+                DisposeOp::Background | DisposeOp::Previous => {
+                    for y in 0..rows {
+                        let start = ((top + y) * width + left) * 4;
+                        screen[start..start + columns * 4].fill(0);
+                    }
+                }
+                DisposeOp::None => {}
+            }
+        }
+        #[cfg(feature = "icc")]
+        animation.apply_embedded_profile(icc_profile.as_deref());
+        Ok(animation)
+    }
+
+    /// Decodes a PNG or APNG file. See `from_apng()`.
+    pub fn from_apng_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ChafaError> {
+        Self::from_apng(super::open_file(path.as_ref())?)
+    }
+}
+
+/// Converts the decoder's 8-bit output to RGBA.
+fn to_rgba(buf: &[u8], color_type: ColorType) -> Vec<u8> {
+    match color_type {
+        ColorType::Rgba => buf.to_vec(),
+        ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|px| [px[0], px[1], px[2], 0xff])
+            .collect(),
+        ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|px| [px[0], px[0], px[0], px[1]])
+            .collect(),
+        _ => buf.iter().flat_map(|v| [*v, *v, *v, 0xff]).collect(),
+    }
+}
+
+/// Draws an unassociated RGBA pixel over another one.
+fn blend_over(dst: &mut [u8], src: &[u8]) {
+    let sa = src[3] as u32;
+    let da = dst[3] as u32 * (255 - sa) / 255;
+    let alpha = sa + da;
+    if alpha == 0 {
+        dst.fill(0);
+        return;
+    }
+    for i in 0..3 {
+        dst[i] = ((src[i] as u32 * sa + dst[i] as u32 * da + alpha / 2) / alpha) as u8;
+    }
+    dst[3] = alpha as u8;
+}
+
+fn png_error(e: png::DecodingError) -> ChafaError {
+    ChafaError::InvalidInput(format!("Failed to decode PNG: {}", e))
+}
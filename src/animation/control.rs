@@ -0,0 +1,162 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// How many times a `Player` goes through the frames of an animation. Defaults to `Times(1)`, a single pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Repeat {
+    /// As many times as the animation's loop count says, which decoders take from the file, e.g. a GIF's NETSCAPE2.0 extension. Most animated GIFs loop forever.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "from-animation", alias = "animation")
+    )]
+    FromAnimation,
+    /// A fixed number of times.
+    Times(u32),
+    /// Until stopped with `PlaybackControl::stop()`.
+    Forever,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat::Times(1)
+    }
+}
+
+/// Pauses, seeks or stops a `Player` while it plays, from another thread. Get one with `Player::control()`; clones control the same player.
+///
+/// Pausing and seeking take effect between frames. Stopping and seeking also cut short the delay of the frame being shown.
+#[derive(Clone, Debug, Default)]
+pub struct PlaybackControl {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+    // Wakes `Player::play_async()`, which can't block on the condvar.
+    #[cfg(feature = "async")]
+    notify: ::tokio::sync::Notify,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    paused: bool,
+    stopped: bool,
+    seek: Option<usize>,
+}
+
+/// What playback should do before showing the next frame.
+pub(crate) enum Step {
+    Continue,
+    Seek(usize),
+    Stop,
+}
+
+impl PlaybackControl {
+    /// Holds playback at the current frame until `resume()` is called.
+    pub fn pause(&self) {
+        self.update(|state| state.paused = true);
+    }
+
+    /// Continues paused playback.
+    pub fn resume(&self) {
+        self.update(|state| state.paused = false);
+    }
+
+    /// Pauses playback if it is running and resumes it if it is paused.
+    pub fn toggle_pause(&self) {
+        self.update(|state| state.paused = !state.paused);
+    }
+
+    /// Returns `true` if playback is paused.
+    pub fn is_paused(&self) -> bool {
+        self.lock().paused
+    }
+
+    /// Continues playback from the frame at index. Indexes past the end go to the last frame.
+    pub fn seek(&self, index: usize) {
+        self.update(|state| state.seek = Some(index));
+    }
+
+    /// Ends playback; the player returns as soon as it notices. A stopped control stays stopped, so a player can't be restarted with it.
+    pub fn stop(&self) {
+        self.update(|state| state.stopped = true);
+    }
+
+    /// Returns `true` if playback was stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.lock().stopped
+    }
+
+    /// Blocks while paused, then returns what to do next.
+    pub(crate) fn next_step(&self) -> Step {
+        let state = self
+            .shared
+            .condvar
+            .wait_while(self.lock(), |state| state.paused && !state.stopped)
+            .unwrap_or_else(|e| e.into_inner());
+        Self::step(state)
+    }
+
+    /// Sleeps for delay, waking early if playback is stopped or seeks.
+    pub(crate) fn sleep(&self, delay: Duration) {
+        let _ = self
+            .shared
+            .condvar
+            .wait_timeout_while(self.lock(), delay, |state| {
+                !state.stopped && state.seek.is_none()
+            });
+    }
+
+    /// Waits while paused without blocking the thread, then returns what to do next.
+    #[cfg(feature = "async")]
+    pub(crate) async fn next_step_async(&self) -> Step {
+        self.wait_until(|state| !state.paused || state.stopped)
+            .await;
+        Self::step(self.lock())
+    }
+
+    /// Sleeps for delay without blocking the thread, waking early if playback is stopped or seeks.
+    #[cfg(feature = "async")]
+    pub(crate) async fn sleep_async(&self, delay: Duration) {
+        let wake = self.wait_until(|state| state.stopped || state.seek.is_some());
+        let _ = ::tokio::time::timeout(delay, wake).await;
+    }
+
+    #[cfg(feature = "async")]
+    async fn wait_until(&self, ready: impl Fn(&State) -> bool) {
+        loop {
+            // Registered before looking at the state, so an update in between still wakes it.
+            let mut notified = std::pin::pin!(self.shared.notify.notified());
+            notified.as_mut().enable();
+            if ready(&self.lock()) {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    fn step(mut state: MutexGuard<'_, State>) -> Step {
+        if state.stopped {
+            Step::Stop
+        } else if let Some(index) = state.seek.take() {
+            Step::Seek(index)
+        } else {
+            Step::Continue
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut State)) {
+        f(&mut self.lock());
+        self.shared.condvar.notify_all();
+        #[cfg(feature = "async")]
+        self.shared.notify.notify_waiters();
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{Animation, ChafaError, Frame, FrameSource, PixelType};
+use ::gif::{ColorOutput, DecodeOptions, DisposalMethod, Repeat};
+use std::io::Read;
+use std::time::Duration;
+
+/// Delay browsers substitute for frames that ask for 10 ms or less, which would otherwise spin as fast as possible.
+const MIN_DELAY_FALLBACK: Duration = Duration::from_millis(100);
+
+impl Animation {
+    /// Decodes a GIF, compositing each frame over the ones before it according to their disposal methods, the way browsers show them.
+    ///
+    /// Areas that no frame has drawn on, and areas restored to the background, are transparent. Frames asking for a delay of 10 ms or less get 100 ms instead, as in browsers. The loop count is taken from the NETSCAPE2.0 extension. To show frames while the rest are still decoding, use `GifFrames` instead.
+    /// # Parameters:
+    /// --- `reader`: Source of the GIF data;
+    pub fn from_gif<R: Read>(reader: R) -> Result<Self, ChafaError> {
+        let mut frames = GifFrames::new(reader)?;
+        let mut animation = Animation::new(frames.width(), frames.height())?;
+        animation.set_loop_count(frames.loop_count());
+        #[cfg(feature = "icc")]
+        animation.set_embedded_profile(frames.embedded_profile().clone());
+        while let Some((pixels, delay)) = frames.next_pixels()? {
+            animation.push_frame(pixels, delay)?;
+        }
+
+        if animation.is_empty() {
+            return Err(ChafaError::InvalidInput(String::from(
+                "GIF contains no frames",
+            )));
+        }
+        Ok(animation)
+    }
+
+    /// Decodes a GIF file. See `from_gif()`.
+    pub fn from_gif_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ChafaError> {
+        Self::from_gif(super::open_file(path.as_ref())?)
+    }
+}
+
+/// Decodes a GIF one frame at a time, compositing frames as `Animation::from_gif()` does. Long or endless GIFs can be played this way without holding every frame in memory.
+pub struct GifFrames<R: Read> {
+    decoder: ::gif::Decoder<R>,
+    width: usize,
+    height: usize,
+    screen: Vec<u8>,
+    #[cfg(feature = "icc")]
+    srgb: Option<super::icc::SrgbTransform>,
+    #[cfg(feature = "icc")]
+    embedded_profile: super::EmbeddedProfile,
+}
+
+impl<R: Read> GifFrames<R> {
+    /// Reads the GIF header.
+    /// # Parameters:
+    /// --- `reader`: Source of the GIF data;
+    pub fn new(reader: R) -> Result<Self, ChafaError> {
+        let mut options = DecodeOptions::new();
+        options.set_color_output(ColorOutput::RGBA);
+        let decoder = options.read_info(reader).map_err(gif_error)?;
+        let (width, height) = (decoder.width() as usize, decoder.height() as usize);
+        if width == 0 || height == 0 {
+            return Err(ChafaError::InvalidInput(format!(
+                "GIF size must be positive, got {}x{}",
+                width, height
+            )));
+        }
+        #[cfg(feature = "icc")]
+        let (srgb, embedded_profile) = super::icc::SrgbTransform::embedded(decoder.icc_profile());
+        Ok(Self {
+            #[cfg(feature = "icc")]
+            srgb,
+            #[cfg(feature = "icc")]
+            embedded_profile,
+            decoder,
+            width,
+            height,
+            screen: vec![0u8; width * height * 4],
+        })
+    }
+
+    /// Tells whether the GIF has an embedded ICC profile that frames are converted from. See `Animation::embedded_profile()`.
+    #[cfg(feature = "icc")]
+    pub fn embedded_profile(&self) -> &super::EmbeddedProfile {
+        &self.embedded_profile
+    }
+
+    /// Width of the GIF's logical screen, in pixels.
+    pub fn width(&self) -> i32 {
+        self.width as i32
+    }
+
+    /// Height of the GIF's logical screen, in pixels.
+    pub fn height(&self) -> i32 {
+        self.height as i32
+    }
+
+    /// Returns how many times the GIF is meant to play, or None if it loops forever.
+    pub fn loop_count(&self) -> Option<u32> {
+        match self.decoder.repeat() {
+            Repeat::Infinite => None,
+            Repeat::Finite(repeats) => Some(u32::from(repeats) + 1),
+        }
+    }
+
+    /// Decodes the next frame.
+    /// # Returns:
+    /// The composited frame in RGBA8Unassociated, with rows of width * 4 bytes, and its delay, or None after the last frame.
+    pub fn next_pixels(&mut self) -> Result<Option<(Vec<u8>, Duration)>, ChafaError> {
+        let (width, height) = (self.width, self.height);
+        let Some(frame) = self.decoder.read_next_frame().map_err(gif_error)? else {
+            return Ok(None);
+        };
+        let left = frame.left as usize;
+        let top = frame.top as usize;
+        // Frames may reach past the logical screen; the excess is clipped.
+        let columns = (frame.width as usize).min(width.saturating_sub(left));
+        let rows = (frame.height as usize).min(height.saturating_sub(top));
+        let previous = (frame.dispose == DisposalMethod::Previous).then(|| self.screen.clone());
+
+        for y in 0..rows {
+            for x in 0..columns {
+                let src = (y * frame.width as usize + x) * 4;
+                let px = &frame.buffer[src..src + 4];
+                if px[3] != 0 {
+                    let dst = ((top + y) * width + left + x) * 4;
+                    self.screen[dst..dst + 4].copy_from_slice(px);
+                }
+            }
+        }
+
+        let delay = if frame.delay <= 1 {
+            MIN_DELAY_FALLBACK
+        } else {
+            Duration::from_millis(u64::from(frame.delay) * 10)
+        };
+        #[allow(unused_mut)]
+        let mut pixels = self.screen.clone();
+        #[cfg(feature = "icc")]
+        if let Some(srgb) = &self.srgb {
+            srgb.apply(&mut pixels);
+        }
+
+        match frame.dispose {
+            DisposalMethod::Background => {
+                for y in 0..rows {
+                    let start = ((top + y) * width + left) * 4;
+                    self.screen[start..start + columns * 4].fill(0);
+                }
+            }
+            DisposalMethod::Previous => {
+                if let Some(previous) = previous {
+                    self.screen = previous;
+                }
+            }
+            _ => {}
+        }
+        Ok(Some((pixels, delay)))
+    }
+}
+
+impl<R: Read> FrameSource for GifFrames<R> {
+    fn next_frame(&mut self) -> Result<Option<(Frame, Duration)>, ChafaError> {
+        let Some((pixels, delay)) = self.next_pixels()? else {
+            return Ok(None);
+        };
+        let frame = Frame::new(
+            &pixels,
+            PixelType::RGBA8Unassociated,
+            self.width(),
+            self.height(),
+            self.width() * 4,
+        )?;
+        Ok(Some((frame, delay)))
+    }
+}
+
+fn gif_error(e: ::gif::DecodingError) -> ChafaError {
+    ChafaError::InvalidInput(format!("Failed to decode GIF: {}", e))
+}
@@ -0,0 +1,80 @@
+use crate::{Animation, ChafaError};
+use qcms::{DataType, Intent, Profile, Transform};
+
+/// What became of the ICC profile embedded in a decoded image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EmbeddedProfile {
+    /// The image had no profile, so its colors are taken as sRGB.
+    None,
+    /// The colors were converted from the profile to sRGB.
+    Converted,
+    /// The profile couldn't be read or converted from, so the colors are left as stored; carries the reason.
+    Unusable(ChafaError),
+}
+
+impl Animation {
+    /// Converts every frame from the color space of an ICC profile to sRGB, which is what terminals assume. Photos in wide gamut spaces such as Display P3 or Adobe RGB otherwise look washed out.
+    ///
+    /// The decoders already do this for embedded profiles; see `embedded_profile()`.
+    /// # Parameters:
+    /// --- `icc_profile`: The ICC profile the frames' pixels are in;
+    pub fn convert_to_srgb(&mut self, icc_profile: &[u8]) -> Result<(), ChafaError> {
+        let transform = SrgbTransform::new(icc_profile)?;
+        for frame in std::sync::Arc::make_mut(&mut self.frames) {
+            transform.apply(&mut frame.pixels);
+        }
+        Ok(())
+    }
+
+    /// Tells whether the decoder found an ICC profile embedded in the image, and whether the frames were converted from it to sRGB. Decoding doesn't fail over a profile that can't be used; the colors are left as stored instead, and the reason is kept here.
+    pub fn embedded_profile(&self) -> &EmbeddedProfile {
+        &self.embedded_profile
+    }
+
+    /// Records the profile status of a decoder that converted the frames itself.
+    #[cfg(any(feature = "gif", feature = "webp"))]
+    pub(crate) fn set_embedded_profile(&mut self, embedded_profile: EmbeddedProfile) {
+        self.embedded_profile = embedded_profile;
+    }
+
+    /// Converts to sRGB if a decoder found an embedded profile, recording the outcome for `embedded_profile()`.
+    #[cfg(any(feature = "apng", feature = "jpeg", feature = "bmp"))]
+    pub(crate) fn apply_embedded_profile(&mut self, icc_profile: Option<&[u8]>) {
+        self.embedded_profile = match icc_profile.map(|p| self.convert_to_srgb(p)) {
+            None => EmbeddedProfile::None,
+            Some(Ok(())) => EmbeddedProfile::Converted,
+            Some(Err(e)) => EmbeddedProfile::Unusable(e),
+        };
+    }
+}
+
+/// Converts RGBA8 pixels from the color space of an ICC profile to sRGB, for decoders that hand out frames one at a time.
+pub(crate) struct SrgbTransform(Transform);
+
+impl SrgbTransform {
+    pub(crate) fn new(icc_profile: &[u8]) -> Result<Self, ChafaError> {
+        let input = Profile::new_from_slice(icc_profile, false)
+            .ok_or_else(|| ChafaError::InvalidInput(String::from("Invalid ICC profile")))?;
+        let mut output = Profile::new_sRGB();
+        output.precache_output_transform();
+        Transform::new(&input, &output, DataType::RGBA8, Intent::Perceptual)
+            .map(Self)
+            .ok_or_else(|| {
+                ChafaError::InvalidInput(String::from("ICC profile can't be converted to sRGB"))
+            })
+    }
+
+    /// Like `new()`, for embedded profiles: a profile that is missing or can't be used gives no transform, leaving the colors as stored, along with the status to report.
+    #[cfg(any(feature = "gif", feature = "webp"))]
+    pub(crate) fn embedded(icc_profile: Option<&[u8]>) -> (Option<Self>, EmbeddedProfile) {
+        match icc_profile.map(Self::new) {
+            None => (None, EmbeddedProfile::None),
+            Some(Ok(transform)) => (Some(transform), EmbeddedProfile::Converted),
+            Some(Err(e)) => (None, EmbeddedProfile::Unusable(e)),
+        }
+    }
+
+    pub(crate) fn apply(&self, pixels: &mut [u8]) {
+        self.0.apply(pixels);
+    }
+}
@@ -0,0 +1,188 @@
+/*
+ * https://sw.kovidgoyal.net/kitty/graphics-protocol/#animation
+ */
+
+use crate::Animation;
+use crate::canvas::Passthrough;
+use crate::term::wrap_passthrough;
+
+/// Largest amount of base64 data kitty accepts in one escape code.
+const CHUNK_MAX: usize = 4096;
+
+/// Encodes an animation for kitty's graphics protocol so the terminal plays it by itself.
+///
+/// The first frame is transmitted in full. Every later frame is sent as the rectangle that changed since the frame before it, composed onto a copy of that frame, so long animations with small moving parts take a fraction of the bandwidth of retransmitting whole images. Once all frames are loaded, the terminal is told to play them with their delays and the animation's loop count.
+pub struct KittyAnimation {
+    /// ID the image is stored under in the terminal. Reusing an ID replaces the image.
+    pub image_id: u32,
+    /// Width of the placement, in character cells.
+    pub columns: i32,
+    /// Height of the placement, in character cells.
+    pub rows: i32,
+    /// Passthrough guards to wrap the escape codes in.
+    pub passthrough: Passthrough,
+}
+
+impl KittyAnimation {
+    /// Creates an encoder that places animations over columns x rows cells, stored under image_id.
+    pub fn new(image_id: u32, columns: i32, rows: i32) -> Self {
+        Self {
+            image_id,
+            columns,
+            rows,
+            passthrough: Passthrough::None,
+        }
+    }
+
+    /// Sets the passthrough guards the escape codes are wrapped in, for when the terminal is behind a multiplexer.
+    pub fn passthrough(mut self, passthrough: Passthrough) -> Self {
+        self.passthrough = passthrough;
+        self
+    }
+
+    /// Builds the escape codes that upload animation, display it at the cursor position and start playback. The cursor ends up after the placement, as with still images.
+    pub fn encode(&self, animation: &Animation) -> Vec<u8> {
+        let mut out = Vec::new();
+        let frames = animation.frames();
+        let Some(first) = frames.first() else {
+            return out;
+        };
+        let (width, height) = (animation.width() as usize, animation.height() as usize);
+
+        self.push_command(
+            &mut out,
+            &format!(
+                "a=T,i={},f=32,s={},v={},c={},r={},q=2",
+                self.image_id, width, height, self.columns, self.rows
+            ),
+            &first.pixels,
+        );
+        self.push_command(
+            &mut out,
+            &format!("a=a,i={},r=1,z={},q=2", self.image_id, gap_ms(first.delay)),
+            &[],
+        );
+
+        for (index, pair) in frames.windows(2).enumerate() {
+            let (previous, frame) = (&pair[0], &pair[1]);
+            // An unchanged frame still needs an entry of its own for its delay, so it copies a single pixel.
+            let (x, y, w, h) = changed_rect(&previous.pixels, &frame.pixels, width, height)
+                .unwrap_or((0, 0, 1, 1));
+            let mut region = Vec::with_capacity(w * h * 4);
+            for row in y..y + h {
+                let start = (row * width + x) * 4;
+                region.extend_from_slice(&frame.pixels[start..start + w * 4]);
+            }
+            self.push_command(
+                &mut out,
+                &format!(
+                    "a=f,i={},f=32,x={},y={},s={},v={},c={},X=1,z={},q=2",
+                    self.image_id,
+                    x,
+                    y,
+                    w,
+                    h,
+                    index + 1,
+                    gap_ms(frame.delay)
+                ),
+                &region,
+            );
+        }
+
+        // Kitty counts one more than the number of plays, with 1 meaning forever.
+        let loops = animation
+            .loop_count()
+            .map_or(1, |count| count.max(1).saturating_add(1));
+        self.push_command(
+            &mut out,
+            &format!("a=a,i={},s=3,v={},q=2", self.image_id, loops),
+            &[],
+        );
+        out
+    }
+
+    /// Builds the escape code that removes the animation from the screen and frees its frames in the terminal.
+    pub fn delete(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.push_command(&mut out, &format!("a=d,d=I,i={},q=2", self.image_id), &[]);
+        out
+    }
+
+    /// Appends a graphics command, splitting its payload into chunks as the protocol requires.
+    fn push_command(&self, out: &mut Vec<u8>, control: &str, payload: &[u8]) {
+        let encoded = base64(payload);
+        let mut chunks = encoded.chunks(CHUNK_MAX).peekable();
+        let mut command = Vec::with_capacity(encoded.len() + control.len() + 16);
+
+        command.extend_from_slice(b"\x1b_G");
+        command.extend_from_slice(control.as_bytes());
+        if chunks.peek().is_none() {
+            command.extend_from_slice(b"\x1b\\");
+        }
+        let mut first = true;
+        while let Some(chunk) = chunks.next() {
+            let more = if chunks.peek().is_some() { 1 } else { 0 };
+            if first {
+                command.extend_from_slice(format!(",m={};", more).as_bytes());
+                first = false;
+            } else {
+                command.extend_from_slice(format!("\x1b_Gm={};", more).as_bytes());
+            }
+            command.extend_from_slice(chunk);
+            command.extend_from_slice(b"\x1b\\");
+        }
+        out.extend(wrap_passthrough(&command, self.passthrough));
+    }
+}
+
+/// Kitty treats a gap of 0 as "use the default" and negative ones as "skip this frame", so delays are kept at 1 ms or more.
+fn gap_ms(delay: std::time::Duration) -> u128 {
+    delay.as_millis().max(1)
+}
+
+/// Returns the smallest rectangle (x, y, width, height) holding every pixel that differs between two frames, or None if they are identical.
+fn changed_rect(
+    a: &[u8],
+    b: &[u8],
+    width: usize,
+    height: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let (mut x0, mut y0, mut x1, mut y1) = (width, height, 0, 0);
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) * 4;
+            if a[i..i + 4] != b[i..i + 4] {
+                x0 = x0.min(x);
+                y0 = y0.min(y);
+                x1 = x1.max(x + 1);
+                y1 = y1.max(y + 1);
+            }
+        }
+    }
+    (x1 > x0).then(|| (x0, y0, x1 - x0, y1 - y0))
+}
+
+/// Encodes data as standard base64 with padding.
+fn base64(data: &[u8]) -> Vec<u8> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = Vec::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        out.push(ALPHABET[(n >> 18) as usize & 63]);
+        out.push(ALPHABET[(n >> 12) as usize & 63]);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6) as usize & 63]
+        } else {
+            b'='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[n as usize & 63]
+        } else {
+            b'='
+        });
+    }
+    out
+}
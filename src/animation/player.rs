@@ -0,0 +1,443 @@
+use crate::animation::control::Step;
+#[cfg(feature = "async")]
+use crate::async_render::{Owned, blocking};
+use crate::canvas::{
+    Canvas, Config, Passthrough, PixelMode, PrintOptions, Reposition, SYNC_UPDATE_BEGIN,
+    SYNC_UPDATE_END,
+};
+use crate::term::{Info, Seq};
+use crate::{
+    Animation, ChafaError, FrameSource, Image, KittyAnimation, Placement, PlaybackControl, Repeat,
+    misc,
+};
+use std::io::Write;
+#[cfg(feature = "async")]
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Draws the frames of an animation on a canvas and prints them in place, keeping to each frame's delay.
+pub struct Player<'a> {
+    animation: &'a Animation,
+    canvas: Canvas,
+    repeat: Repeat,
+    ping_pong: bool,
+    control: PlaybackControl,
+}
+
+impl<'a> Player<'a> {
+    /// Creates a player drawing animation on a canvas made with config. The canvas geometry determines the size of the output; the frames are scaled to fit it.
+    /// # Parameters:
+    /// --- `animation`: The animation to play;
+    /// --- `config`: Configuration of the canvas frames are drawn on;
+    pub fn new(animation: &'a Animation, config: &Config) -> Result<Self, ChafaError> {
+        let canvas = Canvas::new(config)?;
+        Ok(Self {
+            animation,
+            canvas,
+            repeat: Repeat::default(),
+            ping_pong: false,
+            control: PlaybackControl::default(),
+        })
+    }
+
+    /// Sets how many times the frames are played. Defaults to a single pass; `Repeat::FromAnimation` follows the animation's own loop count instead.
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Sets whether to play the frames forwards and then backwards, instead of jumping back to the first one after the last. Each pass then goes there and back, without showing the first and last frames twice.
+    pub fn ping_pong(mut self, ping_pong: bool) -> Self {
+        self.ping_pong = ping_pong;
+        self
+    }
+
+    /// Returns a handle to pause, seek or stop playback from another thread.
+    pub fn control(&self) -> PlaybackControl {
+        self.control.clone()
+    }
+
+    /// Returns the canvas frames are drawn on.
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Draws the frame at index on the canvas and returns its printable output.
+    /// # Parameters:
+    /// --- `index`: Index of the frame to draw;
+    /// --- `term_info`: Terminal to format for, or `None` for fallback;
+    /// --- `options`: Output options to apply, e.g. `PrintOptions::animation()`;
+    pub fn render(
+        &self,
+        index: usize,
+        term_info: Option<&Info>,
+        options: &PrintOptions,
+    ) -> Result<String, ChafaError> {
+        let frame = self.animation.frames().get(index).ok_or_else(|| {
+            ChafaError::InvalidInput(format!(
+                "Frame index {} is out of range for {} frames",
+                index,
+                self.animation.len()
+            ))
+        })?;
+        self.canvas.set_pixels(
+            &frame.pixels,
+            misc::PixelType::RGBA8Unassociated,
+            self.animation.width(),
+            self.animation.height(),
+            self.animation.width() * 4,
+        )?;
+        self.canvas.create_string_with(term_info, options)
+    }
+
+    /// Plays the frames as many times as `repeat()` says, printing each over the previous one and sleeping for its delay. Time spent rendering counts towards the delay. The first frame is printed at the cursor position.
+    ///
+    /// By default the frames are played once. With `Repeat::FromAnimation` or `Repeat::Forever`, this doesn't return for animations that loop forever until stopped through `control()`.
+    /// # Parameters:
+    /// --- `term_info`: Terminal to format for, or `None` for fallback;
+    /// --- `writer`: Destination of the output, usually stdout;
+    pub fn play<W: Write>(&self, term_info: Option<&Info>, writer: &mut W) -> std::io::Result<()> {
+        let mut options = PrintOptions::animation().reposition(Reposition::None);
+        self.run(|index| {
+            let out = self
+                .render(index, term_info, &options)
+                .map_err(std::io::Error::other)?;
+            writer.write_all(out.as_bytes())?;
+            writer.flush()?;
+            options = PrintOptions::animation();
+            Ok(())
+        })
+    }
+
+    /// Plays the frames as `play()` does without blocking the async runtime. Frames are drawn on tokio's blocking thread pool, while pauses and delays are waited out on the runtime's timer, which must be enabled; the output is written to an `AsyncWrite`.
+    ///
+    /// Frames are drawn on a canvas of their own, made with the same configuration as `canvas()`.
+    /// # Parameters:
+    /// --- `term_info`: Terminal to format for, or `None` for fallback;
+    /// --- `writer`: Destination of the output, e.g. `tokio::io::stdout()`;
+    #[cfg(feature = "async")]
+    pub async fn play_async<W: ::tokio::io::AsyncWrite + Unpin>(
+        &self,
+        term_info: Option<&Info>,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        use ::tokio::io::AsyncWriteExt;
+
+        let config = self.canvas.config().map_err(std::io::Error::other)?;
+        let canvas = Canvas::new(&config).map_err(std::io::Error::other)?;
+        let mut job = Owned::new(canvas, term_info.cloned());
+        let layout = (
+            self.animation.width(),
+            self.animation.height(),
+            self.animation.width() * 4,
+        );
+        let mut options = PrintOptions::animation().reposition(Reposition::None);
+
+        let mut schedule = self.schedule();
+        loop {
+            match self.control.next_step_async().await {
+                Step::Stop => break,
+                Step::Seek(index) => schedule.seek(index),
+                Step::Continue => {}
+            }
+            let Some(index) = schedule.current() else {
+                break;
+            };
+            let start = Instant::now();
+            let frames = Arc::clone(&self.animation.frames);
+            let frame_options = options.clone();
+            let out;
+            (job, out) = blocking(move || {
+                let (canvas, term_info) = job.into_inner();
+                canvas
+                    .set_pixels(
+                        &frames[index].pixels,
+                        misc::PixelType::RGBA8Unassociated,
+                        layout.0,
+                        layout.1,
+                        layout.2,
+                    )
+                    .map_err(std::io::Error::other)?;
+                let out = canvas
+                    .create_string_with(term_info.as_ref(), &frame_options)
+                    .map_err(std::io::Error::other)?;
+                Ok((Owned::new(canvas, term_info), out))
+            })
+            .await?;
+            writer.write_all(out.as_bytes()).await?;
+            writer.flush().await?;
+            options = PrintOptions::animation();
+
+            let delay = self.animation.frames()[index].delay;
+            self.control
+                .sleep_async(delay.saturating_sub(start.elapsed()))
+                .await;
+            schedule.advance();
+        }
+        Ok(())
+    }
+
+    /// Shows frames in playback order, keeping to their delays and following the control, until the repeats run out or playback is stopped.
+    fn run(&self, mut show: impl FnMut(usize) -> std::io::Result<()>) -> std::io::Result<()> {
+        let mut schedule = self.schedule();
+        loop {
+            match self.control.next_step() {
+                Step::Stop => break,
+                Step::Seek(index) => schedule.seek(index),
+                Step::Continue => {}
+            }
+            let Some(index) = schedule.current() else {
+                break;
+            };
+            let start = Instant::now();
+            show(index)?;
+            let delay = self.animation.frames()[index].delay;
+            self.control.sleep(delay.saturating_sub(start.elapsed()));
+            schedule.advance();
+        }
+        Ok(())
+    }
+
+    /// Lays out the playback order of the frames as `repeat()` and `ping_pong()` say.
+    fn schedule(&self) -> Schedule {
+        let len = self.animation.len();
+        let mut order: Vec<usize> = (0..len).collect();
+        if self.ping_pong && len > 2 {
+            order.extend((1..len - 1).rev());
+        }
+        let passes = match self.repeat {
+            Repeat::FromAnimation => self.animation.loop_count(),
+            Repeat::Times(times) => Some(times),
+            Repeat::Forever => None,
+        };
+        Schedule {
+            order,
+            passes,
+            pass: 0,
+            position: 0,
+        }
+    }
+
+    /// Plays frames from a source as they come, until it runs out, printing each over the previous one and sleeping for its delay as `play()` does. Frames are stretched over the canvas, like those of an animation. Playback stops at the first error of the source, which is returned; `ChafaError::Io` errors are returned as they are.
+    /// # Parameters:
+    /// --- `source`: Where to take frames from;
+    /// --- `config`: Configuration of the canvas frames are drawn on;
+    /// --- `term_info`: Terminal to format for, or `None` for fallback;
+    /// --- `writer`: Destination of the output, usually stdout;
+    pub fn play_source<S: FrameSource + ?Sized, W: Write>(
+        source: &mut S,
+        config: &Config,
+        term_info: Option<&Info>,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let canvas = Canvas::new(config).map_err(std::io::Error::other)?;
+        let mut options = PrintOptions::animation().reposition(Reposition::None);
+        while let Some((frame, delay)) = source.next_frame().map_err(|e| match e {
+            ChafaError::Io(e) => e,
+            e => std::io::Error::other(e),
+        })? {
+            let start = Instant::now();
+            let image = Image::new().map_err(std::io::Error::other)?;
+            image.set_frame(frame);
+            canvas.set_placement(Placement::new(image, 0).map_err(std::io::Error::other)?);
+            let out = canvas
+                .create_string_with(term_info, &options)
+                .map_err(std::io::Error::other)?;
+            writer.write_all(out.as_bytes())?;
+            writer.flush()?;
+            options = PrintOptions::animation();
+
+            std::thread::sleep(delay.saturating_sub(start.elapsed()));
+        }
+        Ok(())
+    }
+
+    /// Hands the whole animation to a terminal that speaks kitty's graphics protocol, which then plays it by itself, looping as the animation's loop count says. Frames are uploaded once, as deltas against the frame before them, and placed over the canvas geometry at the cursor position.
+    ///
+    /// Unlike `play()`, this returns as soon as everything is sent. Write `KittyAnimation::delete()` for the same image_id to stop playback and free the frames.
+    /// # Parameters:
+    /// --- `image_id`: ID to store the image under in the terminal;
+    /// --- `passthrough`: Passthrough guards to use, e.g. when running inside tmux;
+    /// --- `writer`: Destination of the output, usually stdout;
+    pub fn play_kitty<W: Write>(
+        &self,
+        image_id: u32,
+        passthrough: Passthrough,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let (columns, rows) = self
+            .canvas
+            .config()
+            .map_err(std::io::Error::other)?
+            .get_geometry();
+        let kitty = KittyAnimation::new(image_id, columns, rows).passthrough(passthrough);
+        writer.write_all(&kitty.encode(self.animation))?;
+        writer.flush()
+    }
+
+    /// Plays the frames as `play()` does on a terminal that shows sixel images, redrawing only the rows of cells that changed since the previous frame. Over slow links this cuts the output of mostly static animations to a fraction, and it avoids the flicker of scrolling.
+    ///
+    /// Frames are scaled to the canvas size in pixels and compared there; each frame then redraws the band of cell rows between the first and last changed one, on a canvas of that height that is kept for reuse. Every band is drawn at an absolute position, and sixel scrolling stays enabled with the cursor advancing to the right of images, so drawing never scrolls the screen. Each frame is wrapped in synchronized update sequences if `Info::supports_synchronized_update()` says the terminal takes them. Afterwards the cursor is left below the animation.
+    ///
+    /// The canvas must be in CHAFA_PIXEL_MODE_SIXELS with its cell geometry set, and term_info must be able to position the cursor.
+    /// # Parameters:
+    /// --- `x`: Column of the top left corner, in character cells;
+    /// --- `y`: Row of the top left corner, in character cells;
+    /// --- `term_info`: Terminal to format for;
+    /// --- `writer`: Destination of the output, usually stdout;
+    pub fn play_sixel<W: Write>(
+        &self,
+        x: u32,
+        y: u32,
+        term_info: &Info,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let config = self.canvas.config().map_err(std::io::Error::other)?;
+        let (columns, rows) = config.get_geometry();
+        let (cell_width, cell_height) = config.get_cell_geometry();
+        if config.get_pixel_mode() != PixelMode::Sixels
+            || cell_width <= 0
+            || cell_height <= 0
+            || !term_info.have_seq(Seq::CursorToPos)
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Chafa -> Sixel playback needs a sixel canvas with a known cell size and a terminal that can position the cursor",
+            ));
+        }
+
+        let (width, height) = (columns * cell_width, rows * cell_height);
+        let rowstride = width as usize * 4;
+        let band_stride = rowstride * cell_height as usize;
+        let sync = term_info.supports_synchronized_update();
+
+        let mut setup = Vec::new();
+        term_info.emit_enable_sixel_scrolling(&mut setup);
+        term_info.emit_set_sixel_advance_right(&mut setup);
+        writer.write_all(&setup)?;
+
+        // Canvases for bands of each height, created as needed.
+        let mut bands: Vec<Option<Canvas>> = (0..rows).map(|_| None).collect();
+        let mut previous: Option<Vec<u8>> = None;
+        self.run(|index| {
+            let pixels = misc::rescale_pixels(
+                &self.animation.frames()[index].pixels,
+                (
+                    self.animation.width(),
+                    self.animation.height(),
+                    self.animation.width() * 4,
+                ),
+                4,
+                width,
+                height,
+            );
+            let changed = match &previous {
+                None => Some((0, rows as usize)),
+                Some(previous) => changed_bands(previous, &pixels, band_stride),
+            };
+
+            if let Some((first, last)) = changed {
+                let count = last - first;
+                let canvas = if count == rows as usize {
+                    &self.canvas
+                } else {
+                    match &mut bands[count] {
+                        Some(canvas) => canvas,
+                        slot => {
+                            let band_config = config.copy().map_err(std::io::Error::other)?;
+                            band_config
+                                .set_geometry(columns, count as i32)
+                                .map_err(std::io::Error::other)?;
+                            slot.insert(Canvas::new(&band_config).map_err(std::io::Error::other)?)
+                        }
+                    }
+                };
+                canvas
+                    .set_pixels(
+                        &pixels[first * band_stride..last * band_stride],
+                        misc::PixelType::RGBA8Unassociated,
+                        width,
+                        count as i32 * cell_height,
+                        rowstride as i32,
+                    )
+                    .map_err(std::io::Error::other)?;
+
+                let mut out = Vec::new();
+                if sync {
+                    out.extend_from_slice(SYNC_UPDATE_BEGIN.as_bytes());
+                }
+                term_info.emit_cursor_to_pos(&mut out, x, y + first as u32);
+                out.extend(
+                    canvas
+                        .create_bytes(Some(term_info))
+                        .map_err(std::io::Error::other)?,
+                );
+                if sync {
+                    out.extend_from_slice(SYNC_UPDATE_END.as_bytes());
+                }
+                writer.write_all(&out)?;
+                writer.flush()?;
+            }
+            previous = Some(pixels);
+            Ok(())
+        })?;
+
+        let mut end = Vec::new();
+        term_info.emit_set_sixel_advance_down(&mut end);
+        term_info.emit_cursor_to_pos(&mut end, x, y + rows as u32);
+        writer.write_all(&end)?;
+        writer.flush()
+    }
+}
+
+/// Where playback is in the order of frames, and how many passes are left.
+struct Schedule {
+    order: Vec<usize>,
+    passes: Option<u32>,
+    pass: u32,
+    position: usize,
+}
+
+impl Schedule {
+    /// Returns the index of the frame to show, or `None` once the passes have run out.
+    fn current(&self) -> Option<usize> {
+        if self.passes.is_some_and(|passes| self.pass >= passes) {
+            return None;
+        }
+        self.order.get(self.position).copied()
+    }
+
+    /// Moves on to the next frame, starting another pass after the last one.
+    fn advance(&mut self) {
+        self.position += 1;
+        if self.position == self.order.len() {
+            self.position = 0;
+            self.pass += 1;
+        }
+    }
+
+    /// Moves to the first appearance of the frame at index in the order. Indexes past the end go to the last frame.
+    fn seek(&mut self, index: usize) {
+        let Some(&last) = self.order.iter().max() else {
+            return;
+        };
+        let index = index.min(last);
+        self.position = self.order.iter().position(|&i| i == index).unwrap_or(0);
+    }
+}
+
+/// Returns the first and one past the last band of cell rows that differ between two frames of the same size, or None if they are identical. This is how `Player::play_sixel()` picks the rows to redraw.
+/// # Parameters:
+/// --- `a`: Pixels of the previous frame;
+/// --- `b`: Pixels of the next frame;
+/// --- `band_stride`: Number of bytes in a band, i.e. the rowstride times the cell height in pixels;
+pub fn changed_bands(a: &[u8], b: &[u8], band_stride: usize) -> Option<(usize, usize)> {
+    let differs = |(a, b): (&[u8], &[u8])| a != b;
+    let mut pairs = a.chunks(band_stride).zip(b.chunks(band_stride));
+    let first = pairs.position(differs)?;
+    let last = a
+        .chunks(band_stride)
+        .zip(b.chunks(band_stride))
+        .rposition(differs)?;
+    Some((first, last + 1))
+}
@@ -0,0 +1,169 @@
+use crate::{ChafaError, Frame, FrameSource, PixelType};
+use std::io::{ErrorKind, Read};
+use std::time::Duration;
+
+/// Layout of uncompressed video frames, named after ffmpeg's pixel formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RawFormat {
+    /// Packed RGB, 8 bits per channel.
+    Rgb24,
+    /// Packed BGR, 8 bits per channel.
+    Bgr24,
+    /// Packed RGBA with unassociated alpha, 8 bits per channel.
+    Rgba,
+    /// Packed BGRA with unassociated alpha, 8 bits per channel.
+    Bgra,
+    /// Planar YUV 4:2:0: a Y plane followed by U and V planes at half resolution.
+    Yuv420p,
+    /// A Y plane followed by a plane of interleaved U and V samples at half resolution.
+    Nv12,
+}
+
+impl RawFormat {
+    /// Looks up a format by its ffmpeg name, as given to `-pix_fmt`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rgb24" => Some(RawFormat::Rgb24),
+            "bgr24" => Some(RawFormat::Bgr24),
+            "rgba" => Some(RawFormat::Rgba),
+            "bgra" => Some(RawFormat::Bgra),
+            "yuv420p" => Some(RawFormat::Yuv420p),
+            "nv12" => Some(RawFormat::Nv12),
+            _ => None,
+        }
+    }
+
+    /// Returns the format's ffmpeg name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RawFormat::Rgb24 => "rgb24",
+            RawFormat::Bgr24 => "bgr24",
+            RawFormat::Rgba => "rgba",
+            RawFormat::Bgra => "bgra",
+            RawFormat::Yuv420p => "yuv420p",
+            RawFormat::Nv12 => "nv12",
+        }
+    }
+
+    /// Returns the number of bytes one frame of width x height pixels takes up, without any padding.
+    pub fn frame_size(&self, width: usize, height: usize) -> usize {
+        let chroma = width.div_ceil(2) * height.div_ceil(2) * 2;
+        match self {
+            RawFormat::Rgb24 | RawFormat::Bgr24 => width * height * 3,
+            RawFormat::Rgba | RawFormat::Bgra => width * height * 4,
+            RawFormat::Yuv420p | RawFormat::Nv12 => width * height + chroma,
+        }
+    }
+}
+
+/// Reads uncompressed frames of a fixed size and rate from any reader, such as stdin. This lets a program play the output of `ffmpeg -i video.mkv -f rawvideo -pix_fmt rgb24 -` without linking to ffmpeg.
+///
+/// The source ends when the reader does, and a partial frame at the end is dropped. Read errors are returned as `ChafaError::Io`.
+pub struct RawVideoSource<R: Read> {
+    reader: R,
+    format: RawFormat,
+    width: i32,
+    height: i32,
+    interval: Duration,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> RawVideoSource<R> {
+    /// Creates a source reading frames from reader.
+    /// # Parameters:
+    /// --- `reader`: Where frames are read from, e.g. `std::io::stdin().lock()`;
+    /// --- `format`: Layout of the frames;
+    /// --- `width`: Width of each frame, in pixels;
+    /// --- `height`: Height of each frame, in pixels;
+    /// --- `fps`: Frame rate to play at;
+    pub fn new(
+        reader: R,
+        format: RawFormat,
+        width: i32,
+        height: i32,
+        fps: f64,
+    ) -> Result<Self, ChafaError> {
+        if width <= 0 || height <= 0 {
+            return Err(ChafaError::InvalidInput(format!(
+                "Raw video size must be positive, got {}x{}",
+                width, height
+            )));
+        }
+        if !(fps.is_finite() && fps > 0.0) {
+            return Err(ChafaError::InvalidInput(format!(
+                "Raw video frame rate must be positive, got {}",
+                fps
+            )));
+        }
+        Ok(Self {
+            reader,
+            format,
+            width,
+            height,
+            interval: Duration::from_secs_f64(1.0 / fps),
+            buf: vec![0; format.frame_size(width as usize, height as usize)],
+        })
+    }
+
+    /// Returns the layout of the frames.
+    pub fn format(&self) -> RawFormat {
+        self.format
+    }
+
+    /// Returns how long each frame is shown.
+    pub fn frame_interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Fills the buffer with the next frame. Returns false at the end of the input.
+    fn read_frame(&mut self) -> Result<bool, ChafaError> {
+        let mut filled = 0;
+        while filled < self.buf.len() {
+            match self.reader.read(&mut self.buf[filled..]) {
+                Ok(0) => return Ok(false),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(ChafaError::Io(e)),
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read> FrameSource for RawVideoSource<R> {
+    fn next_frame(&mut self) -> Result<Option<(Frame, Duration)>, ChafaError> {
+        if !self.read_frame()? {
+            return Ok(None);
+        }
+        let (width, height) = (self.width, self.height);
+        let packed = |pixel_type: PixelType| {
+            let rowstride = width * pixel_type.bytes_per_pixel() as i32;
+            Frame::new(&self.buf, pixel_type, width, height, rowstride)
+        };
+        let luma = width as usize * height as usize;
+        let chroma_width = (width as usize).div_ceil(2);
+        let frame = match self.format {
+            RawFormat::Rgb24 => packed(PixelType::RGB8),
+            RawFormat::Bgr24 => packed(PixelType::BGR8),
+            RawFormat::Rgba => packed(PixelType::RGBA8Unassociated),
+            RawFormat::Bgra => packed(PixelType::BGRA8Unassociated),
+            RawFormat::Yuv420p => {
+                let (y, chroma) = self.buf.split_at(luma);
+                let (u, v) = chroma.split_at(chroma.len() / 2);
+                Frame::from_yuv420(
+                    y,
+                    u,
+                    v,
+                    [width as usize, chroma_width, chroma_width],
+                    width,
+                    height,
+                )
+            }
+            RawFormat::Nv12 => {
+                let (y, uv) = self.buf.split_at(luma);
+                Frame::from_nv12(y, uv, [width as usize, chroma_width * 2], width, height)
+            }
+        };
+        Ok(Some((frame?, self.interval)))
+    }
+}
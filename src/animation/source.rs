@@ -0,0 +1,10 @@
+use crate::{ChafaError, Frame};
+use std::time::Duration;
+
+/// Anything that hands out frames one after the other with their timing: decoders streaming a file (`GifFrames`, `WebPFrames`), video decoders, capture devices or generated content. `Player::play_source()` shows them as they come.
+pub trait FrameSource {
+    /// Produces the next frame along with how long it should be shown.
+    /// # Returns:
+    /// The frame and its delay, None once the source is exhausted, or the error that kept the next frame from being produced.
+    fn next_frame(&mut self) -> Result<Option<(Frame, Duration)>, ChafaError>;
+}
@@ -0,0 +1,136 @@
+use crate::{ChafaError, Frame, FrameSource, PixelType};
+use ffmpeg::format::{Pixel, context::Input};
+use ffmpeg::media::Type;
+use ffmpeg::software::scaling::{Context as Scaler, Flags};
+use ffmpeg::util::frame::Video as VideoFrame;
+use ffmpeg_next as ffmpeg;
+use std::time::Duration;
+
+/// Frame interval to use for streams that don't state their frame rate.
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(40);
+
+/// Decodes the best video stream of a file or network stream with ffmpeg, handing out its frames as RGB8 for `Player::play_source()`. Anything ffmpeg can open works, including URLs and devices.
+pub struct VideoSource {
+    input: Input,
+    stream_index: usize,
+    decoder: ffmpeg::decoder::Video,
+    scaler: Scaler,
+    interval: Duration,
+    flushed: bool,
+}
+
+impl VideoSource {
+    /// Opens a video and sets up decoding of its best video stream.
+    /// # Parameters:
+    /// --- `path`: File name or URL of the video;
+    pub fn open<P: AsRef<std::path::Path> + ?Sized>(path: &P) -> Result<Self, ChafaError> {
+        let error = |e: ffmpeg::Error| {
+            let msg = format!("Failed to open video {}: {}", path.as_ref().display(), e);
+            match e {
+                // Errors of the operating system, e.g. a missing file or a refused connection.
+                ffmpeg::Error::Other { errno } => ChafaError::Io(std::io::Error::new(
+                    std::io::Error::from_raw_os_error(errno).kind(),
+                    msg,
+                )),
+                _ => ChafaError::InvalidInput(msg),
+            }
+        };
+        ffmpeg::init().map_err(error)?;
+        let input = ffmpeg::format::input(path).map_err(error)?;
+        let stream = input.streams().best(Type::Video).ok_or_else(|| {
+            ChafaError::InvalidInput(format!("{} has no video stream", path.as_ref().display()))
+        })?;
+        let stream_index = stream.index();
+        let rate = stream.avg_frame_rate();
+        let interval = if rate.numerator() > 0 && rate.denominator() > 0 {
+            Duration::from_secs_f64(f64::from(rate.denominator()) / f64::from(rate.numerator()))
+        } else {
+            DEFAULT_INTERVAL
+        };
+        let decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+            .and_then(|context| context.decoder().video())
+            .map_err(error)?;
+        let scaler = Scaler::get(
+            decoder.format(),
+            decoder.width(),
+            decoder.height(),
+            Pixel::RGB24,
+            decoder.width(),
+            decoder.height(),
+            Flags::BILINEAR,
+        )
+        .map_err(error)?;
+
+        Ok(Self {
+            input,
+            stream_index,
+            decoder,
+            scaler,
+            interval,
+            flushed: false,
+        })
+    }
+
+    /// Width of the video, in pixels.
+    pub fn width(&self) -> i32 {
+        self.decoder.width() as i32
+    }
+
+    /// Height of the video, in pixels.
+    pub fn height(&self) -> i32 {
+        self.decoder.height() as i32
+    }
+
+    /// Returns how long each frame is shown, from the stream's average frame rate.
+    pub fn frame_interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Decodes the next frame, feeding the decoder packets until it has one.
+    fn decode(&mut self) -> Result<Option<VideoFrame>, ffmpeg::Error> {
+        let mut decoded = VideoFrame::empty();
+        loop {
+            if self.decoder.receive_frame(&mut decoded).is_ok() {
+                return Ok(Some(decoded));
+            }
+            if self.flushed {
+                return Ok(None);
+            }
+            let index = self.stream_index;
+            match self
+                .input
+                .packets()
+                .find(|(stream, _)| stream.index() == index)
+            {
+                Some((_, packet)) => self.decoder.send_packet(&packet)?,
+                None => {
+                    // Drain the frames the decoder still holds.
+                    self.decoder.send_eof()?;
+                    self.flushed = true;
+                }
+            }
+        }
+    }
+}
+
+impl FrameSource for VideoSource {
+    fn next_frame(&mut self) -> Result<Option<(Frame, Duration)>, ChafaError> {
+        let Some(decoded) = self.decode().map_err(decode_error)? else {
+            return Ok(None);
+        };
+        let mut rgb = VideoFrame::empty();
+        self.scaler.run(&decoded, &mut rgb).map_err(decode_error)?;
+        let frame = Frame::new(
+            rgb.data(0),
+            PixelType::RGB8,
+            rgb.width() as i32,
+            rgb.height() as i32,
+            rgb.stride(0) as i32,
+        )?;
+        Ok(Some((frame, self.interval)))
+    }
+}
+
+fn decode_error(e: ffmpeg::Error) -> ChafaError {
+    ChafaError::InvalidInput(format!("Failed to decode video: {}", e))
+}
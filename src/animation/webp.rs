@@ -0,0 +1,155 @@
+use crate::{Animation, ChafaError, Frame, FrameSource, PixelType};
+use image_webp::{LoopCount, WebPDecoder};
+use std::io::{BufRead, Seek};
+use std::time::Duration;
+
+impl Animation {
+    /// Decodes a WebP image. Animated files yield every frame, already composited by the decoder; still images yield a single frame with no delay.
+    ///
+    /// The loop count is taken from the ANIM chunk. To show frames while the rest are still decoding, use `WebPFrames` instead.
+    /// # Parameters:
+    /// --- `reader`: Source of the WebP data;
+    pub fn from_webp<R: BufRead + Seek>(reader: R) -> Result<Self, ChafaError> {
+        let mut frames = WebPFrames::new(reader)?;
+        let mut animation = Animation::new(frames.width(), frames.height())?;
+        animation.set_loop_count(frames.loop_count());
+        #[cfg(feature = "icc")]
+        animation.set_embedded_profile(frames.embedded_profile().clone());
+        while let Some((pixels, delay)) = frames.next_pixels()? {
+            animation.push_frame(pixels, delay)?;
+        }
+        Ok(animation)
+    }
+
+    /// Decodes a WebP file. See `from_webp()`.
+    pub fn from_webp_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ChafaError> {
+        Self::from_webp(super::open_file(path.as_ref())?)
+    }
+}
+
+/// Decodes a WebP one frame at a time, as `Animation::from_webp()` does, without holding every frame in memory.
+pub struct WebPFrames<R: BufRead + Seek> {
+    decoder: WebPDecoder<R>,
+    buf: Vec<u8>,
+    remaining: u32,
+    #[cfg(feature = "icc")]
+    srgb: Option<super::icc::SrgbTransform>,
+    #[cfg(feature = "icc")]
+    embedded_profile: super::EmbeddedProfile,
+}
+
+impl<R: BufRead + Seek> WebPFrames<R> {
+    /// Reads the WebP headers.
+    /// # Parameters:
+    /// --- `reader`: Source of the WebP data;
+    pub fn new(reader: R) -> Result<Self, ChafaError> {
+        // Only reading the ICC profile needs mutable access.
+        #[cfg_attr(not(feature = "icc"), allow(unused_mut))]
+        let mut decoder = WebPDecoder::new(reader).map_err(webp_error)?;
+        let (width, height) = decoder.dimensions();
+        let size = decoder.output_buffer_size().ok_or_else(|| {
+            ChafaError::InvalidInput(format!("WebP of {}x{} is too large", width, height))
+        })?;
+        let remaining = if decoder.is_animated() {
+            decoder.num_frames()
+        } else {
+            1
+        };
+        #[cfg(feature = "icc")]
+        let (srgb, embedded_profile) = match decoder.icc_profile() {
+            Ok(icc_profile) => super::icc::SrgbTransform::embedded(icc_profile.as_deref()),
+            Err(e) => (None, super::EmbeddedProfile::Unusable(webp_error(e))),
+        };
+        Ok(Self {
+            #[cfg(feature = "icc")]
+            srgb,
+            #[cfg(feature = "icc")]
+            embedded_profile,
+            decoder,
+            buf: vec![0u8; size],
+            remaining,
+        })
+    }
+
+    /// Tells whether the WebP has an embedded ICC profile that frames are converted from. See `Animation::embedded_profile()`.
+    #[cfg(feature = "icc")]
+    pub fn embedded_profile(&self) -> &super::EmbeddedProfile {
+        &self.embedded_profile
+    }
+
+    /// Width of the image, in pixels.
+    pub fn width(&self) -> i32 {
+        self.decoder.dimensions().0 as i32
+    }
+
+    /// Height of the image, in pixels.
+    pub fn height(&self) -> i32 {
+        self.decoder.dimensions().1 as i32
+    }
+
+    /// Returns how many times the animation is meant to play, or None if it loops forever. Still images play once.
+    pub fn loop_count(&self) -> Option<u32> {
+        if !self.decoder.is_animated() {
+            return Some(1);
+        }
+        match self.decoder.loop_count() {
+            LoopCount::Forever => None,
+            LoopCount::Times(times) => Some(u32::from(times.get())),
+        }
+    }
+
+    /// Decodes the next frame.
+    /// # Returns:
+    /// The frame in RGBA8Unassociated, with rows of width * 4 bytes, and its delay, or None after the last frame.
+    pub fn next_pixels(&mut self) -> Result<Option<(Vec<u8>, Duration)>, ChafaError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let delay = if self.decoder.is_animated() {
+            let delay = self.decoder.read_frame(&mut self.buf).map_err(webp_error)?;
+            Duration::from_millis(u64::from(delay))
+        } else {
+            self.decoder.read_image(&mut self.buf).map_err(webp_error)?;
+            Duration::ZERO
+        };
+        #[allow(unused_mut)]
+        let mut pixels = to_rgba(&self.buf, self.decoder.has_alpha());
+        #[cfg(feature = "icc")]
+        if let Some(srgb) = &self.srgb {
+            srgb.apply(&mut pixels);
+        }
+        Ok(Some((pixels, delay)))
+    }
+}
+
+impl<R: BufRead + Seek> FrameSource for WebPFrames<R> {
+    fn next_frame(&mut self) -> Result<Option<(Frame, Duration)>, ChafaError> {
+        let Some((pixels, delay)) = self.next_pixels()? else {
+            return Ok(None);
+        };
+        let frame = Frame::new(
+            &pixels,
+            PixelType::RGBA8Unassociated,
+            self.width(),
+            self.height(),
+            self.width() * 4,
+        )?;
+        Ok(Some((frame, delay)))
+    }
+}
+
+/// The decoder puts out RGB for images without alpha, which frames store as opaque RGBA.
+fn to_rgba(buf: &[u8], has_alpha: bool) -> Vec<u8> {
+    if has_alpha {
+        buf.to_vec()
+    } else {
+        buf.chunks_exact(3)
+            .flat_map(|px| [px[0], px[1], px[2], 0xff])
+            .collect()
+    }
+}
+
+fn webp_error(e: image_webp::DecodingError) -> ChafaError {
+    ChafaError::InvalidInput(format!("Failed to decode WebP: {}", e))
+}
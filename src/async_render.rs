@@ -0,0 +1,84 @@
+use crate::PixelType;
+use crate::canvas::{Canvas, Config, PrintOptions};
+use crate::term::Info;
+use ::tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Draws an image on a canvas made with config and writes the output to an `AsyncWrite`. The canvas work runs on tokio's blocking thread pool, so large frames, sixels in particular, don't stall the async runtime while they are drawn.
+/// # Parameters:
+/// --- `config`: Configuration of the canvas to draw on. It is copied, so it can be changed while drawing;
+/// --- `pixels`: Image bytes, e.g. a `Vec<u8>` or `Arc<[u8]>`;
+/// --- `pixel_type`: Pixel format of pixels;
+/// --- `width`: Width of the image, in pixels;
+/// --- `height`: Height of the image, in pixels;
+/// --- `rowstride`: Number of bytes between the start of each pixel row;
+/// --- `term_info`: Terminal to format for, or `None` for fallback;
+/// --- `options`: Output options to apply;
+/// --- `writer`: Destination of the output, e.g. `tokio::io::stdout()`;
+#[allow(clippy::too_many_arguments)]
+pub async fn render_async<P, W>(
+    config: &Config,
+    pixels: P,
+    pixel_type: PixelType,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+    term_info: Option<&Info>,
+    options: &PrintOptions,
+    writer: &mut W,
+) -> std::io::Result<()>
+where
+    P: AsRef<[u8]> + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    let job = Owned::new(
+        config.copy().map_err(std::io::Error::other)?,
+        term_info.cloned(),
+    );
+    let options = options.clone();
+    let out = blocking(move || {
+        let (config, term_info) = job.into_inner();
+        let canvas = Canvas::new(&config).map_err(std::io::Error::other)?;
+        canvas
+            .set_pixels(pixels.as_ref(), pixel_type, width, height, rowstride)
+            .map_err(std::io::Error::other)?;
+        canvas
+            .create_string_with(term_info.as_ref(), &options)
+            .map_err(std::io::Error::other)
+    })
+    .await?;
+    writer.write_all(out.as_bytes()).await?;
+    writer.flush().await
+}
+
+/// Carries a canvas or config, with the term info to format for, to another thread. Chafa counts references atomically and doesn't tie objects to the thread that made them, so this is sound as long as nothing else holds the objects while they move; callers only wrap copies of their own.
+pub(crate) struct Owned<T> {
+    object: T,
+    term_info: Option<Info>,
+}
+
+unsafe impl Send for Owned<Canvas> {}
+unsafe impl Send for Owned<Config> {}
+
+impl<T> Owned<T> {
+    pub(crate) fn new(object: T, term_info: Option<Info>) -> Self {
+        Self { object, term_info }
+    }
+
+    /// Takes the objects out. Closures must call this rather than destructure, or they capture the unwrapped fields.
+    pub(crate) fn into_inner(self) -> (T, Option<Info>) {
+        (self.object, self.term_info)
+    }
+}
+
+/// Runs f on tokio's blocking thread pool, passing on its panics.
+pub(crate) async fn blocking<T, F>(f: F) -> std::io::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> std::io::Result<T> + Send + 'static,
+{
+    match ::tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(std::io::Error::other(e)),
+    }
+}
@@ -0,0 +1,100 @@
+use crate::canvas::{Canvas, Config, PrintOptions};
+use crate::term::Info;
+use crate::{ChafaError, PixelType, misc};
+use rayon::iter::{IntoParallelIterator, ParallelExtend, ParallelIterator};
+
+/// An image to draw with `render_batch()`, along with everything needed to draw it.
+pub struct RenderJob {
+    config: Config,
+    pixels: Vec<u8>,
+    pixel_type: PixelType,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+    term_info: Option<Info>,
+    options: PrintOptions,
+}
+
+// The job owns its copies of the config and term info, and chafa counts references atomically without tying objects to the thread that made them, so a job can move to a worker thread.
+unsafe impl Send for RenderJob {}
+
+impl RenderJob {
+    /// Creates a job drawing an image on a canvas made with config.
+    /// # Parameters:
+    /// --- `config`: Configuration of the canvas to draw on. It is copied;
+    /// --- `pixels`: Image bytes;
+    /// --- `pixel_type`: Pixel format of pixels;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    /// --- `rowstride`: Number of bytes between the start of each pixel row;
+    pub fn new(
+        config: &Config,
+        pixels: Vec<u8>,
+        pixel_type: PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+    ) -> Result<Self, ChafaError> {
+        misc::validate_image(Some(pixels.len()), pixel_type, (width, height, rowstride))?;
+        Ok(Self {
+            config: config.copy()?,
+            pixels,
+            pixel_type,
+            width,
+            height,
+            rowstride,
+            term_info: None,
+            options: PrintOptions::default(),
+        })
+    }
+
+    /// Sets the terminal to format for. Defaults to chafa's fallback sequences.
+    pub fn term_info(mut self, term_info: &Info) -> Self {
+        self.term_info = Some(term_info.clone());
+        self
+    }
+
+    /// Sets the output options to apply. Defaults to `PrintOptions::default()`.
+    pub fn options(mut self, options: PrintOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Draws the image on the calling thread and returns its printable output.
+    pub fn render(self) -> Result<String, ChafaError> {
+        let canvas = Canvas::new(&self.config)?;
+        canvas.set_pixels(
+            &self.pixels,
+            self.pixel_type,
+            self.width,
+            self.height,
+            self.rowstride,
+        )?;
+        canvas.create_string_with(self.term_info.as_ref(), &self.options)
+    }
+}
+
+/// Draws many images at once on rayon's thread pool and returns the output of each, in the order of items. A failed image doesn't stop the others; jobs that couldn't be made, e.g. because an image failed to decode, are passed through as errors in their place.
+///
+/// Jobs are taken from items max_in_flight at a time, and each batch is drawn before the next is taken, so no more than max_in_flight images are held in memory at once when items makes its jobs lazily, e.g. by decoding files as it goes. Jobs collected up front hold all their pixels until drawn.
+/// # Parameters:
+/// --- `items`: The images to draw; wrap ready jobs in `Ok`;
+/// --- `max_in_flight`: How many jobs to take at a time, or 0 for one per thread of the pool;
+pub fn render_batch(
+    items: impl IntoIterator<Item = Result<RenderJob, ChafaError>>,
+    max_in_flight: usize,
+) -> Vec<Result<String, ChafaError>> {
+    let max_in_flight = match max_in_flight {
+        0 => rayon::current_num_threads(),
+        n => n,
+    };
+    let mut items = items.into_iter();
+    let mut results = Vec::new();
+    loop {
+        let batch: Vec<_> = items.by_ref().take(max_in_flight).collect();
+        if batch.is_empty() {
+            return results;
+        }
+        results.par_extend(batch.into_par_iter().map(|job| job?.render()));
+    }
+}
@@ -3,6 +3,11 @@
  */
 
 use crate::ffi;
+use crate::term::{
+    Capabilities, Db, Env, Terminal, in_zellij, probe_kitty_graphics, tmux_allows_passthrough,
+    zellij_pixel_mode,
+};
+use crate::{ChafaError, MapPreset, SymbolMap};
 
 /// A ChafaCanvasConfig describes a set of parameters for ChafaCanvas, such as its geometry, color space and other output characteristics.
 ///
@@ -15,28 +20,96 @@ pub struct Config {
 
 impl Config {
     /// Creates a new ChafaCanvasConfig with default settings. This object can later be used in the creation of a ChafaCanvas.
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, ChafaError> {
         let raw: *mut ffi::ChafaCanvasConfig = unsafe { ffi::chafa_canvas_config_new() };
         if raw.is_null() {
-            Err("Chafa -> Failed to create config")
+            Err(ChafaError::Failed("Chafa -> Failed to create config"))
         } else {
             Ok(Config { raw })
         }
     }
 
-    pub fn new_detect() -> Result<Self, &'static str> {
-        let config = Self::new();
-        if let Err(e) = config {
-            return Err(e);
+    /// Creates a new ChafaCanvasConfig with canvas and pixel modes suited to the terminal detected from the environment.
+    ///
+    /// Only environment variables are looked at; nothing is written to the terminal, so this is safe to call while something else reads its input. Inside tmux, symbols are used unless tmux is configured to forward pixel data. Inside Zellij, which only shows sixel images, other pixel modes fall back to sixels or symbols. Use `new_probe()` to also ask the terminal itself.
+    pub fn new_detect() -> Result<Self, ChafaError> {
+        Self::detect_with(None)
+    }
+
+    /// Creates a new ChafaCanvasConfig like `new_detect()`, then asks the terminal about what the environment doesn't reveal.
+    ///
+    /// If the environment doesn't reveal any pixel graphics protocol, the terminal is asked whether it supports kitty graphics. In pixel modes the cell geometry is taken from the terminal, as `detect_cell_geometry()` does, so images get as many pixels as the cells really have; chafa's default of 10x20 would render them at half resolution on HiDPI displays.
+    ///
+    /// The queries are written to the controlling terminal, which is put in raw mode while this blocks for up to timeout waiting for the replies. Call it before anything else starts reading the terminal's input.
+    /// # Parameters:
+    /// --- `timeout`: How long to wait for the terminal to answer, e.g. 100 ms;
+    pub fn new_probe(timeout: std::time::Duration) -> Result<Self, ChafaError> {
+        Self::detect_with(Some(timeout))
+    }
+
+    fn detect_with(probe_timeout: Option<std::time::Duration>) -> Result<Self, ChafaError> {
+        let conf = Self::new()?;
+        let env = Env::capture();
+        let info = Db::default()?.detect_env(&env)?;
+        let caps = Capabilities::from(&info);
+
+        conf.set_canvas_mode(caps.best_canvas_mode());
+
+        let mut pixel_mode = caps.best_pixel_mode();
+        if let Some(timeout) = probe_timeout
+            && pixel_mode == PixelMode::Symbols
+            && probe_kitty_graphics(timeout).unwrap_or(false)
+        {
+            pixel_mode = PixelMode::Kitty;
+        }
+        // tmux drops pixel data silently unless passthrough is allowed.
+        if pixel_mode != PixelMode::Symbols
+            && caps.passthrough() == Passthrough::Tmux
+            && tmux_allows_passthrough(&env) == Some(false)
+        {
+            pixel_mode = PixelMode::Symbols;
+        }
+        // Zellij only forwards sixels, which it redraws itself.
+        if in_zellij(&env) {
+            pixel_mode = zellij_pixel_mode(pixel_mode, caps.supports_sixel());
+        }
+        conf.set_pixel_mode(pixel_mode);
+        if probe_timeout.is_some()
+            && pixel_mode != PixelMode::Symbols
+            && let Ok(terminal) = Terminal::get_default()
+        {
+            conf.detect_cell_geometry(&terminal);
         }
-        let conf = config.unwrap();
 
-        // TODO: ...
-        // conf.set_pixel_mode(PixelMode::Sixels);
+        Ok(conf)
+    }
 
+    /// Creates a new ChafaCanvasConfig for plain-text environments such as CI logs, which mangle anything beyond ASCII.
+    ///
+    /// Output uses printable ASCII symbols only, and colors are limited to the 8 colors of ANSI X3.64, so it contains nothing but 7-bit characters and basic SGR sequences. Use `is_ascii_sgr_only()` to check output.
+    pub fn new_ascii() -> Result<Self, ChafaError> {
+        let conf = Self::new()?;
+        let symbols = SymbolMap::preset(MapPreset::AsciiOnly)?;
+
+        conf.set_pixel_mode(PixelMode::Symbols);
+        conf.set_canvas_mode(CanvasMode::Indexed8);
+        conf.set_symbol_map(&symbols);
+        conf.set_fill_symbol_map(&symbols);
+        // REP (ESC [ n b) would break the ASCII and SGR only promise.
+        conf.set_optimizations(Optimizations::None);
         Ok(conf)
     }
 
+    /// Creates a new ChafaCanvasConfig that's a copy of config, which can then be changed without affecting the original.
+    pub fn copy(&self) -> Result<Self, ChafaError> {
+        let raw: *mut ffi::ChafaCanvasConfig = unsafe { ffi::chafa_canvas_config_copy(self.raw) };
+        if raw.is_null() {
+            Err(ChafaError::Failed("Chafa -> Failed to copy config"))
+        } else {
+            Ok(Config { raw })
+        }
+    }
+
     /// Returns a tuple containing config's width and height in character cells.
     pub fn get_geometry(&self) -> (i32, i32) {
         let mut width: i32 = 0;
@@ -48,11 +121,13 @@ impl Config {
         (width, height)
     }
 
-    /// Sets config 's width and height in character cells to width x height.
-    pub fn set_geometry(&self, width: i32, height: i32) {
+    /// Sets config 's width and height in character cells to width x height. Both must be positive.
+    pub fn set_geometry(&self, width: i32, height: i32) -> Result<(), ChafaError> {
+        check_size("Canvas geometry", width, height)?;
         unsafe {
             ffi::chafa_canvas_config_set_geometry(self.raw, width, height);
         }
+        Ok(())
     }
 
     /// Returns a tuple containing config's cell width and height in pixels.
@@ -66,11 +141,44 @@ impl Config {
         (width, height)
     }
 
-    /// Sets config 's cell width and height in pixels to cell_width x cell_height.
-    pub fn set_cell_geometry(&self, width: i32, height: i32) {
+    /// Sets config 's cell width and height in pixels to cell_width x cell_height. Both must be positive.
+    pub fn set_cell_geometry(&self, width: i32, height: i32) -> Result<(), ChafaError> {
+        check_size("Cell geometry", width, height)?;
         unsafe {
             ffi::chafa_canvas_config_set_cell_geometry(self.raw, width, height);
         }
+        Ok(())
+    }
+
+    /// Computes the size of a character cell in pixels from terminal's window size in pixels and cells, and sets it as config's cell geometry.
+    ///
+    /// A correct cell geometry is required for images to keep their aspect ratio in the sixel, kitty and iTerm2 pixel modes.
+    /// # Returns:
+    /// A tuple containing the applied cell width and height in pixels, or `None` if the terminal didn't report its size, in which case config is left unchanged.
+    pub fn detect_cell_geometry(&self, terminal: &Terminal) -> Option<(i32, i32)> {
+        let (width_px, height_px) = terminal.get_size_px();
+        let (width_cells, height_cells) = terminal.get_size_cells();
+
+        if width_px <= 0 || height_px <= 0 || width_cells <= 0 || height_cells <= 0 {
+            return None;
+        }
+        let cell_width = width_px / width_cells;
+        let cell_height = height_px / height_cells;
+        if cell_width <= 0 || cell_height <= 0 {
+            return None;
+        }
+
+        self.set_cell_geometry(cell_width, cell_height).ok()?;
+        Some((cell_width, cell_height))
+    }
+
+    /// Multiplies config's cell geometry by scale, e.g. 2.0 on a Retina display for terminals that report their size in points rather than device pixels. Pixel modes then send images at the display's full resolution; the aspect ratio is unchanged.
+    /// # Returns:
+    /// A tuple containing the applied cell width and height in pixels.
+    pub fn set_pixel_scale(&self, scale: f32) -> Result<(i32, i32), ChafaError> {
+        let (width, height) = crate::geometry::scale_cell_size(self.get_cell_geometry(), scale);
+        self.set_cell_geometry(width, height)?;
+        Ok((width, height))
     }
 
     /// Returns config's ChafaPixelMode.
@@ -223,9 +331,39 @@ impl Config {
         }
     }
 
+    /// Returns the optimizations enabled for config. These compress the output, e.g. by reusing attributes or repeating cells.
+    pub fn get_optimizations(&self) -> Optimizations {
+        unsafe {
+            Optimizations::from_bits_truncate(ffi::chafa_canvas_config_get_optimizations(self.raw))
+        }
+    }
+
+    /// Sets the optimizations to apply to the output. Some, like CHAFA_OPTIMIZATION_REPEAT_CELLS, emit sequences not every terminal understands.
+    pub fn set_optimizations(&self, optimizations: Optimizations) {
+        unsafe {
+            ffi::chafa_canvas_config_set_optimizations(self.raw, optimizations.bits());
+        }
+    }
+
     // /// Returns a pointer to the symbol map belonging to config .
     // /// This can be inspected using the ChafaSymbolMap getter functions, but not changed.
     // pub fn get_symbol_map(&self) -> SymbolMap {}
+
+    /// Assigns a copy of symbol_map to config.
+    pub fn set_symbol_map(&self, symbol_map: &SymbolMap) {
+        unsafe {
+            ffi::chafa_canvas_config_set_symbol_map(self.raw, symbol_map.raw);
+        }
+    }
+
+    /// Assigns a copy of fill_symbol_map to config.
+    ///
+    /// Fill symbols are assigned according to their overall foreground to background coverage, disregarding shape.
+    pub fn set_fill_symbol_map(&self, fill_symbol_map: &SymbolMap) {
+        unsafe {
+            ffi::chafa_canvas_config_set_fill_symbol_map(self.raw, fill_symbol_map.raw);
+        }
+    }
 }
 
 impl Drop for Config {
@@ -239,6 +377,9 @@ impl Drop for Config {
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum PixelMode {
     /// Pixel data is approximated using character symbols ("ANSI art").
     Symbols = ffi::ChafaPixelMode_CHAFA_PIXEL_MODE_SYMBOLS,
@@ -247,36 +388,52 @@ pub enum PixelMode {
     /// Pixel data is encoded using the Kitty terminal protocol.
     Kitty = ffi::ChafaPixelMode_CHAFA_PIXEL_MODE_KITTY,
     /// Pixel data is encoded using the iTerm2 terminal protocol.
+    #[cfg_attr(feature = "serde", serde(alias = "iterm"))]
     Iterm2 = ffi::ChafaPixelMode_CHAFA_PIXEL_MODE_ITERM2,
 
     /// Last supported pixel mode plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaPixelMode_CHAFA_PIXEL_MODE_MAX,
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CanvasMode {
     /// Truecolor.
+    #[cfg_attr(feature = "serde", serde(rename = "truecolor", alias = "full"))]
     TrueColor = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_TRUECOLOR,
     /// 256 colors.
+    #[cfg_attr(feature = "serde", serde(rename = "indexed-256", alias = "256"))]
     Indexed256 = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_256,
     /// 256 colors, but avoid using the lower 16 whose values vary between terminal environments.
+    #[cfg_attr(feature = "serde", serde(rename = "indexed-240", alias = "240"))]
     Indexed240 = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_240,
     /// 16 colors using the aixterm ANSI extension.
+    #[cfg_attr(feature = "serde", serde(rename = "indexed-16", alias = "16"))]
     Indexed16 = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_16,
     /// Default foreground and background colors, plus inversion.
+    #[cfg_attr(feature = "serde", serde(rename = "fgbg-bgfg", alias = "2"))]
     FgbgBgfg = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_FGBG_BGFG,
     /// Default foreground and background colors. No ANSI codes will be used.
+    #[cfg_attr(feature = "serde", serde(rename = "fgbg", alias = "none"))]
     FgBg = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_FGBG,
     /// 8 colors, compatible with original ANSI X3.64.
+    #[cfg_attr(feature = "serde", serde(rename = "indexed-8", alias = "8"))]
     Indexed8 = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_8,
     /// 16 FG colors (8 of which enabled with bold/bright) and 8 BG colors.
+    #[cfg_attr(feature = "serde", serde(rename = "indexed-16-8", alias = "16/8"))]
     Indexed168 = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_16_8,
 
     /// Last supported canvas mode plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_MAX,
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum ColorExtractor {
     /// Use the average colors of each symbol's coverage area.
     Average = ffi::ChafaColorExtractor_CHAFA_COLOR_EXTRACTOR_AVERAGE,
@@ -284,21 +441,30 @@ pub enum ColorExtractor {
     Median = ffi::ChafaColorExtractor_CHAFA_COLOR_EXTRACTOR_MEDIAN,
 
     /// Last supported color extractor plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaColorExtractor_CHAFA_COLOR_EXTRACTOR_MAX,
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColorSpace {
     /// RGB color space. Fast but imprecise.
+    #[cfg_attr(feature = "serde", serde(rename = "rgb"))]
     RGB = ffi::ChafaColorSpace_CHAFA_COLOR_SPACE_RGB,
     /// DIN99d color space. Slower, but good perceptual color precision.
+    #[cfg_attr(feature = "serde", serde(rename = "din99d"))]
     DIN99d = ffi::ChafaColorSpace_CHAFA_COLOR_SPACE_DIN99D,
 
     /// Last supported color space plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaColorSpace_CHAFA_COLOR_SPACE_MAX,
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum DitherMode {
     /// No dithering.
     None = ffi::ChafaDitherMode_CHAFA_DITHER_MODE_NONE,
@@ -310,11 +476,13 @@ pub enum DitherMode {
     Noise = ffi::ChafaDitherMode_CHAFA_DITHER_MODE_NOISE,
 
     /// Last supported dither mode plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaDitherMode_CHAFA_DITHER_MODE_MAX,
 }
 
 bitflags::bitflags! {
     #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
     pub struct Optimizations: u32 {
         /// Suppress redundant SGR control sequences.
         const ReuseAttributes = ffi::ChafaOptimizations_CHAFA_OPTIMIZATION_REUSE_ATTRIBUTES;
@@ -331,6 +499,9 @@ bitflags::bitflags! {
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Passthrough {
     /// No passthrough guards will be used.
     None = ffi::ChafaPassthrough_CHAFA_PASSTHROUGH_NONE,
@@ -340,5 +511,17 @@ pub enum Passthrough {
     Tmux = ffi::ChafaPassthrough_CHAFA_PASSTHROUGH_TMUX,
 
     /// Last supported passthrough mode plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaPassthrough_CHAFA_PASSTHROUGH_MAX,
 }
+
+/// Rejects sizes chafa can't use, which it would otherwise ignore with a logged warning.
+fn check_size(what: &str, width: i32, height: i32) -> Result<(), ChafaError> {
+    if width <= 0 || height <= 0 {
+        return Err(ChafaError::InvalidInput(format!(
+            "{} must be positive, got {}x{}",
+            what, width, height
+        )));
+    }
+    Ok(())
+}
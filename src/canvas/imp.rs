@@ -2,9 +2,13 @@
  * https://hpjansson.org/chafa/ref/chafa-ChafaCanvas.html
  */
 
-use crate::canvas::Config;
-use crate::misc;
+use crate::canvas::{
+    Config, PixelMode, PrintOptions, Reposition, SYNC_UPDATE_BEGIN, SYNC_UPDATE_END,
+};
+use crate::term::Seq;
+use crate::{ChafaError, misc};
 use crate::{ffi, placement::Placement, term::Info};
+use std::cell::RefCell;
 use std::{ffi::CStr, fmt::write};
 
 /// A ChafaCanvas is a canvas that can render its contents as text strings.
@@ -14,6 +18,8 @@ use std::{ffi::CStr, fmt::write};
 /// You can draw an image to the canvas using chafa_canvas_draw_all_pixels() and create an ANSI text (or sixel) representation of the canvas' current contents using chafa_canvas_build_ansi().
 pub struct Canvas {
     pub raw: *mut ffi::ChafaCanvas,
+    // The placement last set, kept so the frame it draws from outlives chafa's use of it.
+    placement: RefCell<Option<Placement>>,
 }
 
 impl Canvas {
@@ -21,34 +27,43 @@ impl Canvas {
     /// The canvas makes a private copy of the configuration, so it will not be affected by subsequent changes.
     /// # Parameters:
     /// --- `config`: Configuration to use.
-    pub fn new(config: &Config) -> Result<Self, &'static str> {
+    pub fn new(config: &Config) -> Result<Self, ChafaError> {
         let raw: *mut ffi::ChafaCanvas = unsafe { ffi::chafa_canvas_new(config.raw) };
         if raw.is_null() {
-            Err("Chafa -> Failed to create canvas")
+            Err(ChafaError::Failed("Chafa -> Failed to create canvas"))
         } else {
-            Ok(Canvas { raw })
+            Ok(Canvas {
+                raw,
+                placement: RefCell::new(None),
+            })
         }
     }
 
     /// Returns the configuration belonging to canvas .
     /// This can be inspected using the ChafaCanvasConfig getter functions, but not changed.
-    pub fn config(&self) -> Result<Config, &'static str> {
+    pub fn config(&self) -> Result<Config, ChafaError> {
         let raw: *const ffi::ChafaCanvasConfig = unsafe { ffi::chafa_canvas_peek_config(self.raw) };
 
         if raw.is_null() {
-            Err("Chafa -> Failed to retrieve config")
+            Err(ChafaError::Failed("Chafa -> Failed to retrieve config"))
         } else {
+            // The canvas owns the peeked config; take our own reference so dropping the
+            // returned `Config` doesn't release the canvas' copy.
+            unsafe {
+                ffi::chafa_canvas_config_ref(raw as *mut _);
+            }
             Ok(Config { raw: raw as *mut _ })
         }
     }
 
     /// Places placement on canvas , replacing the latter's content. The placement will cover the entire canvas.
     ///
-    /// The canvas will keep a reference to the placement until it is replaced or the canvas itself is freed.
+    /// The canvas will keep a reference to the placement until it is replaced or the canvas itself is freed. This includes the placement's image and frame, so frames borrowing memory, such as those from `Frame::from_mmap()`, stay valid while the canvas can draw them.
     pub fn set_placement(&self, placement: Placement) {
         unsafe {
             ffi::chafa_canvas_set_placement(self.raw, placement.raw);
         }
+        *self.placement.borrow_mut() = Some(placement);
     }
 
     /// Replaces pixel data of canvas with a copy of that found at pixels,
@@ -59,6 +74,8 @@ impl Canvas {
     /// --- `src_width`: Width in pixels of source pixel data;
     /// --- `src_height`: Height in pixels of source pixel data;
     /// --- `src_rowstride`: Number of bytes between the start of each pixel row.
+    ///
+    /// Empty images, rowstrides shorter than a row and pixel data too small for the image are rejected with `ChafaError::InvalidInput`, leaving the canvas as it was.
     pub fn set_pixels(
         &self,
         pixels: &[u8],
@@ -66,7 +83,12 @@ impl Canvas {
         src_width: i32,
         src_height: i32,
         src_rowstride: i32,
-    ) {
+    ) -> Result<(), ChafaError> {
+        misc::validate_image(
+            Some(pixels.len()),
+            pixel_type,
+            (src_width, src_height, src_rowstride),
+        )?;
         unsafe {
             ffi::chafa_canvas_draw_all_pixels(
                 self.raw,
@@ -77,6 +99,39 @@ impl Canvas {
                 src_rowstride,
             );
         }
+        Ok(())
+    }
+
+    /// Replaces pixel data of canvas with a copy of a region of pixels, scaled to fill the canvas. This is how pan and zoom viewers show part of a large image; the region is drawn straight from pixels, without copying it out first.
+    /// # Parameters:
+    /// --- `pixels`: Image bytes;
+    /// --- `pixel_type`: Pixel format of pixels;
+    /// --- `src_width`: Width in pixels of source pixel data;
+    /// --- `src_height`: Height in pixels of source pixel data;
+    /// --- `src_rowstride`: Number of bytes between the start of each pixel row;
+    /// --- `src_rect`: Region of the source to draw;
+    pub fn draw_region(
+        &self,
+        pixels: &[u8],
+        pixel_type: misc::PixelType,
+        src_width: i32,
+        src_height: i32,
+        src_rowstride: i32,
+        src_rect: misc::Rect,
+    ) -> Result<(), ChafaError> {
+        let offset = misc::region_offset(
+            pixels.len(),
+            pixel_type,
+            (src_width, src_height, src_rowstride),
+            src_rect,
+        )?;
+        self.set_pixels(
+            &pixels[offset..],
+            pixel_type,
+            src_rect.width,
+            src_rect.height,
+            src_rowstride,
+        )
     }
 
     /// Builds a UTF-8 string of terminal control sequences and symbols representing the canvas' current contents.
@@ -85,21 +140,122 @@ impl Canvas {
     /// All output lines except for the last one will end in a newline.
     /// # Parameters:
     /// --- `term_info`: Terminal to format for, or `None` for fallback
-    pub fn create_string(&self, term_info: Option<Info>) -> Result<String, &'static str> {
+    pub fn create_string(&self, term_info: Option<Info>) -> Result<String, ChafaError> {
         let mut term: *mut ffi::ChafaTermInfo = std::ptr::null_mut();
 
-        if let Some(ti) = term_info {
+        if let Some(ti) = &term_info {
             term = ti.raw;
         }
+        self.print_raw(term)
+    }
+
+    /// Builds a UTF-8 string representing the canvas' current contents like `create_string()`, with additional processing applied according to `options`.
+    /// # Parameters:
+    /// --- `term_info`: Terminal to format for, or `None` for fallback;
+    /// --- `options`: Output options to apply.
+    pub fn create_string_with(
+        &self,
+        term_info: Option<&Info>,
+        options: &PrintOptions,
+    ) -> Result<String, ChafaError> {
+        let term: *mut ffi::ChafaTermInfo = match term_info {
+            Some(ti) => ti.raw,
+            None => std::ptr::null_mut(),
+        };
+        let mut body = self.print_raw(term)?;
+
+        if body.ends_with('\n') {
+            body.pop();
+        }
+        if !options.trailing_reset {
+            let mut reset = String::new();
+            match term_info {
+                Some(ti) => ti.emit_reset_attributes(&mut reset),
+                None => reset.push_str("\x1b[0m"),
+            }
+            if !reset.is_empty() && body.ends_with(&reset) {
+                body.truncate(body.len() - reset.len());
+            }
+        }
+
+        if !matches!(options.reposition, Reposition::None) {
+            // Frames drawn in place must leave the cursor on their last row,
+            // so no newline is added.
+            let rows = self.config().map(|c| c.get_geometry().1).unwrap_or(0);
+            let prefix = reposition_prefix(term_info, &options.reposition, rows.max(0) as u32);
+            body.insert_str(0, &prefix);
+        } else if options.trailing_newline {
+            body.push('\n');
+        }
+
+        if options.synchronized_update
+            && term_info.is_some_and(|ti| ti.supports_synchronized_update())
+        {
+            Ok(format!("{}{}{}", SYNC_UPDATE_BEGIN, body, SYNC_UPDATE_END))
+        } else {
+            Ok(body)
+        }
+    }
+
+    /// Writes the canvas' current contents to `writer`, formatted for `term_info` and processed according to `options`.
+    /// # Parameters:
+    /// --- `term_info`: Terminal to format for, or `None` for fallback;
+    /// --- `options`: Output options to apply;
+    /// --- `writer`: Destination of the output.
+    pub fn print_to<W: std::io::Write>(
+        &self,
+        term_info: Option<&Info>,
+        options: &PrintOptions,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let out = self
+            .create_string_with(term_info, options)
+            .map_err(std::io::Error::other)?;
+        writer.write_all(out.as_bytes())?;
+        writer.flush()
+    }
+
+    /// Builds the raw bytes of terminal control sequences and symbols representing the canvas' current contents, exactly as chafa produced them.
+    ///
+    /// Unlike `create_string()`, no UTF-8 conversion is performed, so binary-safe payloads (e.g. base64 image data wrapped for passthrough) are returned untouched.
+    /// # Parameters:
+    /// --- `term_info`: Terminal to format for, or `None` for fallback
+    pub fn create_bytes(&self, term_info: Option<&Info>) -> Result<Vec<u8>, ChafaError> {
+        let term: *mut ffi::ChafaTermInfo = match term_info {
+            Some(ti) => ti.raw,
+            None => std::ptr::null_mut(),
+        };
+        self.print_raw_bytes(term)
+    }
+
+    fn print_raw(&self, term: *mut ffi::ChafaTermInfo) -> Result<String, ChafaError> {
+        let bytes = self.print_raw_bytes(term)?;
+        Ok(match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        })
+    }
+
+    fn print_raw_bytes(&self, term: *mut ffi::ChafaTermInfo) -> Result<Vec<u8>, ChafaError> {
         let g_str = unsafe { ffi::chafa_canvas_print(self.raw, term) };
         if g_str.is_null() {
-            return Err("Chafa -> Failed to retrieve GString from `chafa_canvas_print()`");
+            return Err(ChafaError::Failed(
+                "Chafa -> Failed to retrieve GString from `chafa_canvas_print()`",
+            ));
         }
-        let str = unsafe { std::ffi::CStr::from_ptr((*g_str).str_ as *const std::os::raw::c_char) };
+        let bytes = unsafe {
+            let str_p = (*g_str).str_ as *const u8;
+            let len = (*g_str).len as usize;
+            if str_p.is_null() {
+                Vec::new()
+            } else {
+                std::slice::from_raw_parts(str_p, len).to_vec()
+            }
+        };
         unsafe {
             ffi::g_string_free(g_str, 1);
         }
-        Ok(str.to_string_lossy().into_owned())
+        Ok(bytes)
     }
 
     /// Builds an array of UTF-8 strings made up of terminal control sequences and symbols representing the canvas' current contents.
@@ -112,18 +268,72 @@ impl Canvas {
     /// They must be printed in sequence, exactly as they appear.
     /// # Parameters:
     /// --- `term_info`: Terminal to format for, or `None` for fallback
-    pub fn create_string_rows(&self, term_info: Option<Info>) -> Result<Vec<String>, &'static str> {
+    pub fn create_string_rows(&self, term_info: Option<Info>) -> Result<Vec<String>, ChafaError> {
         let mut term: *mut ffi::ChafaTermInfo = std::ptr::null_mut();
 
-        if let Some(ti) = term_info {
+        if let Some(ti) = &term_info {
             term = ti.raw;
         }
+        self.print_rows_raw(term)
+    }
+
+    /// Writes the canvas' current contents to `writer` with its top left corner at cell (x, y). The coordinates are zero-indexed.
+    ///
+    /// In CHAFA_PIXEL_MODE_SYMBOLS, every row is prefixed with a cursor positioning sequence instead of being separated by newlines, so the output can be painted into an arbitrary rectangle (e.g. a preview pane) without scrolling or disturbing the cells around it.
+    /// In other pixel modes, the cursor is positioned once before the image data.
+    /// # Parameters:
+    /// --- `x`: Column of the top left corner, in character cells;
+    /// --- `y`: Row of the top left corner, in character cells;
+    /// --- `term_info`: Terminal to format for. Must be able to emit CHAFA_TERM_SEQ_CURSOR_TO_POS;
+    /// --- `writer`: Destination of the output.
+    pub fn print_at<W: std::io::Write>(
+        &self,
+        x: i32,
+        y: i32,
+        term_info: &Info,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        if !term_info.have_seq(Seq::CursorToPos) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Chafa -> Terminal can't position the cursor",
+            ));
+        }
+        let x = x.max(0) as u32;
+        let y = y.max(0) as u32;
+
+        let symbols = self
+            .config()
+            .map(|c| matches!(c.get_pixel_mode(), PixelMode::Symbols))
+            .unwrap_or(true);
+
+        let rows = self
+            .print_rows_raw(term_info.raw)
+            .map_err(std::io::Error::other)?;
+
+        if symbols {
+            for (i, row) in rows.iter().enumerate() {
+                writer.write_all(cursor_to_pos(term_info, x, y + i as u32).as_bytes())?;
+                writer.write_all(row.as_bytes())?;
+            }
+        } else {
+            writer.write_all(cursor_to_pos(term_info, x, y).as_bytes())?;
+            for row in rows.iter() {
+                writer.write_all(row.as_bytes())?;
+            }
+        }
+        writer.flush()
+    }
+
+    fn print_rows_raw(&self, term: *mut ffi::ChafaTermInfo) -> Result<Vec<String>, ChafaError> {
         let mut vec: Vec<String> = Vec::new();
         unsafe {
             let arr: *mut *mut ffi::gchar = ffi::chafa_canvas_print_rows_strv(self.raw, term);
 
             if arr.is_null() {
-                return Err("Chafa -> Failed to create array of rows");
+                return Err(ChafaError::Failed(
+                    "Chafa -> Failed to create array of rows",
+                ));
             }
 
             let mut i = 0;
@@ -233,6 +443,30 @@ impl Canvas {
     }
 }
 
+/// Formats CHAFA_TERM_SEQ_CURSOR_TO_POS for `term_info`.
+fn cursor_to_pos(term_info: &Info, x: u32, y: u32) -> String {
+    let mut seq = String::new();
+    term_info.emit_cursor_to_pos(&mut seq, x, y);
+    seq
+}
+
+/// Builds the sequence that moves the cursor back to where the previous frame of `rows` rows started, according to `reposition`.
+fn reposition_prefix(term_info: Option<&Info>, reposition: &Reposition, rows: u32) -> String {
+    let mut seq = String::new();
+    match (reposition, term_info) {
+        (Reposition::None, _) => {}
+        (Reposition::Home, Some(ti)) => ti.emit_cursor_to_top_left(&mut seq),
+        (Reposition::Home, None) => seq.push_str("\x1b[H"),
+        (Reposition::Rewind, _) if rows <= 1 => seq.push('\r'),
+        (Reposition::Rewind, Some(ti)) => {
+            seq.push('\r');
+            ti.emit_cursor_up(&mut seq, rows - 1);
+        }
+        (Reposition::Rewind, None) => seq.push_str(&format!("\r\x1b[{}A", rows - 1)),
+    }
+    seq
+}
+
 impl std::fmt::Display for Canvas {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let str = self.create_string(None);
@@ -1,4 +1,10 @@
 mod config;
 mod imp;
+mod montage;
+mod print;
+#[cfg(feature = "resize")]
+mod resize;
 pub use config::*;
 pub use imp::*;
+pub use montage::*;
+pub use print::*;
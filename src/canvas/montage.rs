@@ -0,0 +1,218 @@
+use crate::canvas::{Canvas, Config, PixelMode};
+use crate::{Animation, ChafaError, SYMBOL_HEIGHT_PIXELS, SYMBOL_WIDTH_PIXELS, misc};
+use unicode_width::UnicodeWidthChar;
+
+/// Lays out several images in a grid of equally sized tiles, each with an optional caption underneath, and draws them on a single canvas. This is the thumbnail view of terminal file managers.
+///
+/// Images are scaled to fit their tile, keeping their aspect ratio, and centered in it. Captions are cut to the tile width and centered; they are written into the canvas' cells, so they only show in CHAFA_PIXEL_MODE_SYMBOLS.
+pub struct Montage<'a> {
+    columns: i32,
+    tile_width: i32,
+    tile_height: i32,
+    gap: i32,
+    tiles: Vec<Tile<'a>>,
+}
+
+struct Tile<'a> {
+    pixels: &'a [u8],
+    width: i32,
+    height: i32,
+    caption: String,
+}
+
+impl<'a> Montage<'a> {
+    /// Creates an empty montage. Rows are added as tiles are.
+    /// # Parameters:
+    /// --- `columns`: Number of tiles per row;
+    /// --- `tile_width`: Width of each tile's image area, in character cells;
+    /// --- `tile_height`: Height of each tile's image area, in character cells, not counting the caption;
+    pub fn new(columns: i32, tile_width: i32, tile_height: i32) -> Result<Self, ChafaError> {
+        if columns <= 0 || tile_width <= 0 || tile_height <= 0 {
+            return Err(ChafaError::InvalidInput(format!(
+                "Montage needs positive columns and tile size, got {} columns of {}x{}",
+                columns, tile_width, tile_height
+            )));
+        }
+        Ok(Self {
+            columns,
+            tile_width,
+            tile_height,
+            gap: 1,
+            tiles: Vec::new(),
+        })
+    }
+
+    /// Sets the number of empty cells between tiles, both across and down. Defaults to 1.
+    pub fn gap(mut self, gap: i32) -> Self {
+        self.gap = gap.max(0);
+        self
+    }
+
+    /// Appends a tile.
+    /// # Parameters:
+    /// --- `pixels`: RGBA8Unassociated pixel data of the image, with rows of width * 4 bytes;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    /// --- `caption`: Text to show below the image, or an empty string for none;
+    pub fn add(
+        &mut self,
+        pixels: &'a [u8],
+        width: i32,
+        height: i32,
+        caption: &str,
+    ) -> Result<(), ChafaError> {
+        if width <= 0 || height <= 0 || pixels.len() < width as usize * height as usize * 4 {
+            return Err(ChafaError::InvalidInput(format!(
+                "Montage tile of {}x{} needs {} bytes of pixel data, got {}",
+                width,
+                height,
+                width.max(0) as usize * height.max(0) as usize * 4,
+                pixels.len()
+            )));
+        }
+        self.tiles.push(Tile {
+            pixels,
+            width,
+            height,
+            caption: caption.to_owned(),
+        });
+        Ok(())
+    }
+
+    /// Appends a tile showing the first frame of an animation, e.g. one from `loader::load_file()`.
+    pub fn add_animation(
+        &mut self,
+        animation: &'a Animation,
+        caption: &str,
+    ) -> Result<(), ChafaError> {
+        let frame = animation.frames().first().ok_or_else(|| {
+            ChafaError::InvalidInput(String::from("Montage tile animation has no frames"))
+        })?;
+        self.add(
+            &frame.pixels,
+            animation.width(),
+            animation.height(),
+            caption,
+        )
+    }
+
+    /// Returns the size of the montage in character cells.
+    pub fn geometry(&self) -> (i32, i32) {
+        let rows = (self.tiles.len() as i32 + self.columns - 1) / self.columns;
+        let columns = (self.tiles.len() as i32).min(self.columns);
+        (
+            (columns * (self.tile_width + self.gap) - self.gap).max(0),
+            (rows * (self.row_height() + self.gap) - self.gap).max(0),
+        )
+    }
+
+    /// Draws the montage on a new canvas, ready to print.
+    /// # Parameters:
+    /// --- `config`: Canvas configuration to use. It is copied, and its geometry replaced with that of the montage;
+    pub fn render(&self, config: &Config) -> Result<Canvas, ChafaError> {
+        if self.tiles.is_empty() {
+            return Err(ChafaError::InvalidInput(String::from(
+                "Montage has no tiles",
+            )));
+        }
+        let config = config.copy()?;
+        let (columns, rows) = self.geometry();
+        config.set_geometry(columns, rows)?;
+
+        // Pixels per cell to compose at, and the shape of a cell on screen.
+        let (cell_width, cell_height) = config.get_cell_geometry();
+        let font_ratio = if cell_width > 0 && cell_height > 0 {
+            cell_width as f32 / cell_height as f32
+        } else {
+            misc::default_font_ratio()
+        };
+        let (px_width, px_height) =
+            if config.get_pixel_mode() != PixelMode::Symbols && cell_width > 0 && cell_height > 0 {
+                (cell_width, cell_height)
+            } else {
+                (SYMBOL_WIDTH_PIXELS as i32, SYMBOL_HEIGHT_PIXELS as i32)
+            };
+
+        let (width, height) = (columns * px_width, rows * px_height);
+        let mut pixels = vec![0u8; width as usize * height as usize * 4];
+        for (index, tile) in self.tiles.iter().enumerate() {
+            let (x, y) = self.tile_origin(index);
+            // Fit in cells, counting a cell as font_ratio times as wide as it is high.
+            let aspect = tile.width as f32 / tile.height as f32 / font_ratio;
+            let (fit_width, fit_height) =
+                if aspect > self.tile_width as f32 / self.tile_height as f32 {
+                    (self.tile_width as f32, self.tile_width as f32 / aspect)
+                } else {
+                    (self.tile_height as f32 * aspect, self.tile_height as f32)
+                };
+            let scaled_width =
+                ((fit_width * px_width as f32).round() as i32).clamp(1, self.tile_width * px_width);
+            let scaled_height = ((fit_height * px_height as f32).round() as i32)
+                .clamp(1, self.tile_height * px_height);
+            let scaled = misc::rescale_pixels(
+                tile.pixels,
+                (tile.width, tile.height, tile.width * 4),
+                4,
+                scaled_width,
+                scaled_height,
+            );
+
+            let left = (x * px_width + (self.tile_width * px_width - scaled_width) / 2) as usize;
+            let top = (y * px_height + (self.tile_height * px_height - scaled_height) / 2) as usize;
+            let row_bytes = scaled_width as usize * 4;
+            for (row, src) in scaled.chunks(row_bytes).enumerate() {
+                let start = ((top + row) * width as usize + left) * 4;
+                pixels[start..start + row_bytes].copy_from_slice(src);
+            }
+        }
+
+        let canvas = Canvas::new(&config)?;
+        canvas.set_pixels(
+            &pixels,
+            misc::PixelType::RGBA8Unassociated,
+            width,
+            height,
+            width * 4,
+        )?;
+        for (index, tile) in self.tiles.iter().enumerate() {
+            self.draw_caption(&canvas, index, &tile.caption);
+        }
+        Ok(canvas)
+    }
+
+    fn row_height(&self) -> i32 {
+        let captions = self.tiles.iter().any(|tile| !tile.caption.is_empty());
+        self.tile_height + i32::from(captions)
+    }
+
+    /// Returns the cell of the top left corner of the tile at index.
+    fn tile_origin(&self, index: usize) -> (i32, i32) {
+        let (column, row) = (index as i32 % self.columns, index as i32 / self.columns);
+        (
+            column * (self.tile_width + self.gap),
+            row * (self.row_height() + self.gap),
+        )
+    }
+
+    fn draw_caption(&self, canvas: &Canvas, index: usize, caption: &str) {
+        let mut text = Vec::new();
+        let mut used = 0;
+        for c in caption.chars().filter(|c| !c.is_control()) {
+            let width = c.width().unwrap_or(0) as i32;
+            if width == 0 {
+                continue;
+            }
+            if used + width > self.tile_width {
+                break;
+            }
+            text.push(c);
+            used += width;
+        }
+
+        let (x, y) = self.tile_origin(index);
+        let mut x = x + (self.tile_width - used) / 2;
+        for c in text {
+            x += canvas.set_char_at(c, x, y + self.tile_height);
+        }
+    }
+}
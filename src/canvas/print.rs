@@ -0,0 +1,114 @@
+/// Options controlling how a canvas' contents are turned into printable output, on top of what the canvas configuration already determines.
+#[derive(Clone)]
+pub struct PrintOptions {
+    /// Wraps the output in DEC synchronized update sequences (BSU/ESU) if the terminal supports them, so large frames are presented all at once instead of tearing.
+    ///
+    /// Ignored when no terminal info is provided or `Info::supports_synchronized_update()` is `false`, which it is until set from a probe.
+    pub synchronized_update: bool,
+    /// Moves the cursor before the output so successive frames overwrite each other in place. Any final newline is omitted when this is enabled.
+    pub reposition: Reposition,
+    /// Whether the output ends in a newline. Chafa never emits one after the last row, so this defaults to `false`.
+    ///
+    /// Ignored when `reposition` is enabled.
+    pub trailing_newline: bool,
+    /// Whether the attribute reset sequence at the end of the output is kept. Defaults to `true`.
+    ///
+    /// Disabling this is useful when embedding the output in prompts, status lines or other widgets that manage attributes themselves.
+    pub trailing_reset: bool,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            synchronized_update: false,
+            reposition: Reposition::None,
+            trailing_newline: false,
+            trailing_reset: true,
+        }
+    }
+}
+
+impl PrintOptions {
+    /// Creates a new set of print options with default settings. The output will be identical to that of `Canvas::create_string()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the output is wrapped in synchronized update sequences.
+    pub fn synchronized_update(mut self, enable: bool) -> Self {
+        self.synchronized_update = enable;
+        self
+    }
+
+    /// Sets how the cursor is moved before the output.
+    pub fn reposition(mut self, reposition: Reposition) -> Self {
+        self.reposition = reposition;
+        self
+    }
+
+    /// Sets whether the output ends in a newline.
+    pub fn trailing_newline(mut self, enable: bool) -> Self {
+        self.trailing_newline = enable;
+        self
+    }
+
+    /// Sets whether the attribute reset sequence at the end of the output is kept.
+    pub fn trailing_reset(mut self, enable: bool) -> Self {
+        self.trailing_reset = enable;
+        self
+    }
+
+    /// Creates print options suitable for animation playback: frames are drawn in place, relative to where the previous frame started, using synchronized updates where supported.
+    ///
+    /// The first frame should be printed without repositioning, since there is no previous frame to rewind over.
+    pub fn animation() -> Self {
+        Self::new()
+            .synchronized_update(true)
+            .reposition(Reposition::Rewind)
+    }
+}
+
+/// How the cursor is moved before printing a canvas.
+#[derive(Clone, Copy, Default)]
+pub enum Reposition {
+    /// Print at the current cursor position.
+    #[default]
+    None,
+    /// Move the cursor to the top left corner of the screen first.
+    Home,
+    /// Move the cursor back to the first column of the row where a previous frame of the same height started. Assumes the cursor was left on the last row of that frame.
+    Rewind,
+}
+
+/// Begin Synchronized Update (DECSET 2026).
+pub const SYNC_UPDATE_BEGIN: &str = "\x1b[?2026h";
+/// End Synchronized Update (DECRST 2026).
+pub const SYNC_UPDATE_END: &str = "\x1b[?2026l";
+
+/// Checks that output only holds 7-bit ASCII text, newlines and SGR sequences (`ESC [ params m`), as produced with `Config::new_ascii()`.
+pub fn is_ascii_sgr_only(output: &[u8]) -> bool {
+    let mut i = 0;
+    while i < output.len() {
+        match output[i] {
+            0x1b => {
+                if output.get(i + 1) != Some(&b'[') {
+                    return false;
+                }
+                i += 2;
+                while output
+                    .get(i)
+                    .is_some_and(|b| b.is_ascii_digit() || *b == b';')
+                {
+                    i += 1;
+                }
+                if output.get(i) != Some(&b'm') {
+                    return false;
+                }
+            }
+            b'\n' | 0x20..=0x7e => {}
+            _ => return false,
+        }
+        i += 1;
+    }
+    true
+}
@@ -0,0 +1,94 @@
+use crate::canvas::{Canvas, PixelMode};
+use crate::{ChafaError, SYMBOL_HEIGHT_PIXELS, SYMBOL_WIDTH_PIXELS, misc};
+use fast_image_resize::images::{Image, ImageRef};
+use fast_image_resize::{FilterType, ResizeAlg, ResizeOptions, Resizer};
+use std::borrow::Cow;
+
+impl Canvas {
+    /// Like `set_pixels()`, but first shrinks large images with fast_image_resize to about the resolution the canvas can show: SYMBOL_WIDTH_PIXELS x SYMBOL_HEIGHT_PIXELS per cell in symbol mode, or the cell geometry in pixel modes. Chafa's own scaler is slow on multi-megapixel sources, so this can cut render time by an order of magnitude.
+    ///
+    /// The aspect ratio is kept and the result is never smaller than the canvas in either direction, so chafa still does the final fit. Images that are already small enough, and pixel modes without a known cell geometry, are drawn as they are.
+    /// # Parameters:
+    /// --- `pixels`: Image bytes;
+    /// --- `pixel_type`: Pixel format of pixels;
+    /// --- `src_width`: Width in pixels of source pixel data;
+    /// --- `src_height`: Height in pixels of source pixel data;
+    /// --- `src_rowstride`: Number of bytes between the start of each pixel row;
+    pub fn draw_prescaled(
+        &self,
+        pixels: &[u8],
+        pixel_type: misc::PixelType,
+        src_width: i32,
+        src_height: i32,
+        src_rowstride: i32,
+    ) -> Result<(), ChafaError> {
+        let config = self.config()?;
+        let (columns, rows) = config.get_geometry();
+        let (cell_width, cell_height) = match config.get_pixel_mode() {
+            PixelMode::Symbols => (SYMBOL_WIDTH_PIXELS as i32, SYMBOL_HEIGHT_PIXELS as i32),
+            _ => config.get_cell_geometry(),
+        };
+        let Some((width, height)) = prescaled_size(
+            (src_width, src_height),
+            (columns * cell_width, rows * cell_height),
+        ) else {
+            return self.set_pixels(pixels, pixel_type, src_width, src_height, src_rowstride);
+        };
+
+        misc::region_offset(
+            pixels.len(),
+            pixel_type,
+            (src_width, src_height, src_rowstride),
+            misc::Rect::new(0, 0, src_width, src_height),
+        )?;
+        let bpp = pixel_type.bytes_per_pixel();
+        let row_bytes = src_width as usize * bpp;
+        // fast_image_resize wants rows without padding.
+        let packed: Cow<[u8]> = if src_rowstride as usize == row_bytes {
+            Cow::Borrowed(&pixels[..row_bytes * src_height as usize])
+        } else {
+            Cow::Owned(
+                pixels
+                    .chunks(src_rowstride as usize)
+                    .take(src_height as usize)
+                    .flat_map(|row| &row[..row_bytes])
+                    .copied()
+                    .collect(),
+            )
+        };
+
+        let resize_type = if bpp == 3 {
+            fast_image_resize::PixelType::U8x3
+        } else {
+            fast_image_resize::PixelType::U8x4
+        };
+        let error = |e: &dyn std::fmt::Display| {
+            ChafaError::InvalidInput(format!("Failed to prescale image: {}", e))
+        };
+        let src = ImageRef::new(src_width as u32, src_height as u32, &packed, resize_type)
+            .map_err(|e| error(&e))?;
+        let mut dst = Image::new(width as u32, height as u32, resize_type);
+        // Alpha weighting only knows about unassociated alpha in the last channel.
+        let options = ResizeOptions::new()
+            .resize_alg(ResizeAlg::Convolution(FilterType::Bilinear))
+            .use_alpha(pixel_type.alpha_index() == Some(3) && !pixel_type.is_premultiplied());
+        Resizer::new()
+            .resize(&src, &mut dst, &options)
+            .map_err(|e| error(&e))?;
+
+        self.set_pixels(dst.buffer(), pixel_type, width, height, width * bpp as i32)
+    }
+}
+
+/// Returns the smallest size with the aspect ratio of src that covers target, or None if src is no larger than that.
+fn prescaled_size(src: (i32, i32), target: (i32, i32)) -> Option<(i32, i32)> {
+    if src.0 <= 0 || src.1 <= 0 || target.0 <= 0 || target.1 <= 0 {
+        return None;
+    }
+    let scale = (target.0 as f64 / src.0 as f64).max(target.1 as f64 / src.1 as f64);
+    let size = (
+        (src.0 as f64 * scale).ceil() as i32,
+        (src.1 as f64 * scale).ceil() as i32,
+    );
+    (size.0 < src.0 && size.1 < src.1).then_some(size)
+}
@@ -0,0 +1,82 @@
+/*
+ * Grabs the contents of displays and windows into frames, through xcap.
+ */
+
+use crate::{ChafaError, Frame, misc};
+
+pub use xcap::{Monitor, Window};
+
+/// Captures the primary display.
+pub fn primary_monitor() -> Result<Frame, ChafaError> {
+    let monitors = Monitor::all().map_err(capture_error)?;
+    let mut primary = None;
+    for monitor in monitors {
+        if monitor.is_primary().map_err(capture_error)? {
+            primary = Some(monitor);
+            break;
+        }
+    }
+    let monitor = primary
+        .ok_or_else(|| ChafaError::InvalidInput(String::from("No primary display found")))?;
+    capture_monitor(&monitor)
+}
+
+/// Captures the display containing a point of the desktop.
+/// # Parameters:
+/// --- `x`: Horizontal desktop coordinate, in pixels;
+/// --- `y`: Vertical desktop coordinate, in pixels;
+pub fn monitor_at(x: i32, y: i32) -> Result<Frame, ChafaError> {
+    capture_monitor(&Monitor::from_point(x, y).map_err(capture_error)?)
+}
+
+/// Captures a display. Use `Monitor::all()` to list them.
+pub fn capture_monitor(monitor: &Monitor) -> Result<Frame, ChafaError> {
+    to_frame(monitor.capture_image().map_err(capture_error)?)
+}
+
+/// Captures the topmost window whose title or application name contains a string. Case is ignored. Minimized windows are skipped, since there is nothing to capture.
+/// # Parameters:
+/// --- `name`: Part of the window title or application name to look for;
+pub fn window(name: &str) -> Result<Frame, ChafaError> {
+    let name = name.to_lowercase();
+    let matches = |window: &Window| {
+        let title = window.title().unwrap_or_default().to_lowercase();
+        let app_name = window.app_name().unwrap_or_default().to_lowercase();
+        !window.is_minimized().unwrap_or(false)
+            && (title.contains(&name) || app_name.contains(&name))
+    };
+    // Windows are listed from front to back.
+    let window = Window::all()
+        .map_err(capture_error)?
+        .into_iter()
+        .find(matches)
+        .ok_or_else(|| ChafaError::InvalidInput(format!("No window matching \"{}\"", name)))?;
+    capture_window(&window)
+}
+
+/// Captures a window. Use `Window::all()` to list them.
+pub fn capture_window(window: &Window) -> Result<Frame, ChafaError> {
+    to_frame(window.capture_image().map_err(capture_error)?)
+}
+
+fn to_frame(image: xcap::image::RgbaImage) -> Result<Frame, ChafaError> {
+    let (width, height) = (image.width() as i32, image.height() as i32);
+    Frame::new(
+        image.as_raw(),
+        misc::PixelType::RGBA8Unassociated,
+        width,
+        height,
+        width * 4,
+    )
+}
+
+fn capture_error(e: xcap::XCapError) -> ChafaError {
+    match e {
+        #[cfg(target_os = "linux")]
+        xcap::XCapError::StdIOError(e) => ChafaError::Io(std::io::Error::new(
+            e.kind(),
+            format!("Screen capture failed: {}", e),
+        )),
+        e => ChafaError::InvalidInput(format!("Screen capture failed: {}", e)),
+    }
+}
@@ -0,0 +1,126 @@
+use crate::ffi;
+
+/// Errors reported by the chafa wrappers.
+///
+/// `std::io::Error` can't be cloned or compared, so `Io` errors are cloned as a new error of the same kind and message, and compare equal when their kind and message match.
+#[derive(Debug)]
+pub enum ChafaError {
+    /// A string argument contained an interior NUL byte and could not be passed to chafa.
+    Nul,
+    /// A control sequence would exceed CHAFA_TERM_SEQ_LENGTH_MAX bytes when formatted.
+    SeqTooLong,
+    /// An argument was rejected before reaching chafa; carries a description.
+    InvalidInput(String),
+    /// Chafa reported an error through a GError; carries its message.
+    GLib(String),
+    /// A chafa call failed without giving a reason.
+    Failed(&'static str),
+    /// The chafa library loaded at runtime lacks the API of a release the headers the bindings were generated from include. The headers' version is (major, minor, micro).
+    VersionMismatch {
+        missing: crate::ApiLevel,
+        headers: (u32, u32, u32),
+    },
+    /// Reading or writing a file, pipe or device failed.
+    Io(std::io::Error),
+}
+
+impl ChafaError {
+    /// Takes ownership of a GError set by chafa, frees it and returns the corresponding error.
+    ///
+    /// # Safety:
+    /// `error` must be a valid, non-null GError that is not used afterwards.
+    pub(crate) unsafe fn from_gerror(error: *mut ffi::GError) -> Self {
+        unsafe {
+            let msg = std::ffi::CStr::from_ptr((*error).message as *const std::os::raw::c_char)
+                .to_string_lossy()
+                .into_owned();
+            ffi::g_error_free(error);
+            ChafaError::GLib(msg)
+        }
+    }
+}
+
+impl std::fmt::Display for ChafaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChafaError::Nul => write!(f, "Chafa -> String contains an interior NUL byte"),
+            ChafaError::SeqTooLong => write!(f, "Chafa -> Control sequence is too long"),
+            ChafaError::InvalidInput(msg) => write!(f, "Chafa -> Invalid input: {}", msg),
+            ChafaError::GLib(msg) => write!(f, "Chafa -> {}", msg),
+            ChafaError::Failed(msg) => write!(f, "{}", msg),
+            ChafaError::Io(e) => write!(f, "Chafa -> {}", e),
+            ChafaError::VersionMismatch { missing, headers } => write!(
+                f,
+                "Chafa -> Loaded library lacks the API of chafa {}.{}, which the {}.{}.{} headers the bindings were built against have",
+                missing.version().0,
+                missing.version().1,
+                headers.0,
+                headers.1,
+                headers.2
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChafaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChafaError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Clone for ChafaError {
+    fn clone(&self) -> Self {
+        match self {
+            ChafaError::Nul => ChafaError::Nul,
+            ChafaError::SeqTooLong => ChafaError::SeqTooLong,
+            ChafaError::InvalidInput(msg) => ChafaError::InvalidInput(msg.clone()),
+            ChafaError::GLib(msg) => ChafaError::GLib(msg.clone()),
+            ChafaError::Failed(msg) => ChafaError::Failed(msg),
+            ChafaError::VersionMismatch { missing, headers } => ChafaError::VersionMismatch {
+                missing: *missing,
+                headers: *headers,
+            },
+            ChafaError::Io(e) => ChafaError::Io(std::io::Error::new(e.kind(), e.to_string())),
+        }
+    }
+}
+
+impl PartialEq for ChafaError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ChafaError::Nul, ChafaError::Nul) => true,
+            (ChafaError::SeqTooLong, ChafaError::SeqTooLong) => true,
+            (ChafaError::InvalidInput(a), ChafaError::InvalidInput(b)) => a == b,
+            (ChafaError::GLib(a), ChafaError::GLib(b)) => a == b,
+            (ChafaError::Failed(a), ChafaError::Failed(b)) => a == b,
+            (
+                ChafaError::VersionMismatch { missing, headers },
+                ChafaError::VersionMismatch {
+                    missing: other_missing,
+                    headers: other_headers,
+                },
+            ) => missing == other_missing && headers == other_headers,
+            (ChafaError::Io(a), ChafaError::Io(b)) => {
+                a.kind() == b.kind() && a.to_string() == b.to_string()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ChafaError {}
+
+impl From<std::io::Error> for ChafaError {
+    fn from(e: std::io::Error) -> Self {
+        ChafaError::Io(e)
+    }
+}
+
+impl From<std::ffi::NulError> for ChafaError {
+    fn from(_: std::ffi::NulError) -> Self {
+        ChafaError::Nul
+    }
+}
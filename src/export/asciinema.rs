@@ -0,0 +1,149 @@
+/*
+ * https://docs.asciinema.org/manual/asciicast/v2/
+ */
+
+use std::io::Write;
+use std::time::Duration;
+
+/// An asciicast v2 recording made up of rendered frames. Each frame is stored as an output event and written out as a `.cast` file that can be played back with asciinema.
+pub struct Cast {
+    /// Width of the recorded terminal, in character cells.
+    pub width: u32,
+    /// Height of the recorded terminal, in character cells.
+    pub height: u32,
+    /// Unix timestamp of the beginning of the recording, in seconds.
+    pub timestamp: Option<u64>,
+    /// Title of the recording.
+    pub title: Option<String>,
+    /// Value of the `TERM` variable to store in the header.
+    pub term: Option<String>,
+    /// Whether to move the cursor to the top left corner before each frame, so successive frames overwrite each other.
+    pub home_cursor: bool,
+    frames: Vec<(Duration, String)>,
+}
+
+impl Cast {
+    /// Creates a new, empty recording.
+    /// # Parameters:
+    /// --- `width`: Width of the terminal, in character cells;
+    /// --- `height`: Height of the terminal, in character cells.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            timestamp: None,
+            title: None,
+            term: None,
+            home_cursor: true,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Appends a frame to the recording.
+    /// # Parameters:
+    /// --- `time`: Time since the beginning of the recording at which the frame is shown;
+    /// --- `output`: Rendered frame, e.g. the result of `Canvas::create_string()`.
+    pub fn push_frame(&mut self, time: Duration, output: &str) {
+        self.frames.push((time, output.to_owned()));
+    }
+
+    /// Returns the number of frames in the recording.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if the recording contains no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Writes the recording in asciicast v2 format: a JSON header line followed by one output event per frame.
+    ///
+    /// Players replay output as it is, without the tty that turns line feeds into CR LF, so line feeds in frames are written as CR LF to keep rows from drifting right.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let mut header = format!(
+            "{{\"version\": 2, \"width\": {}, \"height\": {}",
+            self.width, self.height
+        );
+        if let Some(timestamp) = self.timestamp {
+            header.push_str(&format!(", \"timestamp\": {}", timestamp));
+        }
+        if let Some(title) = &self.title {
+            header.push_str(&format!(", \"title\": \"{}\"", escape_json(title)));
+        }
+        if let Some(term) = &self.term {
+            header.push_str(&format!(
+                ", \"env\": {{\"TERM\": \"{}\"}}",
+                escape_json(term)
+            ));
+        }
+        header.push('}');
+        writeln!(writer, "{}", header)?;
+
+        let mut frames: Vec<&(Duration, String)> = self.frames.iter().collect();
+        frames.sort_by_key(|(time, _)| *time);
+
+        for (time, output) in frames {
+            let output = to_crlf(output);
+            let data = if self.home_cursor {
+                format!("\x1b[H{}", output)
+            } else {
+                output
+            };
+            writeln!(
+                writer,
+                "[{:.6}, \"o\", \"{}\"]",
+                time.as_secs_f64(),
+                escape_json(&data)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the recording as a string in asciicast v2 format.
+    pub fn to_cast_string(&self) -> String {
+        let mut buf: Vec<u8> = Vec::new();
+        self.write_to(&mut buf)
+            .expect("Writing to a Vec<u8> should never fail");
+        String::from_utf8(buf).expect("Cast output should always be valid UTF-8")
+    }
+
+    /// Writes the recording to a `.cast` file at `path`.
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut file)?;
+        file.flush()
+    }
+}
+
+/// Turns every bare LF in s into CR LF, leaving existing CR LF pairs alone.
+fn to_crlf(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + s.len() / 16);
+    let mut previous = '\0';
+    for c in s.chars() {
+        if c == '\n' && previous != '\r' {
+            out.push('\r');
+        }
+        out.push(c);
+        previous = c;
+    }
+    out
+}
+
+fn escape_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
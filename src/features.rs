@@ -2,10 +2,12 @@
  * https://hpjansson.org/chafa/ref/chafa-Features.html#
  */
 
-use crate::ffi;
+use crate::{ChafaError, ffi};
 
 bitflags::bitflags! {
+    /// CPU features chafa can use to speed up rendering. These are all chafa has, and all of them are x86 extensions; it has no ARM-specific code paths such as NEON.
     #[repr(transparent)]
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
     pub struct Features: u32 {
         /// Flag indicating MMX support.
         const MMX = ffi::ChafaFeatures_CHAFA_FEATURE_MMX;
@@ -18,24 +20,193 @@ bitflags::bitflags! {
     }
 }
 
+impl Features {
+    /// Returns the name of a single flag as `describe_features()` spells it, e.g. "sse4.1", or `None` for a combination of flags.
+    pub fn name(&self) -> Option<&'static str> {
+        match *self {
+            Features::MMX => Some("mmx"),
+            Features::SSE41 => Some("sse4.1"),
+            Features::POPCNT => Some("popcnt"),
+            Features::AVX2 => Some("avx2"),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Features {
+    /// Writes the names of the flags separated by spaces, e.g. "mmx sse4.1", as `describe_features()` does but without calling into chafa.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, feature) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(feature.name().unwrap_or("unknown"))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Features {
+    type Err = ChafaError;
+
+    /// Parses names separated by spaces or commas, e.g. "mmx sse4.1" as `Display` writes them. Matching ignores ASCII case, and an empty string gives no features.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut features = Features::empty();
+        for name in s.split([' ', ',']).filter(|name| !name.is_empty()) {
+            let feature = Features::all()
+                .iter()
+                .find(|feature| feature.name().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+                .ok_or_else(|| {
+                    ChafaError::InvalidInput(format!("Unknown CPU feature: {}", name))
+                })?;
+            features.insert(feature);
+        }
+        Ok(features)
+    }
+}
+
+impl std::fmt::Debug for Features {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Features(")?;
+        if self.is_empty() {
+            f.write_str("empty")?;
+        }
+        for (index, (name, _)) in self.iter_names().enumerate() {
+            if index > 0 {
+                f.write_str(" | ")?;
+            }
+            f.write_str(name)?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// The CPU features chafa was built with next to those the CPU supports. Only features in both are used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FeatureReport {
+    /// Features the library was built with.
+    pub builtin: Features,
+    /// Features the CPU supports.
+    pub supported: Features,
+}
+
+impl FeatureReport {
+    /// Queries the running library and CPU.
+    pub fn current() -> Self {
+        Self {
+            builtin: get_builtin_features(),
+            supported: get_supported_features(),
+        }
+    }
+
+    /// Features chafa actually uses: built in and supported by the CPU.
+    pub fn active(&self) -> Features {
+        self.builtin & self.supported
+    }
+
+    /// Features the library was built with but the CPU lacks.
+    pub fn unsupported(&self) -> Features {
+        self.builtin - self.supported
+    }
+
+    /// Features the CPU has but the library was built without.
+    pub fn unused(&self) -> Features {
+        self.supported - self.builtin
+    }
+
+    /// Lists every mismatch between the builtin and supported features, one warning per feature.
+    pub fn warnings(&self) -> Vec<FeatureWarning> {
+        self.unsupported()
+            .iter()
+            .map(FeatureWarning::Unsupported)
+            .chain(self.unused().iter().map(FeatureWarning::Unused))
+            .collect()
+    }
+}
+
+/// A mismatch between the CPU features chafa was built with and those the CPU has. Either way chafa falls back to slower code paths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FeatureWarning {
+    /// The library was built with a feature the CPU lacks, so it goes unused.
+    Unsupported(Features),
+    /// The CPU has a feature the library was built without.
+    Unused(Features),
+}
+
+impl std::fmt::Display for FeatureWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureWarning::Unsupported(feature) => {
+                write!(f, "built with {} but the CPU lacks it", feature)
+            }
+            FeatureWarning::Unused(feature) => {
+                write!(
+                    f,
+                    "the CPU supports {} but chafa was built without it",
+                    feature
+                )
+            }
+        }
+    }
+}
+
+/// Compares the CPU features chafa was built with against those the CPU supports, for logging performance problems at startup.
+/// # Returns:
+/// A warning for each mismatched feature; empty if chafa uses everything the CPU offers.
+pub fn verify() -> Vec<FeatureWarning> {
+    FeatureReport::current().warnings()
+}
+
+impl std::fmt::Display for FeatureReport {
+    /// Writes one line per feature, e.g. "avx2: built in, supported".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, feature) in Features::all().iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{}: {}, {}",
+                feature.name().unwrap_or("unknown"),
+                if self.builtin.contains(feature) {
+                    "built in"
+                } else {
+                    "not built in"
+                },
+                if self.supported.contains(feature) {
+                    "supported"
+                } else {
+                    "not supported"
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// Gets a list of the platform-specific features this library was built with.
 pub fn get_builtin_features() -> Features {
     let feats = unsafe { ffi::chafa_get_builtin_features() };
     Features::from_bits_truncate(feats as u32)
 }
 
+/// Gets a list of the platform-specific features the CPU supports, whether or not the library was built with them.
 pub fn get_supported_features() -> Features {
     let feats = unsafe { ffi::chafa_get_supported_features() };
     Features::from_bits_truncate(feats as u32)
 }
 
 /// Takes a set of flags potentially returned from chafa_get_builtin_features() or chafa_get_supported_features() and generates a human-readable ASCII string descriptor.
-pub fn describe_features(features: Features) -> Result<String, &'static str> {
+///
+/// Formatting features with `Display` gives the same names without going through chafa.
+pub fn describe_features(features: Features) -> Result<String, ChafaError> {
     unsafe {
         let str_p = ffi::chafa_describe_features(features.bits() as u32);
 
         if str_p.is_null() {
-            return Err("Chafa -> Failed to create string from Features");
+            return Err(ChafaError::Failed(
+                "Chafa -> Failed to create string from Features",
+            ));
         } else {
             let str = std::ffi::CStr::from_ptr((*str_p) as *const std::os::raw::c_char)
                 .to_string_lossy()
@@ -2,11 +2,20 @@
  * https://hpjansson.org/chafa/ref/chafa-ChafaFrame.html
  */
 
-use crate::{ffi, misc};
+use crate::{ChafaError, ffi, misc, pixels};
+use std::any::Any;
+use std::rc::Rc;
 
 /// A ChafaFrame contains the specific of a single frame of image data. It can be added to a ChafaImage.
 pub struct Frame {
     pub raw: *mut ffi::ChafaFrame,
+    // Chafa has no getters for these, so they are kept from creation.
+    pixel_type: misc::PixelType,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+    // Keeps data that chafa borrows, such as a memory map, alive as long as the frame.
+    owner: Option<Rc<dyn Any>>,
 }
 
 impl Frame {
@@ -17,13 +26,30 @@ impl Frame {
     /// --- `width`: Width of the image, in pixels;
     /// --- `height`: Height of the image, in pixels;
     /// --- `rowstride`: Number of bytes to advance from the start of one row to the next.
+    ///
+    /// Empty images, rowstrides shorter than a row and data too small for the image are rejected with `ChafaError::InvalidInput`. The last row may stop short of a full rowstride, as it does in GdkPixbufs; such data is padded before chafa copies it.
+    ///
+    /// Alpha isn't converted: pixel_type must say whether data is premultiplied, and chafa handles either. Use `pixels::premultiply()` or `pixels::unpremultiply()` beforehand if data has to be in the other form for some other reason.
     pub fn new(
         data: &[u8],
         pixel_type: misc::PixelType,
         width: i32,
         height: i32,
         rowstride: i32,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, ChafaError> {
+        misc::validate_image(Some(data.len()), pixel_type, (width, height, rowstride))?;
+        // chafa_frame_new() copies height * rowstride bytes, padding after the last row included.
+        let full = height as usize * rowstride as usize;
+        let padded;
+        let data = if data.len() < full {
+            let mut buffer = Vec::with_capacity(full);
+            buffer.extend_from_slice(data);
+            buffer.resize(full, 0);
+            padded = buffer;
+            &padded[..]
+        } else {
+            data
+        };
         let raw = unsafe {
             ffi::chafa_frame_new(
                 data.as_ptr() as *const _,
@@ -34,9 +60,16 @@ impl Frame {
             )
         };
         if raw.is_null() {
-            Err("Chafa -> Failed to create Frame")
+            Err(ChafaError::Failed("Chafa -> Failed to create Frame"))
         } else {
-            Ok(Self { raw })
+            Ok(Self {
+                raw,
+                pixel_type,
+                width,
+                height,
+                rowstride,
+                owner: None,
+            })
         }
     }
 
@@ -53,14 +86,323 @@ impl Frame {
         width: i32,
         height: i32,
         rowstride: i32,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, ChafaError> {
         let raw = unsafe {
             ffi::chafa_frame_new_steal(data as *mut _, pixel_type as u32, width, height, rowstride)
         };
         if raw.is_null() {
-            Err("Chafa -> Failed to create Frame")
+            Err(ChafaError::Failed("Chafa -> Failed to create Frame"))
         } else {
-            Ok(Self { raw })
+            Ok(Self {
+                raw,
+                pixel_type,
+                width,
+                height,
+                rowstride,
+                owner: None,
+            })
+        }
+    }
+
+    /// The ChafaPixelType of the frame's data.
+    pub fn pixel_type(&self) -> misc::PixelType {
+        self.pixel_type
+    }
+
+    /// Width of the frame, in pixels.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Height of the frame, in pixels.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Number of bytes from the start of one row of the frame's data to the next.
+    pub fn rowstride(&self) -> i32 {
+        self.rowstride
+    }
+}
+
+impl Frame {
+    /// Creates a new ChafaFrame containing a copy of a region of the image data pointed to by data.
+    /// # Parameters:
+    /// --- `data`: Image data buffer to copy from;
+    /// --- `pixel_type`: The ChafaPixelType of the source data;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    /// --- `rowstride`: Number of bytes to advance from the start of one row to the next;
+    /// --- `region`: Part of the image to copy;
+    pub fn cropped(
+        data: &[u8],
+        pixel_type: misc::PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+        region: misc::Rect,
+    ) -> Result<Self, ChafaError> {
+        let offset =
+            misc::region_offset(data.len(), pixel_type, (width, height, rowstride), region)?;
+        // chafa copies whole rows, which would run past the end of data for the last one.
+        let row_bytes = region.width as usize * pixel_type.bytes_per_pixel();
+        let pixels: Vec<u8> = data[offset..]
+            .chunks(rowstride as usize)
+            .take(region.height as usize)
+            .flat_map(|row| &row[..row_bytes])
+            .copied()
+            .collect();
+        Self::new(
+            &pixels,
+            pixel_type,
+            region.width,
+            region.height,
+            row_bytes as i32,
+        )
+    }
+
+    /// Creates a new ChafaFrame containing a rotated copy of the image data pointed to by data, e.g. for a viewer's rotation keys. Quarter turns swap the width and height.
+    /// # Parameters:
+    /// --- `data`: Image data buffer to copy from;
+    /// --- `pixel_type`: The ChafaPixelType of the source data;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    /// --- `rowstride`: Number of bytes to advance from the start of one row to the next;
+    /// --- `rotation`: How far to turn the image;
+    pub fn rotated(
+        data: &[u8],
+        pixel_type: misc::PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+        rotation: pixels::Rotation,
+    ) -> Result<Self, ChafaError> {
+        let (pixels, width, height) =
+            pixels::rotate(data, pixel_type, width, height, rowstride, rotation)?;
+        let rowstride = width * pixel_type.bytes_per_pixel() as i32;
+        Self::new(&pixels, pixel_type, width, height, rowstride)
+    }
+
+    /// Creates a new ChafaFrame containing a copy of the image data pointed to by data, mirrored left to right. See `rotated()` for the parameters.
+    pub fn flipped_h(
+        data: &[u8],
+        pixel_type: misc::PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+    ) -> Result<Self, ChafaError> {
+        let pixels = pixels::flip_horizontal(data, pixel_type, width, height, rowstride)?;
+        let rowstride = width * pixel_type.bytes_per_pixel() as i32;
+        Self::new(&pixels, pixel_type, width, height, rowstride)
+    }
+
+    /// Creates a new ChafaFrame containing a copy of the image data pointed to by data, mirrored top to bottom. See `rotated()` for the parameters.
+    pub fn flipped_v(
+        data: &[u8],
+        pixel_type: misc::PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+    ) -> Result<Self, ChafaError> {
+        let pixels = pixels::flip_vertical(data, pixel_type, width, height, rowstride)?;
+        let rowstride = width * pixel_type.bytes_per_pixel() as i32;
+        Self::new(&pixels, pixel_type, width, height, rowstride)
+    }
+
+    /// Creates a new ChafaFrame from planar YUV 4:2:0 data (I420), as put out by most video decoders and cameras. The U and V planes have half the resolution of the Y plane in both directions, rounded up.
+    ///
+    /// Samples are taken as limited range BT.601, the usual encoding of video up to SD resolutions, and converted to RGB8 with `pixels::yuv420_to_rgb()`.
+    /// # Parameters:
+    /// --- `y`: Luma plane;
+    /// --- `u`: Blue-difference chroma plane;
+    /// --- `v`: Red-difference chroma plane;
+    /// --- `strides`: Number of bytes from the start of one row to the next in the Y, U and V planes;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    pub fn from_yuv420(
+        y: &[u8],
+        u: &[u8],
+        v: &[u8],
+        strides: [usize; 3],
+        width: i32,
+        height: i32,
+    ) -> Result<Self, ChafaError> {
+        let rgb = pixels::yuv420_to_rgb(y, u, v, strides, width, height)?;
+        Self::new(&rgb, misc::PixelType::RGB8, width, height, width * 3)
+    }
+
+    /// Creates a new ChafaFrame from NV12 data, a Y plane followed by a plane of interleaved U and V samples at half resolution. Hardware video decoders commonly put this out. See `from_yuv420()` for how colors are converted.
+    /// # Parameters:
+    /// --- `y`: Luma plane;
+    /// --- `uv`: Interleaved chroma plane, U first;
+    /// --- `strides`: Number of bytes from the start of one row to the next in the Y and UV planes;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    pub fn from_nv12(
+        y: &[u8],
+        uv: &[u8],
+        strides: [usize; 2],
+        width: i32,
+        height: i32,
+    ) -> Result<Self, ChafaError> {
+        let rgb = pixels::nv12_to_rgb(y, uv, strides, width, height)?;
+        Self::new(&rgb, misc::PixelType::RGB8, width, height, width * 3)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Frame {
+    /// Creates a new ChafaFrame from a decoded image.
+    ///
+    /// 8-bit RGB and RGBA images are copied as they are. Every other color type is converted with `pixels::to_supported()` first, since chafa only takes 8 bits per channel.
+    ///
+    /// The image crate always stores unassociated alpha, so images are passed as RGBA8Unassociated and never premultiplied; automatic premultiplication is out of scope, since chafa takes either form and converts as it needs.
+    /// # Parameters:
+    /// --- `image`: The image to copy from;
+    pub fn from_image(image: &::image::DynamicImage) -> Result<Self, ChafaError> {
+        let (width, height) = (image.width() as i32, image.height() as i32);
+        let (data, pixel_type) = pixels::to_supported(image);
+        let rowstride = width * pixel_type.bytes_per_pixel() as i32;
+        Self::new(&data, pixel_type, width, height, rowstride)
+    }
+}
+
+#[cfg(feature = "gdk-pixbuf")]
+impl Frame {
+    /// Creates a new ChafaFrame from a pixbuf, copying its pixels.
+    ///
+    /// Pixbufs with 3 channels are taken as RGB and those with 4 as RGBA with unassociated alpha, which is how GdkPixbuf stores them, so no premultiplication is done. Rows are read rowstride bytes apart, and the last row may stop short of a full rowstride, as it does in pixbufs; it is padded before chafa copies it. Pixbufs with other layouts, which GdkPixbuf can't produce itself, are rejected with `ChafaError::InvalidInput`.
+    /// # Parameters:
+    /// --- `pixbuf`: The pixbuf to copy from;
+    pub fn from_pixbuf(pixbuf: &gdk_pixbuf::Pixbuf) -> Result<Self, ChafaError> {
+        let pixel_type = match (
+            pixbuf.colorspace(),
+            pixbuf.bits_per_sample(),
+            pixbuf.n_channels(),
+            pixbuf.has_alpha(),
+        ) {
+            (gdk_pixbuf::Colorspace::Rgb, 8, 3, false) => misc::PixelType::RGB8,
+            (gdk_pixbuf::Colorspace::Rgb, 8, 4, true) => misc::PixelType::RGBA8Unassociated,
+            (_, bits, channels, alpha) => {
+                return Err(ChafaError::InvalidInput(format!(
+                    "Unsupported pixbuf layout: {} channels of {} bits{}",
+                    channels,
+                    bits,
+                    if alpha { " with alpha" } else { "" }
+                )));
+            }
+        };
+        let data = pixbuf.read_pixel_bytes();
+        Self::new(
+            &data,
+            pixel_type,
+            pixbuf.width(),
+            pixbuf.height(),
+            pixbuf.rowstride(),
+        )
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Frame {
+    /// Creates a new ChafaFrame that reads its image data straight from a memory map, without copying it. This keeps large raw frames from being loaded into memory twice.
+    ///
+    /// Chafa frees stolen buffers with g_free(), so the map is borrowed instead, and kept alive until the frame and all its clones are dropped. Images holding the frame, and the placements and canvases they are put on, keep it alive too, so the map stays valid for as long as chafa can read it.
+    /// # Parameters:
+    /// --- `mmap`: Memory map holding the image data;
+    /// --- `offset`: Position of the first pixel in the map, e.g. to skip a file header;
+    /// --- `pixel_type`: The ChafaPixelType of the data;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    /// --- `rowstride`: Number of bytes to advance from the start of one row to the next.
+    pub fn from_mmap(
+        mmap: memmap2::Mmap,
+        offset: usize,
+        pixel_type: misc::PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+    ) -> Result<Self, ChafaError> {
+        let data = mmap.get(offset..).ok_or_else(|| {
+            ChafaError::InvalidInput(format!(
+                "Offset {} is past the end of a memory map of {} bytes",
+                offset,
+                mmap.len()
+            ))
+        })?;
+        misc::region_offset(
+            data.len(),
+            pixel_type,
+            (width, height, rowstride),
+            misc::Rect::new(0, 0, width, height),
+        )?;
+        // chafa never writes through the pointer, despite taking it as mutable.
+        let raw = unsafe {
+            ffi::chafa_frame_new_borrow(
+                data.as_ptr() as *mut _,
+                pixel_type as u32,
+                width,
+                height,
+                rowstride,
+            )
+        };
+        if raw.is_null() {
+            return Err(ChafaError::Failed("Chafa -> Failed to create Frame"));
+        }
+        Ok(Self {
+            raw,
+            pixel_type,
+            width,
+            height,
+            rowstride,
+            owner: Some(Rc::new(mmap)),
+        })
+    }
+
+    /// Maps a raw image file into memory and creates a frame reading from it, as `from_mmap()` does.
+    /// # Safety
+    /// The file must not be truncated or written to while the frame is alive, since the frame reads it directly.
+    /// # Parameters:
+    /// --- `path`: Path of the raw image file;
+    /// --- `offset`: Position of the first pixel in the file, e.g. to skip a header;
+    /// --- `pixel_type`: The ChafaPixelType of the data;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    /// --- `rowstride`: Number of bytes to advance from the start of one row to the next.
+    pub unsafe fn map_file<P: AsRef<std::path::Path>>(
+        path: P,
+        offset: usize,
+        pixel_type: misc::PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+    ) -> Result<Self, ChafaError> {
+        let path = path.as_ref();
+        let error = |e: std::io::Error| {
+            ChafaError::Io(std::io::Error::new(
+                e.kind(),
+                format!("Failed to map {}: {}", path.display(), e),
+            ))
+        };
+        let file = std::fs::File::open(path).map_err(error)?;
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(error)?;
+        Self::from_mmap(mmap, offset, pixel_type, width, height, rowstride)
+    }
+}
+
+impl Clone for Frame {
+    /// Adds a reference to the frame. Frames can't be changed once created, so the clones can be shared freely, e.g. between caches and images.
+    fn clone(&self) -> Self {
+        unsafe {
+            ffi::chafa_frame_ref(self.raw);
+        }
+        Self {
+            raw: self.raw,
+            pixel_type: self.pixel_type,
+            width: self.width,
+            height: self.height,
+            rowstride: self.rowstride,
+            owner: self.owner.clone(),
         }
     }
 }
@@ -0,0 +1,313 @@
+use crate::canvas::Config;
+use crate::term::Terminal;
+use crate::{
+    Align, CellGeometry, ChafaError, GeometryRequest, Placement, Rect, Tuck, calc_canvas_geometry,
+    default_font_ratio,
+};
+
+/// Empty cells to leave on each side of a viewport.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Margins {
+    /// Rows to leave above.
+    pub top: i32,
+    /// Columns to leave on the right.
+    pub right: i32,
+    /// Rows to leave below.
+    pub bottom: i32,
+    /// Columns to leave on the left.
+    pub left: i32,
+}
+
+impl Margins {
+    /// Creates margins of the same size on every side.
+    pub fn uniform(cells: i32) -> Self {
+        Self {
+            top: cells,
+            right: cells,
+            bottom: cells,
+            left: cells,
+        }
+    }
+}
+
+/// How `fit_in_viewport()` places an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FitOptions {
+    /// Cells to keep clear inside the viewport. Defaults to none.
+    pub margins: Margins,
+    /// Where the image goes across the room left by the margins. Defaults to `Align::Start`.
+    pub halign: Align,
+    /// Where the image goes down the room left by the margins. Defaults to `Align::Start`.
+    pub valign: Align,
+    /// Whether to enlarge images smaller than the room. Defaults to `false`.
+    pub zoom: bool,
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        Self {
+            margins: Margins::default(),
+            halign: Align::Start,
+            valign: Align::Start,
+            zoom: false,
+        }
+    }
+}
+
+/// Where and how big to draw an image, as worked out by `fit_in_viewport()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ViewportFit {
+    /// Size to give the canvas.
+    pub geometry: CellGeometry,
+    /// Column to move the cursor to before printing, counted from 0 at the left of the terminal.
+    pub x: i32,
+    /// Row to move the cursor to before printing, counted from 0 at the top of the terminal.
+    pub y: i32,
+    /// Width and height in pixels the canvas covers, from `pixel_resolution()`. Pixel modes show the image at this size, so there's no use decoding or scaling it any larger.
+    pub pixels: (i32, i32),
+}
+
+/// Fits an image into part of the terminal, as file manager previewers do: keeps the margins clear, scales the image to the room left while keeping its aspect ratio and aligns it there.
+///
+/// If no room is left, or the image is empty, the geometry is zero and the position is the top left corner of the room.
+/// # Parameters:
+/// --- `src_size`: Width and height of the image, in pixels;
+/// --- `viewport`: Area of the terminal to draw in, in cells;
+/// --- `cell_px`: Width and height of a cell in pixels, e.g. from `Terminal::get_cell_size_px()`. Used for the font ratio; the `default_font_ratio()` is assumed if either is unknown;
+/// --- `options`: Margins, alignment and zoom;
+pub fn fit_in_viewport(
+    src_size: (i32, i32),
+    viewport: Rect,
+    cell_px: (i32, i32),
+    options: &FitOptions,
+) -> ViewportFit {
+    let Margins {
+        top,
+        right,
+        bottom,
+        left,
+    } = options.margins;
+    let (x, y) = (viewport.x + left, viewport.y + top);
+    let (room_width, room_height) = (
+        viewport.width - left - right,
+        viewport.height - top - bottom,
+    );
+    if room_width <= 0 || room_height <= 0 || src_size.0 <= 0 || src_size.1 <= 0 {
+        return ViewportFit {
+            geometry: CellGeometry::default(),
+            x,
+            y,
+            pixels: (0, 0),
+        };
+    }
+
+    let font_ratio = if cell_px.0 > 0 && cell_px.1 > 0 {
+        cell_px.0 as f32 / cell_px.1 as f32
+    } else {
+        default_font_ratio()
+    };
+    let geometry = calc_canvas_geometry(&GeometryRequest {
+        src: src_size,
+        max_cols: Some(room_width),
+        max_rows: Some(room_height),
+        font_ratio,
+        zoom: options.zoom,
+        stretch: false,
+    });
+    ViewportFit {
+        geometry,
+        x: x + align_offset(options.halign, room_width - geometry.cols),
+        y: y + align_offset(options.valign, room_height - geometry.rows),
+        pixels: pixel_resolution(geometry, cell_px),
+    }
+}
+
+/// Returns the width and height in pixels that a canvas of geometry covers with cells of cell_px pixels, or (0, 0) if the cell size is unknown.
+pub fn pixel_resolution(geometry: CellGeometry, cell_px: (i32, i32)) -> (i32, i32) {
+    if cell_px.0 <= 0 || cell_px.1 <= 0 {
+        return (0, 0);
+    }
+    (geometry.cols * cell_px.0, geometry.rows * cell_px.1)
+}
+
+/// Converts a cell size reported in logical pixels, or points, to device pixels.
+/// # Parameters:
+/// --- `cell_px`: Width and height of a cell as the terminal reports them;
+/// --- `scale`: Device pixels per logical pixel, e.g. 2.0 on most HiDPI displays. Values that aren't positive leave the size as it is;
+pub fn scale_cell_size(cell_px: (i32, i32), scale: f32) -> (i32, i32) {
+    if !(scale.is_finite() && scale > 0.0) {
+        return cell_px;
+    }
+    let scale_one = |size: i32| ((size as f32 * scale).round() as i32).max(1);
+    (scale_one(cell_px.0), scale_one(cell_px.1))
+}
+
+/// Returns how far into spare cells to move something aligned with align.
+fn align_offset(align: Align, spare: i32) -> i32 {
+    match align {
+        Align::Center => spare.max(0) / 2,
+        Align::End => spare.max(0),
+        Align::Start | Align::Max => 0,
+    }
+}
+
+/// A canvas size together with the cell geometry to configure, so that pixel modes keep the image's aspect ratio. See `suggest_geometry()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GeometrySuggestion {
+    /// Size to give the canvas.
+    pub geometry: CellGeometry,
+    /// Width and height of a cell in pixels, or `None` if the terminal didn't report it.
+    pub cell_px: Option<(i32, i32)>,
+}
+
+impl GeometrySuggestion {
+    /// Sets config's geometry, and its cell geometry if known. Fails if the geometry is empty, as it is for empty images.
+    pub fn apply(&self, config: &Config) -> Result<(), ChafaError> {
+        config.set_geometry(self.geometry.cols, self.geometry.rows)?;
+        if let Some((width, height)) = self.cell_px {
+            config.set_cell_geometry(width, height)?;
+        }
+        Ok(())
+    }
+}
+
+/// Calculates a canvas geometry with the font ratio of the terminal's real cell size, rather than the request's. Pixel modes draw with the configured cell geometry, so the suggestion carries that too; with chafa's 0.5 and 10x20 defaults, images come out distorted on terminals with other cell sizes.
+/// # Parameters:
+/// --- `request`: Image size, limits and scaling preferences. Its font ratio is used only if the cell size is unknown;
+/// --- `terminal`: Terminal to measure, e.g. `Terminal::get_default()`;
+pub fn suggest_geometry(request: &GeometryRequest, terminal: &Terminal) -> GeometrySuggestion {
+    suggest_geometry_for_cell(request, terminal.get_cell_size_px())
+}
+
+/// Like `suggest_geometry()`, for a cell size that is already known.
+pub fn suggest_geometry_for_cell(
+    request: &GeometryRequest,
+    cell_px: Option<(i32, i32)>,
+) -> GeometrySuggestion {
+    let cell_px = cell_px.filter(|&(width, height)| width > 0 && height > 0);
+    let mut request = *request;
+    if let Some((width, height)) = cell_px {
+        request.font_ratio = width as f32 / height as f32;
+    }
+    GeometrySuggestion {
+        geometry: calc_canvas_geometry(&request),
+        cell_px,
+    }
+}
+
+/// Converts between terminal pixels, terminal cells and positions in an image drawn on a canvas, e.g. to find what part of an image the mouse is over.
+///
+/// Pixels are counted from 0 at the top left of the terminal, as in SGR-Pixels mouse reports, and so are cells, so subtract 1 from the 1-based positions of SGR mouse reports first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CoordinateMap {
+    origin: (i32, i32),
+    geometry: CellGeometry,
+    cell_px: (i32, i32),
+}
+
+impl CoordinateMap {
+    /// Creates a map for a canvas made with config.
+    /// # Parameters:
+    /// --- `config`: Configuration of the canvas, for its geometry and cell size;
+    /// --- `origin`: Cell where the canvas' top left corner was printed;
+    pub fn new(config: &Config, origin: (i32, i32)) -> Self {
+        let (cols, rows) = config.get_geometry();
+        Self::from_parts(
+            origin,
+            CellGeometry { cols, rows },
+            config.get_cell_geometry(),
+        )
+    }
+
+    /// Creates a map for a canvas of geometry printed at origin, with cells of cell_px pixels. Cell sizes that aren't positive are taken as 1.
+    pub fn from_parts(origin: (i32, i32), geometry: CellGeometry, cell_px: (i32, i32)) -> Self {
+        Self {
+            origin,
+            geometry,
+            cell_px: (cell_px.0.max(1), cell_px.1.max(1)),
+        }
+    }
+
+    /// Returns the cell that holds a pixel.
+    pub fn pixel_to_cell(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        (x.div_euclid(self.cell_px.0), y.div_euclid(self.cell_px.1))
+    }
+
+    /// Returns the top left pixel of a cell.
+    pub fn cell_to_pixel(&self, (column, row): (i32, i32)) -> (i32, i32) {
+        (column * self.cell_px.0, row * self.cell_px.1)
+    }
+
+    /// Returns a cell's position relative to the canvas' top left corner, or `None` if it lies outside the canvas.
+    pub fn canvas_cell(&self, (column, row): (i32, i32)) -> Option<(i32, i32)> {
+        let (x, y) = (column - self.origin.0, row - self.origin.1);
+        (x >= 0 && y >= 0 && x < self.geometry.cols && y < self.geometry.rows).then_some((x, y))
+    }
+
+    /// Returns the part of the canvas, in canvas pixels, that an image of src_size takes up when tucked and aligned as given. This mirrors how chafa places images.
+    pub fn image_area(
+        &self,
+        src_size: (i32, i32),
+        tuck: Tuck,
+        halign: Align,
+        valign: Align,
+    ) -> Rect {
+        let (width, height) = (
+            self.geometry.cols * self.cell_px.0,
+            self.geometry.rows * self.cell_px.1,
+        );
+        if src_size.0 <= 0 || src_size.1 <= 0 {
+            return Rect::new(0, 0, 0, 0);
+        }
+        let fit = (width as f32 / src_size.0 as f32).min(height as f32 / src_size.1 as f32);
+        let scale = match tuck {
+            Tuck::Stretch | Tuck::Max => return Rect::new(0, 0, width, height),
+            Tuck::Fit => fit,
+            Tuck::ShrinkToFit => fit.min(1.0),
+        };
+        let scaled_width = ((src_size.0 as f32 * scale).round() as i32).clamp(1, width.max(1));
+        let scaled_height = ((src_size.1 as f32 * scale).round() as i32).clamp(1, height.max(1));
+        Rect::new(
+            align_offset(halign, width - scaled_width),
+            align_offset(valign, height - scaled_height),
+            scaled_width,
+            scaled_height,
+        )
+    }
+
+    /// Like `image_area()`, with the tucking policy and alignment of placement.
+    pub fn placement_area(&self, src_size: (i32, i32), placement: &Placement) -> Rect {
+        self.image_area(
+            src_size,
+            placement.get_tuck(),
+            placement.get_halign(),
+            placement.get_valign(),
+        )
+    }
+
+    /// Returns the pixel of the source image shown at the middle of a cell, or `None` if the cell doesn't show the image.
+    /// # Parameters:
+    /// --- `cell`: Cell of the terminal;
+    /// --- `src_size`: Width and height of the source image, in pixels;
+    /// --- `area`: Part of the canvas the image takes up, from `image_area()` or `placement_area()`;
+    pub fn cell_to_image(
+        &self,
+        cell: (i32, i32),
+        src_size: (i32, i32),
+        area: Rect,
+    ) -> Option<(i32, i32)> {
+        let (column, row) = self.canvas_cell(cell)?;
+        if area.width <= 0 || area.height <= 0 {
+            return None;
+        }
+        let x = column * self.cell_px.0 + self.cell_px.0 / 2 - area.x;
+        let y = row * self.cell_px.1 + self.cell_px.1 / 2 - area.y;
+        if x < 0 || y < 0 || x >= area.width || y >= area.height {
+            return None;
+        }
+        Some((
+            (x as i64 * src_size.0 as i64 / area.width as i64) as i32,
+            (y as i64 * src_size.1 as i64 / area.height as i64) as i32,
+        ))
+    }
+}
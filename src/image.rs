@@ -2,31 +2,67 @@
  * https://hpjansson.org/chafa/ref/chafa-ChafaImage.html
  */
 
-use crate::ffi;
+use crate::{ChafaError, Frame, ffi};
+use std::cell::RefCell;
+use std::rc::Rc;
 
 /// A ChafaImage represents a raster image for placement on a ChafaCanvas. It can currently hold a single ChafaFrame.
 ///
 /// To place an image on a canvas, it must first be assigned to a ChafaPlacement.
 pub struct Image {
     pub raw: *mut ffi::ChafaImage,
+    // Shared between clones, like the ChafaImage itself.
+    frame: Rc<RefCell<Option<Frame>>>,
 }
 
 impl Image {
     /// Creates a new ChafaImage. The image is initially transparent and dimensionless.
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, ChafaError> {
         let raw = unsafe { ffi::chafa_image_new() };
         if raw.is_null() {
-            Err("Chafa -> Failed to create Image")
+            Err(ChafaError::Failed("Chafa -> Failed to create Image"))
         } else {
-            Ok(Image { raw })
+            Ok(Image {
+                raw,
+                frame: Rc::new(RefCell::new(None)),
+            })
         }
     }
 
     /// Assigns frame as the content for image . The image will keep its own reference to the frame.
-    pub fn set_frame(&self, frame: crate::Frame) {
+    pub fn set_frame(&self, frame: Frame) {
         unsafe {
             ffi::chafa_image_set_frame(self.raw, frame.raw);
         }
+        *self.frame.borrow_mut() = Some(frame);
+    }
+
+    /// Returns the frame assigned to the image, or None if it has none yet.
+    pub fn frame(&self) -> Option<Frame> {
+        self.frame.borrow().clone()
+    }
+
+    /// Width of the image's frame, in pixels, or 0 if it has none yet.
+    pub fn width(&self) -> i32 {
+        self.frame.borrow().as_ref().map_or(0, Frame::width)
+    }
+
+    /// Height of the image's frame, in pixels, or 0 if it has none yet.
+    pub fn height(&self) -> i32 {
+        self.frame.borrow().as_ref().map_or(0, Frame::height)
+    }
+}
+
+impl Clone for Image {
+    /// Adds a reference to the image. The clones share one ChafaImage, so a frame set on one of them shows in all, and in every placement made from them.
+    fn clone(&self) -> Self {
+        unsafe {
+            ffi::chafa_image_ref(self.raw);
+        }
+        Self {
+            raw: self.raw,
+            frame: Rc::clone(&self.frame),
+        }
     }
 }
 
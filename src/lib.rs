@@ -1,9 +1,35 @@
 #![allow(unused_imports)]
 pub mod canvas;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod export;
 pub mod term;
+#[cfg(all(unix, feature = "termion"))]
+pub mod termion;
+
+mod animation;
+pub use animation::*;
+
+#[cfg(feature = "async")]
+mod async_render;
+#[cfg(feature = "async")]
+pub use async_render::render_async;
+
+#[cfg(feature = "rayon")]
+mod batch;
+#[cfg(feature = "rayon")]
+pub use batch::*;
 
 pub mod features;
-pub use features::Features;
+pub mod geometry;
+pub mod loader;
+pub mod pixels;
+#[cfg(feature = "ratatui")]
+pub mod ratatui;
+pub use features::{FeatureReport, Features};
+
+mod error;
+pub use error::*;
 
 mod frame;
 pub use frame::*;
@@ -17,9 +43,15 @@ pub use misc::*;
 mod placement;
 pub use placement::*;
 
+#[cfg(feature = "serde")]
+mod serde_str;
+
 mod symbol_map;
 pub use symbol_map::*;
 
+mod version;
+pub use version::*;
+
 #[cfg(not(feature = "ffi"))]
 mod ffi;
 #[cfg(feature = "ffi")]
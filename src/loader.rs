@@ -0,0 +1,202 @@
+/*
+ * Decodes image files into frames and animations for drawing on a canvas.
+ */
+
+use crate::{Animation, ChafaError, Frame};
+use std::path::Path;
+
+/// An image file format the loader knows about. Decoding each one requires its cargo feature: `png`, `jpeg`, `gif`, `webp` or `bmp`. With the `icc` feature, images carrying an embedded ICC profile are converted to sRGB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Format {
+    /// PNG, including animated PNG.
+    Png,
+    /// JPEG/JFIF. Photos are turned upright according to their EXIF orientation.
+    Jpeg,
+    /// GIF, still or animated.
+    Gif,
+    /// WebP, still or animated.
+    WebP,
+    /// Windows bitmap.
+    Bmp,
+}
+
+impl Format {
+    /// Identifies the format from the first bytes of a file.
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Format::Png)
+        } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(Format::Jpeg)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(Format::Gif)
+        } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+            Some(Format::WebP)
+        } else if data.starts_with(b"BM") {
+            Some(Format::Bmp)
+        } else {
+            None
+        }
+    }
+
+    /// Identifies the format from a file name extension, without the dot. Case is ignored.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "png" | "apng" => Some(Format::Png),
+            "jpg" | "jpeg" | "jpe" | "jfif" => Some(Format::Jpeg),
+            "gif" => Some(Format::Gif),
+            "webp" => Some(Format::WebP),
+            "bmp" | "dib" => Some(Format::Bmp),
+            _ => None,
+        }
+    }
+
+    /// Identifies the format from the extension of path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    /// Identifies the format from a MIME type such as `image/png`. Parameters after a `;` are ignored.
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        let essence = mime_type.split(';').next().unwrap_or("").trim();
+        match essence.to_ascii_lowercase().as_str() {
+            "image/png" | "image/apng" => Some(Format::Png),
+            "image/jpeg" | "image/jpg" | "image/pjpeg" => Some(Format::Jpeg),
+            "image/gif" => Some(Format::Gif),
+            "image/webp" => Some(Format::WebP),
+            "image/bmp" | "image/x-bmp" | "image/x-ms-bmp" => Some(Format::Bmp),
+            _ => None,
+        }
+    }
+
+    /// Returns the format's MIME type.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Format::Png => "image/png",
+            Format::Jpeg => "image/jpeg",
+            Format::Gif => "image/gif",
+            Format::WebP => "image/webp",
+            Format::Bmp => "image/bmp",
+        }
+    }
+
+    /// Returns the name of the cargo feature that enables decoding the format.
+    pub fn feature(&self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Jpeg => "jpeg",
+            Format::Gif => "gif",
+            Format::WebP => "webp",
+            Format::Bmp => "bmp",
+        }
+    }
+
+    /// Checks whether support for decoding the format was compiled in.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Format::Png => cfg!(feature = "png"),
+            Format::Jpeg => cfg!(feature = "jpeg"),
+            Format::Gif => cfg!(feature = "gif"),
+            Format::WebP => cfg!(feature = "webp"),
+            Format::Bmp => cfg!(feature = "bmp"),
+        }
+    }
+}
+
+/// Decodes an image held in memory, identifying its format from its contents. Still images give an animation of one frame.
+pub fn load_bytes(data: &[u8]) -> Result<Animation, ChafaError> {
+    let format = Format::sniff(data)
+        .ok_or_else(|| ChafaError::InvalidInput(String::from("Unrecognized image format")))?;
+    load_bytes_as(data, format)
+}
+
+/// Decodes an image held in memory as the given format.
+#[cfg_attr(
+    not(any(
+        feature = "png",
+        feature = "jpeg",
+        feature = "gif",
+        feature = "webp",
+        feature = "bmp"
+    )),
+    allow(unused_variables)
+)]
+pub fn load_bytes_as(data: &[u8], format: Format) -> Result<Animation, ChafaError> {
+    match format {
+        #[cfg(feature = "png")]
+        Format::Png => Animation::from_apng(std::io::Cursor::new(data)),
+        #[cfg(feature = "jpeg")]
+        Format::Jpeg => decode_with_image(data, ::image::ImageFormat::Jpeg),
+        #[cfg(feature = "gif")]
+        Format::Gif => Animation::from_gif(data),
+        #[cfg(feature = "webp")]
+        Format::WebP => Animation::from_webp(std::io::Cursor::new(data)),
+        #[cfg(feature = "bmp")]
+        Format::Bmp => decode_with_image(data, ::image::ImageFormat::Bmp),
+        #[allow(unreachable_patterns)]
+        _ => Err(ChafaError::InvalidInput(format!(
+            "Support for {} is not compiled in; enable the `{}` feature",
+            format.mime_type(),
+            format.feature()
+        ))),
+    }
+}
+
+/// Decodes an image file. The format is identified from the file's contents, falling back to its extension.
+pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Animation, ChafaError> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).map_err(|e| {
+        ChafaError::Io(std::io::Error::new(
+            e.kind(),
+            format!("Failed to read {}: {}", path.display(), e),
+        ))
+    })?;
+    let format = Format::sniff(&data)
+        .or_else(|| Format::from_path(path))
+        .ok_or_else(|| {
+            ChafaError::InvalidInput(format!("Unrecognized image format: {}", path.display()))
+        })?;
+    load_bytes_as(&data, format)
+}
+
+/// Decodes an image held in memory and returns its first frame.
+pub fn load_frame_bytes(data: &[u8]) -> Result<Frame, ChafaError> {
+    load_bytes(data)?.frame(0)
+}
+
+/// Decodes an image file and returns its first frame.
+pub fn load_frame_file<P: AsRef<Path>>(path: P) -> Result<Frame, ChafaError> {
+    load_file(path)?.frame(0)
+}
+
+/// Decodes a still image through the image crate, turning it upright according to its EXIF orientation tag, as image viewers do. Phone cameras store photos in sensor orientation and rely on this tag.
+#[cfg(any(feature = "jpeg", feature = "bmp"))]
+fn decode_with_image(data: &[u8], format: ::image::ImageFormat) -> Result<Animation, ChafaError> {
+    use ::image::ImageDecoder;
+
+    let error = |e: ::image::ImageError| {
+        ChafaError::InvalidInput(format!("Failed to decode {:?}: {}", format, e))
+    };
+    let mut decoder = ::image::ImageReader::with_format(std::io::Cursor::new(data), format)
+        .into_decoder()
+        .map_err(error)?;
+    // A missing or unreadable tag leaves the image as stored.
+    let orientation = decoder
+        .orientation()
+        .unwrap_or(::image::metadata::Orientation::NoTransforms);
+    #[cfg(feature = "icc")]
+    let icc_profile = decoder.icc_profile().ok().flatten();
+    let mut image = ::image::DynamicImage::from_decoder(decoder).map_err(error)?;
+    image.apply_orientation(orientation);
+
+    let rgba = image.to_rgba8();
+    let mut animation = Animation::new(rgba.width() as i32, rgba.height() as i32)?;
+    animation.push_frame(rgba.into_raw(), std::time::Duration::ZERO)?;
+    #[cfg(feature = "icc")]
+    animation.apply_embedded_profile(icc_profile.as_deref());
+    Ok(animation)
+}
@@ -2,46 +2,273 @@
  * https://hpjansson.org/chafa/ref/chafa-Miscellaneous.html
  */
 
-use crate::ffi;
+use crate::{ChafaError, ffi};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Environment variable that sets the font ratio, overriding both detection and the built-in default.
+pub const FONT_RATIO_ENV: &str = "CHAFA_FONT_RATIO";
+
+/// Bits of the f32 set with `set_default_font_ratio()`, or 0 if it wasn't called.
+static DEFAULT_FONT_RATIO: AtomicU32 = AtomicU32::new(0);
+
+/// Sets the font ratio that geometry helpers such as `GeometryRequest::new()` and `geometry::fit_in_viewport()` use when they aren't given one, so an application can correct the aspect of its terminal's font once. Values that aren't positive restore the built-in default.
+pub fn set_default_font_ratio(font_ratio: f32) {
+    let bits = if font_ratio.is_finite() && font_ratio > 0.0 {
+        font_ratio.to_bits()
+    } else {
+        0
+    };
+    DEFAULT_FONT_RATIO.store(bits, Ordering::Relaxed);
+}
+
+/// Gets the font ratio geometry helpers fall back to: the one given to `set_default_font_ratio()`, else the one in the CHAFA_FONT_RATIO environment variable, else 0.5.
+pub fn default_font_ratio() -> f32 {
+    match DEFAULT_FONT_RATIO.load(Ordering::Relaxed) {
+        0 => env_font_ratio().unwrap_or(0.5),
+        bits => f32::from_bits(bits),
+    }
+}
+
+/// Reads the font ratio from the CHAFA_FONT_RATIO environment variable, if it holds a positive number.
+pub(crate) fn env_font_ratio() -> Option<f32> {
+    std::env::var(FONT_RATIO_ENV)
+        .ok()?
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|ratio| ratio.is_finite() && *ratio > 0.0)
+}
+
+/// Input to `calc_canvas_geometry()`: the size of an image, the most room the canvas may take and how to scale into it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeometryRequest {
+    /// Width and height of the source image, in pixels. Both must be zero or greater.
+    pub src: (i32, i32),
+    /// Most columns the canvas may take, or `None` to derive them from `max_rows` and the aspect ratio.
+    pub max_cols: Option<i32>,
+    /// Most rows the canvas may take, or `None` to derive them from `max_cols` and the aspect ratio.
+    pub max_rows: Option<i32>,
+    /// The font's width divided by its height. 0.5 is a typical value, and the default unless changed with `set_default_font_ratio()`.
+    pub font_ratio: f32,
+    /// Whether to upscale the image to fill the maximum dimensions.
+    pub zoom: bool,
+    /// Whether to ignore the aspect ratio of the source.
+    pub stretch: bool,
+}
+
+impl GeometryRequest {
+    /// Creates a request for an image of width x height pixels, with no maximum dimensions, the `default_font_ratio()` and neither zoom nor stretch.
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            src: (width, height),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the font ratio to the one measured with `term::detect_font_ratio()`, keeping the current one if the terminal doesn't report it.
+    pub fn detect_font_ratio(mut self) -> Self {
+        if let Some(font_ratio) = crate::term::detect_font_ratio() {
+            self.font_ratio = font_ratio;
+        }
+        self
+    }
+}
+
+impl Default for GeometryRequest {
+    fn default() -> Self {
+        Self {
+            src: (0, 0),
+            max_cols: None,
+            max_rows: None,
+            font_ratio: default_font_ratio(),
+            zoom: false,
+            stretch: false,
+        }
+    }
+}
+
+/// Size of a canvas in character cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CellGeometry {
+    /// Width, in columns.
+    pub cols: i32,
+    /// Height, in rows.
+    pub rows: i32,
+}
 
 /// Calculates an optimal geometry for a ChafaCanvas given the width and height of an input image, maximum width and height of the canvas, font ratio, zoom and stretch preferences.
 ///
-/// src_width and src_height must both be zero or greater.
-///
-/// dest_width_inout and dest_height_inout must point to integers containing the maximum dimensions of the canvas in character cells.
-/// These will be replaced by the calculated values, which may be zero if one of the input dimensions is zero. If one or both of the input parameters is negative, they will be treated as unspecified and calculated based on the remaining parameters and aspect ratio.
-///
-/// font_ratio is the font's width divided by its height. 0.5 is a typical value.
+/// The result may be zero in either dimension if one of the source dimensions is zero. Maximum dimensions left as `None` are calculated from the other one and the aspect ratio.
 /// # Parameters:
-/// --- `src_width`: Width of source;
-/// --- `src_height`: Height of source;
-/// --- `font_ratio`: Target font's width to height ratio;
-/// --- `zoom`: TRUE to upscale image to fit maximum dimensions, FALSE otherwise;
-/// --- `stretch`: TRUE to ignore aspect of source, FALSE otherwise;
+/// --- `request`: The image size, limits and scaling preferences;
 /// # Returns:
-/// A tuple containing the optimal width and height for the canvas, respectively.
-pub fn calc_canvas_geometry(
-    src_width: i32,
-    src_height: i32,
-    font_ratio: f32,
-    zoom: bool,
-    stretch: bool,
-) -> (i32, i32) {
-    let mut width: i32 = 0;
-    let mut height: i32 = 0;
+/// The optimal size of the canvas.
+pub fn calc_canvas_geometry(request: &GeometryRequest) -> CellGeometry {
+    // Chafa takes negative maximums as unspecified.
+    let mut cols = request.max_cols.unwrap_or(-1);
+    let mut rows = request.max_rows.unwrap_or(-1);
 
     unsafe {
         ffi::chafa_calc_canvas_geometry(
-            src_width,
-            src_height,
-            &mut width,
-            &mut height,
-            font_ratio,
-            if zoom { 1 } else { 0 },
-            if stretch { 1 } else { 0 },
+            request.src.0,
+            request.src.1,
+            &mut cols,
+            &mut rows,
+            request.font_ratio,
+            if request.zoom { 1 } else { 0 },
+            if request.stretch { 1 } else { 0 },
         );
     }
-    (width, height)
+    CellGeometry { cols, rows }
+}
+
+/// Scales 8-bit pixel data, box filtering along axes that shrink and picking the nearest pixel along axes that grow. Every channel is treated alike, so this works for any pixel type with bpp bytes per pixel.
+///
+/// The result has rows of to_width * bpp bytes. layout is the source's (width, height, rowstride), which must all be positive.
+pub(crate) fn rescale_pixels(
+    pixels: &[u8],
+    layout: (i32, i32, i32),
+    bpp: usize,
+    to_width: i32,
+    to_height: i32,
+) -> Vec<u8> {
+    let (width, height, src_rowstride) = layout;
+    let columns = scale_weights(width, to_width);
+    let rows = scale_weights(height, to_height);
+    let rowstride = to_width as usize * bpp;
+    let mut out = vec![0u8; rowstride * to_height as usize];
+    let mut sums = vec![0f32; bpp];
+    for (y, row_weights) in rows.iter().enumerate() {
+        for (x, column_weights) in columns.iter().enumerate() {
+            sums.iter_mut().for_each(|sum| *sum = 0.0);
+            for (sy, wy) in row_weights {
+                for (sx, wx) in column_weights {
+                    let i = sy * src_rowstride as usize + sx * bpp;
+                    for (sum, v) in sums.iter_mut().zip(&pixels[i..i + bpp]) {
+                        *sum += *v as f32 * wy * wx;
+                    }
+                }
+            }
+            let i = y * rowstride + x * bpp;
+            for (px, sum) in out[i..i + bpp].iter_mut().zip(&sums) {
+                *px = sum.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+    out
+}
+
+/// For each target pixel along an axis, lists the source pixels it samples and their weights, which add up to 1.
+fn scale_weights(from: i32, to: i32) -> Vec<Vec<(usize, f32)>> {
+    let scale = from as f32 / to as f32;
+    (0..to)
+        .map(|i| {
+            if to >= from {
+                let nearest = ((i as f32 + 0.5) * scale) as usize;
+                return vec![(nearest.min(from as usize - 1), 1.0)];
+            }
+            let start = i as f32 * scale;
+            let end = start + scale;
+            (start.floor() as usize..(end.ceil() as usize).min(from as usize))
+                .map(|s| {
+                    let covered = (end.min(s as f32 + 1.0) - start.max(s as f32)).max(0.0);
+                    (s, covered / scale)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A rectangle of pixels or character cells, e.g. the part of an image to draw or the area of a terminal to draw in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// Column of the left edge.
+    pub x: i32,
+    /// Row of the top edge.
+    pub y: i32,
+    /// Width, in pixels or cells.
+    pub width: i32,
+    /// Height, in pixels or cells.
+    pub height: i32,
+}
+
+impl Rect {
+    /// Creates a rectangle with its top left corner at (x, y).
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// Checks that an image of width x height pixels, with rows rowstride bytes apart, has a sane layout and, if len is given, fits in len bytes. Chafa's own checks only log warnings, or read out of bounds.
+pub(crate) fn validate_image(
+    len: Option<usize>,
+    pixel_type: PixelType,
+    (width, height, rowstride): (i32, i32, i32),
+) -> Result<(), ChafaError> {
+    if width <= 0 || height <= 0 {
+        return Err(ChafaError::InvalidInput(format!(
+            "Image size must be positive, got {}x{}",
+            width, height
+        )));
+    }
+    let row_bytes = width as usize * pixel_type.bytes_per_pixel();
+    if rowstride <= 0 || (rowstride as usize) < row_bytes {
+        return Err(ChafaError::InvalidInput(format!(
+            "Rowstride of {} is too small for rows of {} pixels, which take {} bytes",
+            rowstride, width, row_bytes
+        )));
+    }
+    let needed = (height as usize - 1) * rowstride as usize + row_bytes;
+    match len {
+        Some(len) if len < needed => Err(ChafaError::InvalidInput(format!(
+            "Pixel data of {} bytes is too small for an image of {}x{} with a rowstride of {}, which needs {}",
+            len, width, height, rowstride, needed
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that rect lies within an image of width x height pixels held in len bytes, with rows rowstride bytes apart, and returns the byte offset of its top left pixel.
+pub(crate) fn region_offset(
+    len: usize,
+    pixel_type: PixelType,
+    (width, height, rowstride): (i32, i32, i32),
+    rect: Rect,
+) -> Result<usize, ChafaError> {
+    validate_image(None, pixel_type, (width, height, rowstride))?;
+    let inside = |start: i32, size: i32, limit: i32| {
+        start >= 0 && size > 0 && start.checked_add(size).is_some_and(|end| end <= limit)
+    };
+    if !inside(rect.x, rect.width, width) || !inside(rect.y, rect.height, height) {
+        return Err(ChafaError::InvalidInput(format!(
+            "Region of {}x{} at ({}, {}) doesn't fit in an image of {}x{}",
+            rect.width, rect.height, rect.x, rect.y, width, height
+        )));
+    }
+    // All values are non-negative from here on, so the casts are lossless.
+    let bpp = pixel_type.bytes_per_pixel();
+    let rowstride = rowstride as usize;
+    let offset = (rect.y as usize)
+        .checked_mul(rowstride)
+        .and_then(|o| o.checked_add(rect.x as usize * bpp));
+    let needed = offset.and_then(|o| {
+        (rect.height as usize - 1)
+            .checked_mul(rowstride)?
+            .checked_add(rect.width as usize * bpp)?
+            .checked_add(o)
+    });
+    match (offset, needed) {
+        (Some(offset), Some(needed)) if needed <= len => Ok(offset),
+        _ => Err(ChafaError::InvalidInput(format!(
+            "Pixel data of {} bytes with a rowstride of {} is too small for an image of {}x{}",
+            len, rowstride, width, height
+        ))),
+    }
 }
 
 pub const CHAFA_MAJOR_VERSION: u32 = ffi::CHAFA_MAJOR_VERSION;
@@ -49,35 +276,115 @@ pub const CHAFA_MICRO_VERSION: u32 = ffi::CHAFA_MICRO_VERSION;
 pub const CHAFA_MINOR_VERSION: u32 = ffi::CHAFA_MINOR_VERSION;
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PixelType {
     /// Premultiplied RGBA, 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "rgba8-premultiplied"))]
     RGBA8Premultiplied = ffi::ChafaPixelType_CHAFA_PIXEL_RGBA8_PREMULTIPLIED,
     /// Premultiplied BGRA, 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "bgra8-premultiplied"))]
     BGRA8Premultiplied = ffi::ChafaPixelType_CHAFA_PIXEL_BGRA8_PREMULTIPLIED,
     /// Premultiplied ARGB, 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "argb8-premultiplied"))]
     ARGB8Premultiplied = ffi::ChafaPixelType_CHAFA_PIXEL_ARGB8_PREMULTIPLIED,
     /// Premultiplied ABGR, 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "abgr8-premultiplied"))]
     ABGR8Premultiplied = ffi::ChafaPixelType_CHAFA_PIXEL_ABGR8_PREMULTIPLIED,
 
     /// Unassociated RGBA, 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "rgba8-unassociated"))]
     RGBA8Unassociated = ffi::ChafaPixelType_CHAFA_PIXEL_RGBA8_UNASSOCIATED,
     /// Unassociated BGRA, 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "bgra8-unassociated"))]
     BGRA8Unassociated = ffi::ChafaPixelType_CHAFA_PIXEL_BGRA8_UNASSOCIATED,
     /// Unassociated ARGB, 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "argb8-unassociated"))]
     ARGB8Unassociated = ffi::ChafaPixelType_CHAFA_PIXEL_ARGB8_UNASSOCIATED,
     /// Unassociated ABGR, 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "abgr8-unassociated"))]
     ABGR8Unassociated = ffi::ChafaPixelType_CHAFA_PIXEL_ABGR8_UNASSOCIATED,
 
     /* 24 bits per pixel */
     /// Packed RGB (no alpha), 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "rgb8"))]
     RGB8 = ffi::ChafaPixelType_CHAFA_PIXEL_RGB8,
     /// Packed BGR (no alpha), 8 bits per channel.
+    #[cfg_attr(feature = "serde", serde(rename = "bgr8"))]
     BGR8 = ffi::ChafaPixelType_CHAFA_PIXEL_BGR8,
 
     /// Last supported pixel type, plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaPixelType_CHAFA_PIXEL_MAX,
 }
 
+impl PixelType {
+    /// Returns the number of bytes one pixel takes up in this format.
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelType::RGB8 | PixelType::BGR8 => 3,
+            _ => 4,
+        }
+    }
+
+    /// Returns the byte offset of the alpha channel within a pixel, or None if this type has no alpha.
+    pub fn alpha_index(&self) -> Option<usize> {
+        match self {
+            PixelType::RGB8 | PixelType::BGR8 | PixelType::Max => None,
+            PixelType::ARGB8Premultiplied
+            | PixelType::ABGR8Premultiplied
+            | PixelType::ARGB8Unassociated
+            | PixelType::ABGR8Unassociated => Some(0),
+            _ => Some(3),
+        }
+    }
+
+    /// Returns `true` if the color channels of this type are premultiplied by alpha.
+    pub fn is_premultiplied(&self) -> bool {
+        matches!(
+            self,
+            PixelType::RGBA8Premultiplied
+                | PixelType::BGRA8Premultiplied
+                | PixelType::ARGB8Premultiplied
+                | PixelType::ABGR8Premultiplied
+        )
+    }
+
+    /// Returns the premultiplied type with the same channel order. Types without alpha are returned as they are.
+    pub fn premultiplied(&self) -> Self {
+        match self {
+            PixelType::RGBA8Unassociated => PixelType::RGBA8Premultiplied,
+            PixelType::BGRA8Unassociated => PixelType::BGRA8Premultiplied,
+            PixelType::ARGB8Unassociated => PixelType::ARGB8Premultiplied,
+            PixelType::ABGR8Unassociated => PixelType::ABGR8Premultiplied,
+            other => *other,
+        }
+    }
+
+    /// Returns the unassociated type with the same channel order. Types without alpha are returned as they are.
+    pub fn unassociated(&self) -> Self {
+        match self {
+            PixelType::RGBA8Premultiplied => PixelType::RGBA8Unassociated,
+            PixelType::BGRA8Premultiplied => PixelType::BGRA8Unassociated,
+            PixelType::ARGB8Premultiplied => PixelType::ARGB8Unassociated,
+            PixelType::ABGR8Premultiplied => PixelType::ABGR8Unassociated,
+            other => *other,
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl PixelType {
+    /// Returns the pixel type chafa can take data of an image crate color type as, or None if it has to be converted first, e.g. with `pixels::to_supported()`. Only 8-bit RGB and RGBA match; the image crate never premultiplies alpha.
+    pub fn from_color_type(color_type: ::image::ColorType) -> Option<Self> {
+        match color_type {
+            ::image::ColorType::Rgb8 => Some(PixelType::RGB8),
+            ::image::ColorType::Rgba8 => Some(PixelType::RGBA8Unassociated),
+            _ => None,
+        }
+    }
+}
+
 impl From<u32> for PixelType {
     fn from(value: u32) -> Self {
         match value {
@@ -0,0 +1,331 @@
+/*
+ * Conversions and transforms of raw pixel data.
+ */
+
+use crate::{ChafaError, PixelType, misc};
+#[cfg(feature = "image")]
+use std::borrow::Cow;
+
+/// Multiplies the color channels of every pixel by its alpha, in place.
+///
+/// Passing premultiplied data to chafa as one of the Unassociated types (or the other way around) gives dark or bright halos around transparent edges. Convert with this when an API wants premultiplied data and yours isn't. `Frame` constructors never convert on their own; they take the pixel type that describes the data as it is. Data of a type that is already premultiplied or has no alpha is left alone. A trailing partial pixel is ignored.
+/// # Parameters:
+/// --- `data`: Pixel data, in any row layout, since pixels are converted independently;
+/// --- `pixel_type`: The ChafaPixelType of data;
+/// # Returns:
+/// The ChafaPixelType data is in afterwards.
+pub fn premultiply(data: &mut [u8], pixel_type: PixelType) -> PixelType {
+    if pixel_type.is_premultiplied() {
+        return pixel_type;
+    }
+    match pixel_type.alpha_index() {
+        Some(0) => convert::<0>(data, premultiply_channel),
+        Some(_) => convert::<3>(data, premultiply_channel),
+        None => return pixel_type,
+    }
+    pixel_type.premultiplied()
+}
+
+/// Divides the color channels of every pixel by its alpha, in place. Fully transparent pixels become transparent black.
+///
+/// This is the inverse of `premultiply()`, up to rounding, which loses precision in nearly transparent pixels. Data of a type that is already unassociated or has no alpha is left alone. A trailing partial pixel is ignored.
+/// # Parameters:
+/// --- `data`: Pixel data, in any row layout, since pixels are converted independently;
+/// --- `pixel_type`: The ChafaPixelType of data;
+/// # Returns:
+/// The ChafaPixelType data is in afterwards.
+pub fn unpremultiply(data: &mut [u8], pixel_type: PixelType) -> PixelType {
+    if !pixel_type.is_premultiplied() {
+        return pixel_type;
+    }
+    match pixel_type.alpha_index() {
+        Some(0) => convert::<0>(data, unpremultiply_channel),
+        Some(_) => convert::<3>(data, unpremultiply_channel),
+        None => return pixel_type,
+    }
+    pixel_type.unassociated()
+}
+
+/// Applies f to the color channels of every 4-byte pixel. With the alpha position fixed at compile time, the loop has no branches per pixel and vectorizes.
+fn convert<const ALPHA: usize>(data: &mut [u8], f: fn(u8, u8) -> u8) {
+    for px in data.chunks_exact_mut(4) {
+        let alpha = px[ALPHA];
+        for (i, channel) in px.iter_mut().enumerate() {
+            if i != ALPHA {
+                *channel = f(*channel, alpha);
+            }
+        }
+    }
+}
+
+/// Computes channel * alpha / 255, rounded, without a division.
+fn premultiply_channel(channel: u8, alpha: u8) -> u8 {
+    let t = u32::from(channel) * u32::from(alpha) + 128;
+    ((t + (t >> 8)) >> 8) as u8
+}
+
+fn unpremultiply_channel(channel: u8, alpha: u8) -> u8 {
+    if alpha == 0 {
+        0
+    } else {
+        let alpha = u32::from(alpha);
+        ((u32::from(channel) * 255 + alpha / 2) / alpha).min(255) as u8
+    }
+}
+
+/// A clockwise rotation by a multiple of 90°.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Rotation {
+    /// Quarter turn clockwise.
+    Cw90,
+    /// Half turn.
+    Cw180,
+    /// Quarter turn counterclockwise.
+    Cw270,
+}
+
+/// Rotates an image. Quarter turns swap its width and height.
+/// # Parameters:
+/// --- `data`: Pixel data;
+/// --- `pixel_type`: The ChafaPixelType of data;
+/// --- `width`: Width of the image, in pixels;
+/// --- `height`: Height of the image, in pixels;
+/// --- `rowstride`: Number of bytes to advance from the start of one row to the next;
+/// --- `rotation`: How far to turn the image;
+/// # Returns:
+/// The rotated pixels in rows of the new width * bytes per pixel, and the new width and height.
+pub fn rotate(
+    data: &[u8],
+    pixel_type: PixelType,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+    rotation: Rotation,
+) -> Result<(Vec<u8>, i32, i32), ChafaError> {
+    let (w, h) = (width - 1, height - 1);
+    match rotation {
+        Rotation::Cw90 => remap(
+            data,
+            pixel_type,
+            (width, height, rowstride),
+            true,
+            |x, y| (y, h - x),
+        ),
+        Rotation::Cw180 => remap(
+            data,
+            pixel_type,
+            (width, height, rowstride),
+            false,
+            |x, y| (w - x, h - y),
+        ),
+        Rotation::Cw270 => remap(
+            data,
+            pixel_type,
+            (width, height, rowstride),
+            true,
+            |x, y| (w - y, x),
+        ),
+    }
+}
+
+/// Mirrors an image left to right. See `rotate()` for the parameters.
+pub fn flip_horizontal(
+    data: &[u8],
+    pixel_type: PixelType,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+) -> Result<Vec<u8>, ChafaError> {
+    remap(
+        data,
+        pixel_type,
+        (width, height, rowstride),
+        false,
+        |x, y| (width - 1 - x, y),
+    )
+    .map(|(pixels, _, _)| pixels)
+}
+
+/// Mirrors an image top to bottom. See `rotate()` for the parameters.
+pub fn flip_vertical(
+    data: &[u8],
+    pixel_type: PixelType,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+) -> Result<Vec<u8>, ChafaError> {
+    remap(
+        data,
+        pixel_type,
+        (width, height, rowstride),
+        false,
+        |x, y| (x, height - 1 - y),
+    )
+    .map(|(pixels, _, _)| pixels)
+}
+
+/// Builds a packed image where each pixel (x, y) is copied from the source pixel source(x, y). transpose swaps the width and height of the result.
+fn remap(
+    data: &[u8],
+    pixel_type: PixelType,
+    (width, height, rowstride): (i32, i32, i32),
+    transpose: bool,
+    source: impl Fn(i32, i32) -> (i32, i32),
+) -> Result<(Vec<u8>, i32, i32), ChafaError> {
+    misc::region_offset(
+        data.len(),
+        pixel_type,
+        (width, height, rowstride),
+        misc::Rect::new(0, 0, width, height),
+    )?;
+    let (out_width, out_height) = if transpose {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    let bpp = pixel_type.bytes_per_pixel();
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * bpp);
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let (sx, sy) = source(x, y);
+            let start = sy as usize * rowstride as usize + sx as usize * bpp;
+            pixels.extend_from_slice(&data[start..start + bpp]);
+        }
+    }
+    Ok((pixels, out_width, out_height))
+}
+
+/// Converts planar YUV 4:2:0 data (I420) to packed RGB8, as `Frame::from_yuv420()` does. The U and V planes have half the resolution of the Y plane in both directions, rounded up. Samples are taken as limited range BT.601.
+/// # Parameters:
+/// --- `y`: Luma plane;
+/// --- `u`: Blue-difference chroma plane;
+/// --- `v`: Red-difference chroma plane;
+/// --- `strides`: Number of bytes from the start of one row to the next in the Y, U and V planes;
+/// --- `width`: Width of the image, in pixels;
+/// --- `height`: Height of the image, in pixels;
+/// # Returns:
+/// The pixels in rows of width * 3 bytes.
+pub fn yuv420_to_rgb(
+    y: &[u8],
+    u: &[u8],
+    v: &[u8],
+    strides: [usize; 3],
+    width: i32,
+    height: i32,
+) -> Result<Vec<u8>, ChafaError> {
+    let (w, h) = yuv_size(width, height)?;
+    let (cw, ch) = (w.div_ceil(2), h.div_ceil(2));
+    check_plane("Y", y, strides[0], w, h)?;
+    check_plane("U", u, strides[1], cw, ch)?;
+    check_plane("V", v, strides[2], cw, ch)?;
+
+    Ok(yuv_to_rgb(y, strides[0], w, h, |cx, cy| {
+        (u[cy * strides[1] + cx], v[cy * strides[2] + cx])
+    }))
+}
+
+/// Converts NV12 data, a Y plane followed by a plane of interleaved U and V samples at half resolution, to packed RGB8, as `Frame::from_nv12()` does. See `yuv420_to_rgb()` for how colors are converted.
+/// # Parameters:
+/// --- `y`: Luma plane;
+/// --- `uv`: Interleaved chroma plane, U first;
+/// --- `strides`: Number of bytes from the start of one row to the next in the Y and UV planes;
+/// --- `width`: Width of the image, in pixels;
+/// --- `height`: Height of the image, in pixels;
+/// # Returns:
+/// The pixels in rows of width * 3 bytes.
+pub fn nv12_to_rgb(
+    y: &[u8],
+    uv: &[u8],
+    strides: [usize; 2],
+    width: i32,
+    height: i32,
+) -> Result<Vec<u8>, ChafaError> {
+    let (w, h) = yuv_size(width, height)?;
+    check_plane("Y", y, strides[0], w, h)?;
+    check_plane("UV", uv, strides[1], w.div_ceil(2) * 2, h.div_ceil(2))?;
+
+    Ok(yuv_to_rgb(y, strides[0], w, h, |cx, cy| {
+        let i = cy * strides[1] + cx * 2;
+        (uv[i], uv[i + 1])
+    }))
+}
+
+fn yuv_size(width: i32, height: i32) -> Result<(usize, usize), ChafaError> {
+    if width <= 0 || height <= 0 {
+        return Err(ChafaError::InvalidInput(format!(
+            "Frame size must be positive, got {}x{}",
+            width, height
+        )));
+    }
+    Ok((width as usize, height as usize))
+}
+
+/// Checks that a plane holds rows of row_bytes bytes, stride bytes apart.
+fn check_plane(
+    name: &str,
+    plane: &[u8],
+    stride: usize,
+    row_bytes: usize,
+    rows: usize,
+) -> Result<(), ChafaError> {
+    if stride < row_bytes {
+        return Err(ChafaError::InvalidInput(format!(
+            "{} plane stride of {} is less than its row of {} bytes",
+            name, stride, row_bytes
+        )));
+    }
+    let needed = stride * (rows - 1) + row_bytes;
+    if plane.len() < needed {
+        return Err(ChafaError::InvalidInput(format!(
+            "{} plane needs {} bytes, got {}",
+            name,
+            needed,
+            plane.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Converts limited range BT.601 YUV to packed RGB8 in 8.8 fixed point. chroma returns the (U, V) samples at a chroma position.
+fn yuv_to_rgb(
+    y: &[u8],
+    y_stride: usize,
+    width: usize,
+    height: usize,
+    chroma: impl Fn(usize, usize) -> (u8, u8),
+) -> Vec<u8> {
+    let clamp = |value: i32| (value >> 8).clamp(0, 255) as u8;
+    let mut rgb = Vec::with_capacity(width * height * 3);
+    for row in 0..height {
+        for (col, &luma) in y[row * y_stride..][..width].iter().enumerate() {
+            let (u, v) = chroma(col / 2, row / 2);
+            let c = 298 * (i32::from(luma) - 16) + 128;
+            let d = i32::from(u) - 128;
+            let e = i32::from(v) - 128;
+            rgb.extend_from_slice(&[
+                clamp(c + 409 * e),
+                clamp(c - 100 * d - 208 * e),
+                clamp(c + 516 * d),
+            ]);
+        }
+    }
+    rgb
+}
+
+/// Returns the pixels of a decoded image in a format chafa takes, along with its pixel type. 8-bit RGB and RGBA images are borrowed as they are. Grayscale is expanded to RGB (or RGBA, with alpha), and deeper channels are reduced to 8 bits, keeping alpha only if the image has it. Rows are width * bytes per pixel long.
+#[cfg(feature = "image")]
+pub fn to_supported(image: &::image::DynamicImage) -> (Cow<'_, [u8]>, PixelType) {
+    if let Some(pixel_type) = PixelType::from_color_type(image.color()) {
+        return (Cow::Borrowed(image.as_bytes()), pixel_type);
+    }
+    if image.color().has_alpha() {
+        (
+            Cow::Owned(image.to_rgba8().into_raw()),
+            PixelType::RGBA8Unassociated,
+        )
+    } else {
+        (Cow::Owned(image.to_rgb8().into_raw()), PixelType::RGB8)
+    }
+}
@@ -2,36 +2,102 @@
  * https://hpjansson.org/chafa/ref/chafa-ChafaPlacement.html
  */
 
-use crate::ffi;
+use crate::{ChafaError, ffi};
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Next ID to hand out to placements created without one. Chafa doesn't expose the IDs it assigns, so they're assigned here instead, always above every ID passed in explicitly so far.
+static NEXT_ID: AtomicI32 = AtomicI32::new(1);
 
 /// A ChafaPlacement describes how an image is placed on a ChafaCanvas. It contains information about the image, its alignment and tucking policy.
 pub struct Placement {
     pub raw: *mut ffi::ChafaPlacement,
+    id: i32,
+    // Keeps the image's frame, and any memory it borrows, alive as long as chafa can draw it.
+    image: crate::Image,
 }
 
 impl Placement {
     /// Creates a new ChafaPlacement.
     /// # Parameters:
-    /// --- `id`: An ID to assign to the placement, or <= 0 to assign one automatically.
-    pub fn new(image: crate::Image, id: i32) -> Result<Self, &'static str> {
+    /// --- `id`: An ID to assign to the placement, or <= 0 to assign one automatically. Automatic IDs skip every ID passed in before, but an explicit ID may still repeat one assigned earlier.
+    pub fn new(image: crate::Image, id: i32) -> Result<Self, ChafaError> {
+        Self::new_for(&image, id)
+    }
+
+    /// Creates a new ChafaPlacement for image that scales it to fit the canvas while preserving its aspect ratio, centered both ways. Same as `contain()`.
+    pub fn fitted(image: &crate::Image) -> Result<Self, ChafaError> {
+        Self::contain(image)
+    }
+
+    /// Creates a new ChafaPlacement for image that stretches it over the whole canvas, like CSS `object-fit: fill`. Chafa can't crop, so unlike CSS `cover` the aspect ratio isn't kept.
+    pub fn cover(image: &crate::Image) -> Result<Self, ChafaError> {
+        Self::preset(image, Tuck::Stretch, Align::Start, Align::Start)
+    }
+
+    /// Creates a new ChafaPlacement for image that scales it to fit the canvas while preserving its aspect ratio, centered both ways, like CSS `object-fit: contain`.
+    pub fn contain(image: &crate::Image) -> Result<Self, ChafaError> {
+        Self::preset(image, Tuck::Fit, Align::Center, Align::Center)
+    }
+
+    /// Creates a new ChafaPlacement for image that scales it to fit the canvas while preserving its aspect ratio, with any padding going to the right and bottom.
+    pub fn top_left(image: &crate::Image) -> Result<Self, ChafaError> {
+        Self::preset(image, Tuck::Fit, Align::Start, Align::Start)
+    }
+
+    fn preset(
+        image: &crate::Image,
+        tuck: Tuck,
+        halign: Align,
+        valign: Align,
+    ) -> Result<Self, ChafaError> {
+        let placement = Self::new_for(image, 0)?;
+        placement
+            .set_tuck(tuck)
+            .set_halign(halign)
+            .set_valign(valign);
+        Ok(placement)
+    }
+
+    fn new_for(image: &crate::Image, id: i32) -> Result<Self, ChafaError> {
+        let id = if id > 0 {
+            NEXT_ID.fetch_max(id.saturating_add(1), Ordering::Relaxed);
+            id
+        } else {
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        };
         let raw = unsafe { ffi::chafa_placement_new(image.raw, id) };
         if raw.is_null() {
-            Err("Chafa -> Failed to create Placement")
+            Err(ChafaError::Failed("Chafa -> Failed to create Placement"))
         } else {
-            Ok(Self { raw })
+            Ok(Self {
+                raw,
+                id,
+                image: image.clone(),
+            })
         }
     }
 
+    /// Gets the ID of placement. This is the ID given to `new()`, or the one assigned automatically if that was <= 0.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Gets the image the placement shows.
+    pub fn image(&self) -> &crate::Image {
+        &self.image
+    }
+
     /// Gets the tucking policy of placement. This describes how the image is resized to fit placement 's extents, and defaults to CHAFA_TUCK_STRETCH.
     pub fn get_tuck(&self) -> Tuck {
         unsafe { Tuck::from(ffi::chafa_placement_get_tuck(self.raw)) }
     }
 
-    /// Sets the tucking policy for placement to tuck . This describes how the image is resized to fit placement 's extents, and defaults to CHAFA_TUCK_STRETCH.
-    pub fn set_tuck(&self, tuck: Tuck) {
+    /// Sets the tucking policy for placement to tuck . This describes how the image is resized to fit placement 's extents, and defaults to CHAFA_TUCK_STRETCH. Returns placement so setters can be chained.
+    pub fn set_tuck(&self, tuck: Tuck) -> &Self {
         unsafe {
             ffi::chafa_placement_set_tuck(self.raw, tuck as u32);
         }
+        self
     }
 
     /// Gets the horizontal alignment of placement . This determines how any padding added by the tucking policy is distributed, and defaults to CHAFA_ALIGN_START.
@@ -39,11 +105,12 @@ impl Placement {
         unsafe { Align::from(ffi::chafa_placement_get_halign(self.raw)) }
     }
 
-    /// Sets the horizontal alignment of placement . This determines how any padding added by the tucking policy is distributed, and defaults to CHAFA_ALIGN_START
-    pub fn set_halign(&self, align: Align) {
+    /// Sets the horizontal alignment of placement . This determines how any padding added by the tucking policy is distributed, and defaults to CHAFA_ALIGN_START. Returns placement so setters can be chained.
+    pub fn set_halign(&self, align: Align) -> &Self {
         unsafe {
             ffi::chafa_placement_set_halign(self.raw, align as u32);
         }
+        self
     }
 
     /// Gets the vertical alignment of placement . This determines how any padding added by the tucking policy is distributed, and defaults to CHAFA_ALIGN_START.
@@ -51,11 +118,12 @@ impl Placement {
         unsafe { Align::from(ffi::chafa_placement_get_valign(self.raw)) }
     }
 
-    /// Sets the vertical alignment of placement . This determines how any padding added by the tucking policy is distributed.
-    pub fn set_valign(&self, align: Align) {
+    /// Sets the vertical alignment of placement . This determines how any padding added by the tucking policy is distributed. Returns placement so setters can be chained.
+    pub fn set_valign(&self, align: Align) -> &Self {
         unsafe {
             ffi::chafa_placement_set_valign(self.raw, align as u32);
         }
+        self
     }
 }
 
@@ -70,6 +138,9 @@ impl Drop for Placement {
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Tuck {
     /// Resize element to fit the area exactly, changing its aspect ratio.
     Stretch = ffi::ChafaTuck_CHAFA_TUCK_STRETCH,
@@ -79,6 +150,7 @@ pub enum Tuck {
     ShrinkToFit = ffi::ChafaTuck_CHAFA_TUCK_SHRINK_TO_FIT,
 
     /// Last supported tucking policy, plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaTuck_CHAFA_TUCK_MAX,
 }
 impl Into<u32> for Tuck {
@@ -104,15 +176,22 @@ impl From<u32> for Tuck {
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Align {
     /// Align flush with beginning of the area (top or left in LTR locales).
+    #[cfg_attr(feature = "serde", serde(alias = "left", alias = "top"))]
     Start = ffi::ChafaAlign_CHAFA_ALIGN_START,
     /// Align flush with end of the area (bottom or right in LTR locales).
+    #[cfg_attr(feature = "serde", serde(alias = "right", alias = "bottom"))]
     End = ffi::ChafaAlign_CHAFA_ALIGN_END,
     /// Align in the middle of the area.
+    #[cfg_attr(feature = "serde", serde(alias = "middle"))]
     Center = ffi::ChafaAlign_CHAFA_ALIGN_CENTER,
 
     /// Last supported alignment, plus one.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Max = ffi::ChafaAlign_CHAFA_ALIGN_MAX,
 }
 impl Into<u32> for Align {
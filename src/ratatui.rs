@@ -0,0 +1,311 @@
+use crate::canvas::{Canvas, CanvasMode, Config, PixelMode, PrintOptions};
+use crate::term::Info;
+use crate::{ChafaError, GeometryRequest, PixelType, calc_canvas_geometry, default_font_ratio};
+use ratatui_core::buffer::{Buffer, CellDiffOption};
+use ratatui_core::layout::Rect;
+use ratatui_core::style::{Color, Modifier};
+use ratatui_core::widgets::{StatefulWidget, Widget};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::num::NonZeroU16;
+
+/// A ratatui widget showing an image, scaled down to fit its area while keeping its aspect ratio and drawn from the area's top left corner.
+///
+/// In CHAFA_PIXEL_MODE_SYMBOLS the canvas' symbols and colors are copied into the buffer, so the image is clipped, layered and diffed like any other widget. In pixel modes the image's escape sequences are put in the first cell and the rest of the image's cells are skipped, so ratatui prints them once and leaves the image alone until something else is drawn there.
+///
+/// Rendering as a `StatefulWidget` keeps the result in a `ChafaImageState` and only redraws the canvas when the image or the size of the area changes, which keeps pixel modes from resending the image on every frame.
+pub struct ChafaImage<'a> {
+    pixels: &'a [u8],
+    generation: Option<u64>,
+    pixel_type: PixelType,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+    config: Option<&'a Config>,
+    term_info: Option<&'a Info>,
+}
+
+impl<'a> ChafaImage<'a> {
+    /// Creates a widget for an image.
+    /// # Parameters:
+    /// --- `pixels`: Image bytes;
+    /// --- `pixel_type`: Pixel format of pixels;
+    /// --- `width`: Width of the image, in pixels;
+    /// --- `height`: Height of the image, in pixels;
+    /// --- `rowstride`: Number of bytes between the start of each pixel row;
+    pub fn new(
+        pixels: &'a [u8],
+        pixel_type: PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+    ) -> Self {
+        Self {
+            pixels,
+            generation: None,
+            pixel_type,
+            width,
+            height,
+            rowstride,
+            config: None,
+            term_info: None,
+        }
+    }
+
+    /// Sets the canvas configuration, e.g. from `Config::new_detect()`. It is copied for each render and its geometry replaced to fit the area. Defaults to chafa's defaults, which draw with symbols.
+    pub fn config(mut self, config: &'a Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Sets a number identifying the image's contents, which the caller changes whenever the pixels change. A `ChafaImageState` then compares it instead of hashing the pixels on every render to tell whether the image changed.
+    pub fn generation(mut self, generation: u64) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+
+    /// Sets the terminal to format pixel modes for, including any passthrough it needs. Defaults to chafa's fallback sequences.
+    pub fn term_info(mut self, term_info: &'a Info) -> Self {
+        self.term_info = Some(term_info);
+        self
+    }
+
+    /// Draws the image on a canvas the size of area, or as much of it as the image needs.
+    fn draw(&self, area: Rect) -> Result<Option<Drawing>, ChafaError> {
+        let config = match self.config {
+            Some(config) => config.copy(),
+            None => Config::new(),
+        }?;
+        let pixel_mode = config.get_pixel_mode();
+        let (cell_width, cell_height) = config.get_cell_geometry();
+        let font_ratio = if pixel_mode != PixelMode::Symbols && cell_width > 0 && cell_height > 0 {
+            cell_width as f32 / cell_height as f32
+        } else {
+            default_font_ratio()
+        };
+        let geometry = calc_canvas_geometry(&GeometryRequest {
+            src: (self.width, self.height),
+            max_cols: Some(i32::from(area.width)),
+            max_rows: Some(i32::from(area.height)),
+            font_ratio,
+            zoom: false,
+            stretch: false,
+        });
+        if geometry.cols <= 0 || geometry.rows <= 0 {
+            return Ok(None);
+        }
+        config.set_geometry(geometry.cols, geometry.rows)?;
+
+        let canvas = Canvas::new(&config)?;
+        canvas.set_pixels(
+            self.pixels,
+            self.pixel_type,
+            self.width,
+            self.height,
+            self.rowstride,
+        )?;
+
+        let (cols, rows) = (geometry.cols as u16, geometry.rows as u16);
+        let content = if pixel_mode == PixelMode::Symbols {
+            let canvas_mode = config.get_canvas_mode();
+            let indexed = !matches!(
+                canvas_mode,
+                CanvasMode::TrueColor | CanvasMode::FgBg | CanvasMode::FgbgBgfg
+            );
+            let mut cells = Vec::with_capacity(usize::from(cols) * usize::from(rows));
+            for y in 0..geometry.rows {
+                for x in 0..geometry.cols {
+                    let (fg, bg, reversed) = match canvas_mode {
+                        CanvasMode::FgBg => (Color::Reset, Color::Reset, false),
+                        // Pens are 0 for the terminal's foreground color and -1 for its background, so a background drawn with the foreground pen is an inverted cell.
+                        CanvasMode::FgbgBgfg => {
+                            let (_, bg) = canvas.get_raw_colors_at(x, y);
+                            (Color::Reset, Color::Reset, bg >= 0)
+                        }
+                        _ if indexed => {
+                            let (fg, bg) = canvas.get_raw_colors_at(x, y);
+                            (to_color(fg, true), to_color(bg, true), false)
+                        }
+                        _ => {
+                            let (fg, bg) = canvas.get_colors_at(x, y);
+                            (to_color(fg, false), to_color(bg, false), false)
+                        }
+                    };
+                    let modifier = if reversed {
+                        Modifier::REVERSED
+                    } else {
+                        Modifier::empty()
+                    };
+                    cells.push((canvas.get_char_at(x, y), fg, bg, modifier));
+                }
+            }
+            Content::Cells(cells)
+        } else {
+            Content::Escape(canvas.create_string_with(self.term_info, &PrintOptions::new())?)
+        };
+        Ok(Some(Drawing {
+            cols,
+            rows,
+            content,
+        }))
+    }
+
+    /// Identifies what was drawn, so a cached drawing is only reused for the same image and area size. Without a generation, the pixels are hashed; their address alone could be reused by another image.
+    fn key(&self, area: Rect) -> Key {
+        let contents = self.generation.unwrap_or_else(|| {
+            let mut hasher = DefaultHasher::new();
+            self.pixels.hash(&mut hasher);
+            hasher.finish()
+        });
+        Key {
+            contents,
+            pixel_type: self.pixel_type as u32,
+            layout: (self.width, self.height, self.rowstride),
+            size: (area.width, area.height),
+        }
+    }
+}
+
+impl Widget for ChafaImage<'_> {
+    /// Draws the image from scratch. Failures leave the area untouched.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+        if area.is_empty() {
+            return;
+        }
+        if let Ok(Some(drawing)) = self.draw(area) {
+            drawing.copy_to(area, buf);
+        }
+    }
+}
+
+impl StatefulWidget for ChafaImage<'_> {
+    type State = ChafaImageState;
+
+    /// Draws the image, reusing the previous drawing if neither the image nor the size of the area changed. Failures leave the area untouched and are kept in the state.
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut ChafaImageState) {
+        let area = area.intersection(buf.area);
+        if area.is_empty() {
+            return;
+        }
+        let key = self.key(area);
+        if state.key != Some(key) {
+            state.key = Some(key);
+            (state.drawing, state.error) = match self.draw(area) {
+                Ok(drawing) => (drawing, None),
+                Err(e) => (None, Some(e)),
+            };
+        }
+        if let Some(drawing) = &state.drawing {
+            drawing.copy_to(area, buf);
+        }
+    }
+}
+
+/// The drawing a `ChafaImage` rendered last, kept between frames.
+#[derive(Default)]
+pub struct ChafaImageState {
+    key: Option<Key>,
+    drawing: Option<Drawing>,
+    error: Option<ChafaError>,
+}
+
+impl ChafaImageState {
+    /// Creates an empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the error of the last drawing, if it failed.
+    pub fn error(&self) -> Option<&ChafaError> {
+        self.error.as_ref()
+    }
+
+    /// Returns the size in cells of the last drawing, or `None` if nothing was drawn.
+    pub fn size(&self) -> Option<(u16, u16)> {
+        self.drawing
+            .as_ref()
+            .map(|drawing| (drawing.cols, drawing.rows))
+    }
+
+    /// Drops the last drawing, so the next render draws the image again. Call this after changing the config or term info the image is rendered with.
+    pub fn invalidate(&mut self) {
+        self.key = None;
+        self.drawing = None;
+        self.error = None;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Key {
+    contents: u64,
+    pixel_type: u32,
+    layout: (i32, i32, i32),
+    size: (u16, u16),
+}
+
+struct Drawing {
+    cols: u16,
+    rows: u16,
+    content: Content,
+}
+
+enum Content {
+    /// Symbol, colors and inversion of each cell, row by row.
+    Cells(Vec<(char, Color, Color, Modifier)>),
+    /// Escape sequences drawing the image in a pixel mode.
+    Escape(String),
+}
+
+impl Drawing {
+    /// Writes the drawing into the top left corner of area, clipped to it.
+    fn copy_to(&self, area: Rect, buf: &mut Buffer) {
+        let (cols, rows) = (self.cols.min(area.width), self.rows.min(area.height));
+        match &self.content {
+            Content::Cells(cells) => {
+                for y in 0..rows {
+                    for x in 0..cols {
+                        let (symbol, fg, bg, modifier) = cells[usize::from(y * self.cols + x)];
+                        let Some(cell) = buf.cell_mut((area.x + x, area.y + y)) else {
+                            continue;
+                        };
+                        // The right half of a wide character holds 0; ratatui skips it by itself.
+                        if symbol == '\0' {
+                            cell.reset();
+                        } else {
+                            cell.set_char(symbol);
+                        }
+                        cell.set_fg(fg).set_bg(bg);
+                        cell.modifier = modifier;
+                    }
+                }
+            }
+            Content::Escape(text) => {
+                // An image clipped by the area would spill over its edges, so it isn't drawn.
+                if cols < self.cols || rows < self.rows {
+                    return;
+                }
+                for y in 0..rows {
+                    for x in 0..cols {
+                        if let Some(cell) = buf.cell_mut((area.x + x, area.y + y)) {
+                            cell.reset();
+                            cell.set_diff_option(CellDiffOption::Skip);
+                        }
+                    }
+                }
+                if let Some(cell) = buf.cell_mut((area.x, area.y)) {
+                    cell.set_symbol(text)
+                        .set_diff_option(CellDiffOption::ForcedWidth(NonZeroU16::MIN));
+                }
+            }
+        }
+    }
+}
+
+/// Converts a color from the canvas, -1 meaning the terminal's default.
+fn to_color(color: i32, indexed: bool) -> Color {
+    match color {
+        ..0 => Color::Reset,
+        _ if indexed => Color::Indexed(color as u8),
+        _ => Color::Rgb((color >> 16) as u8, (color >> 8) as u8, color as u8),
+    }
+}
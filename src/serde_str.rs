@@ -0,0 +1,33 @@
+/*
+ * Serde support for types with chafa names of their own, which are written as strings: symbol tags in selector syntax ("block,border"), CPU features as describe_features() names ("mmx sse4.1") and sequences by their canonical names ("cursor_to_pos").
+ *
+ * Enums derive Serialize and Deserialize where they are defined, with the lowercase names of chafa's enum values, e.g. "indexed-256" or "shrink-to-fit", and the chafa CLI's spellings as aliases.
+ */
+
+use crate::SymbolTags;
+use crate::features::Features;
+use crate::term::Seq;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! serde_via_str {
+    ($($ty:ty),+) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    String::deserialize(deserializer)?
+                        .parse()
+                        .map_err(D::Error::custom)
+                }
+            }
+        )+
+    };
+}
+
+serde_via_str!(SymbolTags, Features, Seq);
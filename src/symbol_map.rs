@@ -2,8 +2,25 @@
  * https://hpjansson.org/chafa/ref/chafa-ChafaSymbolMap.html
  */
 
-use crate::ffi;
 use crate::misc;
+use crate::{ChafaError, ffi};
+use std::cell::RefCell;
+
+pub mod bitmap_font;
+mod builder;
+pub use builder::*;
+mod changes;
+pub use changes::*;
+#[cfg(feature = "fallback-glyphs")]
+pub mod fallback;
+mod matching;
+pub use matching::*;
+mod preset;
+pub use preset::*;
+#[cfg(feature = "font")]
+pub mod font;
+mod set_ops;
+mod width;
 
 /// A ChafaSymbolMap describes a selection of the supported textual symbols that can be used in building a printable output string from a ChafaCanvas.
 ///
@@ -14,16 +31,21 @@ use crate::misc;
 /// The number of available symbols is a significant factor in the speed of ChafaCanvas. For the fastest possible operation you could use a single symbol -- CHAFA_SYMBOL_TAG_VHALF works well by itself.
 pub struct SymbolMap {
     pub raw: *mut ffi::ChafaSymbolMap,
+    /// Everything applied to raw so far, since chafa can't report it back.
+    changes: RefCell<Vec<SymbolMapChange>>,
 }
 
 impl SymbolMap {
     /// Creates a new ChafaSymbolMap representing a set of Unicode symbols. The symbol map starts out empty.
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, ChafaError> {
         let raw: *mut ffi::ChafaSymbolMap = unsafe { ffi::chafa_symbol_map_new() };
         if raw.is_null() {
-            Err("Chafa -> Failed to create symbol map")
+            Err(ChafaError::Failed("Chafa -> Failed to create symbol map"))
         } else {
-            Ok(SymbolMap { raw })
+            Ok(SymbolMap {
+                raw,
+                changes: RefCell::new(Vec::new()),
+            })
         }
     }
 
@@ -32,6 +54,7 @@ impl SymbolMap {
         unsafe {
             ffi::chafa_symbol_map_add_by_tags(self.raw, tags.bits() as i32);
         }
+        self.record(SymbolMapChange::AddTags(tags));
     }
 
     /// Adds symbols in the code point range starting with first and ending with last to symbol_map.
@@ -42,6 +65,7 @@ impl SymbolMap {
         unsafe {
             ffi::chafa_symbol_map_add_by_range(self.raw, u32::from(first), u32::from(last));
         }
+        self.record(SymbolMapChange::AddRange(first, last));
     }
 
     /// Removes symbols matching the set of tags from symbol_map .
@@ -49,6 +73,7 @@ impl SymbolMap {
         unsafe {
             ffi::chafa_symbol_map_remove_by_tags(self.raw, tags.bits() as i32);
         }
+        self.record(SymbolMapChange::RemoveTags(tags));
     }
 
     /// Removes symbols in the code point range starting with first and ending with last from symbol_map .
@@ -56,6 +81,7 @@ impl SymbolMap {
         unsafe {
             ffi::chafa_symbol_map_remove_by_range(self.raw, u32::from(first), u32::from(last));
         }
+        self.record(SymbolMapChange::RemoveRange(first, last));
     }
 
     /// Parses a string consisting of symbol tags separated by [+-,] and applies the pattern to symbol_map . If the string begins with + or -, it's understood to be relative to the current set in symbol_map , otherwise the map is cleared first.
@@ -65,28 +91,28 @@ impl SymbolMap {
     /// Examples: "block,border" sets map to contain symbols matching either of those tags. "+block,border-dot,stipple" adds block and border symbols then removes dot and stipple symbols.
     ///
     /// If there is a parse error, none of the changes are applied.
-    pub fn apply_selectors(&self, selectors: &str) -> Result<(), String> {
+    pub fn apply_selectors(&self, selectors: &str) -> Result<(), SelectorError> {
+        let c_selectors = std::ffi::CString::new(selectors).map_err(|e| SelectorError {
+            token: None,
+            position: Some(e.nul_position()),
+            message: String::from("Selectors contain a NUL byte"),
+        })?;
+
         unsafe {
             let mut error: *mut ffi::GError = std::ptr::null_mut();
-            ffi::chafa_symbol_map_apply_selectors(
-                self.raw,
-                std::ffi::CString::new(selectors)
-                    .expect("Chafa -> Failed to create CString")
-                    .as_ptr(),
-                &mut error,
-            );
+            ffi::chafa_symbol_map_apply_selectors(self.raw, c_selectors.as_ptr(), &mut error);
             if !error.is_null() {
-                let msg = std::ffi::CStr::from_ptr((*error).message as *const std::os::raw::c_char)
-                    .to_string_lossy()
-                    .into_owned();
-                let err_msg = format!("Chafa -> Failed to apply selectors: {}", &msg);
-
+                let message =
+                    std::ffi::CStr::from_ptr((*error).message as *const std::os::raw::c_char)
+                        .to_string_lossy()
+                        .into_owned();
                 ffi::g_error_free(error);
 
-                return Err(err_msg);
+                return Err(SelectorError::new(selectors, message));
             }
         }
 
+        self.record(SymbolMapChange::Selectors(selectors.to_owned()));
         Ok(())
     }
 
@@ -116,6 +142,7 @@ impl SymbolMap {
                 if use_builtin_glyphs { 1 } else { 0 },
             );
         }
+        self.record(SymbolMapChange::AllowBuiltinGlyphs(use_builtin_glyphs));
     }
 
     /// Returns data for the glyph corresponding to code_point stored in symbol_map . Any of pixels_out , width_out , height_out and rowstride_out can be NULL, in which case the corresponding data is not retrieved.
@@ -147,9 +174,16 @@ impl SymbolMap {
             }
         }
 
+        // Copy the pixels into Rust-owned memory so the glyph can be handled like any other buffer.
+        let len = (height * rowstride) as usize;
+        let pixels = unsafe {
+            let pixels = std::slice::from_raw_parts(pixels_ptr as *const u8, len).to_vec();
+            ffi::g_free(pixels_ptr as ffi::gpointer);
+            pixels
+        };
+
         Some(Glyph {
-            pixels: pixels_ptr as *mut u8,
-            len: (height * rowstride) as usize,
+            pixels,
             width,
             height,
             rowstride,
@@ -164,21 +198,188 @@ impl SymbolMap {
     /// # Parameters:
     /// --- `code_point`: The Unicode code point for this glyph;
     /// --- `pixel_format`: Glyph pixel format of pixels;
-    pub fn add_glyph(&self, code_point: u32, pixel_format: misc::PixelType, glyph: &Glyph) {
+    /// --- `glyph`: The glyph to assign;
+    /// # Returns:
+    /// An error if the glyph's rows are too short for `pixel_format`.
+    pub fn add_glyph(
+        &self,
+        code_point: u32,
+        pixel_format: misc::PixelType,
+        glyph: &Glyph,
+    ) -> Result<(), ChafaError> {
+        self.add_glyph_from_slice(
+            code_point,
+            pixel_format,
+            &glyph.pixels,
+            glyph.width,
+            glyph.height,
+            glyph.rowstride,
+        )
+    }
+
+    /// Assigns a rendered glyph to a Unicode code point, reading its pixels from a slice. See `add_glyph()`.
+    /// # Parameters:
+    /// --- `code_point`: The Unicode code point for this glyph;
+    /// --- `pixel_format`: Glyph pixel format of pixels;
+    /// --- `pixels`: Glyph pixel data. It must hold at least `rowstride * (height - 1) + width * bytes per pixel` bytes;
+    /// --- `width`: Glyph width in pixels;
+    /// --- `height`: Glyph height in pixels;
+    /// --- `rowstride`: Offset from start of one row to the next, in bytes.
+    pub fn add_glyph_from_slice(
+        &self,
+        code_point: u32,
+        pixel_format: misc::PixelType,
+        pixels: &[u8],
+        width: i32,
+        height: i32,
+        rowstride: i32,
+    ) -> Result<(), ChafaError> {
+        check_glyph_layout(pixel_format, pixels.len(), width, height, rowstride)?;
+
         unsafe {
             ffi::chafa_symbol_map_add_glyph(
                 self.raw,
                 code_point,
                 pixel_format as u32,
-                glyph.pixels as *mut std::os::raw::c_void,
-                glyph.width,
-                glyph.height,
-                glyph.rowstride,
+                pixels.as_ptr() as *mut std::os::raw::c_void,
+                width,
+                height,
+                rowstride,
             );
         }
+
+        // Keep a copy for the record, padded out to whole rows.
+        let mut data = pixels.to_vec();
+        data.resize(rowstride as usize * height as usize, 0);
+        self.record(SymbolMapChange::Glyph(
+            code_point,
+            pixel_format,
+            Glyph {
+                pixels: data,
+                width,
+                height,
+                rowstride,
+            },
+        ));
+        Ok(())
+    }
+
+    /// Assigns a double-width glyph to a wide code point, such as a CJK ideograph, so it can be used as a two-cell drawing character.
+    ///
+    /// Chafa decides whether an imported symbol is wide from the code point, and tags it CHAFA_SYMBOL_TAG_WIDE along with CHAFA_SYMBOL_TAG_IMPORTED. Symbol maps often exclude wide symbols, so add those tags to the selection to use the glyph.
+    /// # Parameters:
+    /// --- `c`: A character Unicode considers wide;
+    /// --- `pixel_format`: Glyph pixel format of pixels;
+    /// --- `glyph`: The glyph to assign. It must be 2 * SYMBOL_WIDTH_PIXELS wide and SYMBOL_HEIGHT_PIXELS high;
+    pub fn add_wide_glyph(
+        &self,
+        c: char,
+        pixel_format: misc::PixelType,
+        glyph: &Glyph,
+    ) -> Result<(), ChafaError> {
+        let (width, height) = (2 * SYMBOL_WIDTH_PIXELS as i32, SYMBOL_HEIGHT_PIXELS as i32);
+        if glyph.width != width || glyph.height != height {
+            return Err(ChafaError::InvalidInput(format!(
+                "Wide glyphs must be {}x{}, got {}x{}",
+                width, height, glyph.width, glyph.height
+            )));
+        }
+        if unsafe { ffi::g_unichar_iswide(u32::from(c)) } == 0 {
+            return Err(ChafaError::InvalidInput(format!(
+                "U+{:04X} is not a wide character",
+                u32::from(c)
+            )));
+        }
+
+        self.add_glyph(u32::from(c), pixel_format, glyph)
+    }
+
+    /// Adds change to the record, folding it into what's there so the record only grows with changes that still have an effect.
+    ///
+    /// Chafa applies the selection when the map is used, regardless of when glyphs were imported, so only the order of selection changes matters.
+    fn record(&self, change: SymbolMapChange) {
+        let mut changes = self.changes.borrow_mut();
+        match &change {
+            SymbolMapChange::Glyph(code_point, ..) => {
+                if let Some(old) = changes
+                    .iter_mut()
+                    .find(|c| matches!(c, SymbolMapChange::Glyph(old, ..) if old == code_point))
+                {
+                    *old = change;
+                    return;
+                }
+            }
+            SymbolMapChange::AllowBuiltinGlyphs(_) => {
+                if let Some(old) = changes
+                    .iter_mut()
+                    .find(|c| matches!(c, SymbolMapChange::AllowBuiltinGlyphs(_)))
+                {
+                    *old = change;
+                    return;
+                }
+            }
+            SymbolMapChange::Selectors(selectors) if set_ops::is_clearing(selectors) => {
+                changes.retain(|c| !c.is_selection());
+            }
+            _ => {
+                if let Some(last) = changes.iter_mut().rev().find(|c| c.is_selection()) {
+                    // Applying the same change twice in a row selects the same symbols as applying it once.
+                    if *last == change {
+                        return;
+                    }
+                    if let (
+                        SymbolMapChange::AddRange(first, end),
+                        SymbolMapChange::AddRange(new_first, new_end),
+                    )
+                    | (
+                        SymbolMapChange::RemoveRange(first, end),
+                        SymbolMapChange::RemoveRange(new_first, new_end),
+                    ) = (&mut *last, &change)
+                        && u32::from(*new_first) <= u32::from(*end).saturating_add(1)
+                        && u32::from(*first) <= u32::from(*new_end).saturating_add(1)
+                    {
+                        *first = (*first).min(*new_first);
+                        *end = (*end).max(*new_end);
+                        return;
+                    }
+                }
+            }
+        }
+        changes.push(change);
     }
 }
 
+/// Checks that a glyph with the given dimensions fits in `len` bytes of pixel data.
+fn check_glyph_layout(
+    pixel_format: misc::PixelType,
+    len: usize,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+) -> Result<(), ChafaError> {
+    if width <= 0 || height <= 0 {
+        return Err(ChafaError::InvalidInput(format!(
+            "Glyph dimensions must be positive, got {}x{}",
+            width, height
+        )));
+    }
+    let row_len = width as usize * pixel_format.bytes_per_pixel();
+    if rowstride < 0 || (rowstride as usize) < row_len {
+        return Err(ChafaError::InvalidInput(format!(
+            "Glyph rowstride {} is smaller than a row of {} bytes",
+            rowstride, row_len
+        )));
+    }
+    let needed = rowstride as usize * (height as usize - 1) + row_len;
+    if len < needed {
+        return Err(ChafaError::InvalidInput(format!(
+            "Glyph needs {} bytes of pixel data, got {}",
+            needed, len
+        )));
+    }
+    Ok(())
+}
+
 impl Drop for SymbolMap {
     fn drop(&mut self) {
         if !self.raw.is_null() {
@@ -189,36 +390,237 @@ impl Drop for SymbolMap {
     }
 }
 
+/// Error returned when a selector string can't be applied to a symbol map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    /// The selector chafa rejected, if it named one.
+    pub token: Option<String>,
+    /// Byte offset of the offending token (or NUL byte) in the selector string, if known.
+    pub position: Option<usize>,
+    /// The error message reported by chafa.
+    pub message: String,
+}
+
+impl SelectorError {
+    /// Builds an error from chafa's message, which quotes the offending token, e.g. "Unrecognized symbol tag 'foo'."
+    fn new(selectors: &str, message: String) -> Self {
+        let token = message
+            .split_once('\'')
+            .and_then(|(_, rest)| rest.split_once('\''))
+            .map(|(token, _)| token.to_owned())
+            .filter(|token| !token.is_empty());
+        let position = token
+            .as_ref()
+            .and_then(|token| selectors.find(token.as_str()));
+
+        Self {
+            token,
+            position,
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Chafa -> Failed to apply selectors: {}", self.message)
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+/// Pixel data for a glyph, as returned by `SymbolMap::get_glyph()` or built with `Glyph::from_vec()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Glyph {
-    /// Pointer to the glyph data.
-    pub pixels: *mut u8,
-    /// Length of the glyph data.
-    pub len: usize,
+    /// The glyph data.
+    pixels: Vec<u8>,
     /// Width of the glyph, in pixels.
-    pub width: i32,
+    width: i32,
     /// Height of the glyph, in pixels.
-    pub height: i32,
+    height: i32,
     /// Offset from start of one row to the next, in bytes.
-    pub rowstride: i32,
+    rowstride: i32,
 }
 
 impl Glyph {
+    /// Creates a glyph from owned pixel data. The data must hold `height` rows of `rowstride` bytes each.
+    /// # Parameters:
+    /// --- `pixels`: Glyph pixel data;
+    /// --- `pixel_format`: Pixel format of pixels, which rowstride is checked against. Pass the same format to `SymbolMap::add_glyph()`;
+    /// --- `width`: Width of the glyph, in pixels;
+    /// --- `height`: Height of the glyph, in pixels;
+    /// --- `rowstride`: Offset from start of one row to the next, in bytes. It must be at least `width` times the bytes per pixel of pixel_format;
+    pub fn from_vec(
+        pixels: Vec<u8>,
+        pixel_format: misc::PixelType,
+        width: i32,
+        height: i32,
+        rowstride: i32,
+    ) -> Result<Self, ChafaError> {
+        misc::validate_image(None, pixel_format, (width, height, rowstride))?;
+        let needed = rowstride as usize * height as usize;
+        if pixels.len() < needed {
+            return Err(ChafaError::InvalidInput(format!(
+                "Glyph needs {} bytes of pixel data, got {}",
+                needed,
+                pixels.len()
+            )));
+        }
+
+        Ok(Self {
+            pixels,
+            width,
+            height,
+            rowstride,
+        })
+    }
+
+    /// Width of the glyph, in pixels.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Height of the glyph, in pixels.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Offset from start of one row to the next, in bytes.
+    pub fn rowstride(&self) -> i32 {
+        self.rowstride
+    }
+
+    /// Returns the glyph's pixel data.
     pub fn as_slice(&self) -> &[u8] {
-        if self.pixels.is_null() {
-            &[]
-        } else {
-            unsafe { std::slice::from_raw_parts(self.pixels, self.len) }
+        &self.pixels
+    }
+
+    /// Returns the glyph's pixel data for modification.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    /// Consumes the glyph, returning its pixel data.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Returns how much of each pixel the glyph covers, from 0 to 255, row by row without padding.
+    ///
+    /// Like chafa, this uses the alpha channel if pixel_format has one, and an average of the color channels otherwise.
+    /// # Parameters:
+    /// --- `pixel_format`: Pixel format the glyph's data is in;
+    pub fn coverage(&self, pixel_format: misc::PixelType) -> Vec<u8> {
+        let bpp = pixel_format.bytes_per_pixel();
+        let alpha = pixel_format.alpha_index();
+
+        let mut coverage = Vec::with_capacity((self.width * self.height) as usize);
+        for row in self.pixels.chunks(self.rowstride as usize) {
+            for px in row.chunks_exact(bpp).take(self.width as usize) {
+                coverage.push(match alpha {
+                    Some(i) => px[i],
+                    None => ((px[0] as u32 + px[1] as u32 + px[2] as u32) / 3) as u8,
+                });
+            }
         }
+        coverage
     }
-}
 
-impl Drop for Glyph {
-    fn drop(&mut self) {
-        if !self.pixels.is_null() {
-            unsafe {
-                ffi::g_free(self.pixels as ffi::gpointer);
+    /// Converts the glyph to an opaque RGBA8Unassociated image with white for covered pixels on black, so it can be saved or displayed while debugging symbol choices. Rows are width * 4 bytes long.
+    /// # Parameters:
+    /// --- `pixel_format`: Pixel format the glyph's data is in;
+    pub fn to_image(&self, pixel_format: misc::PixelType) -> Vec<u8> {
+        self.coverage(pixel_format)
+            .into_iter()
+            .flat_map(|v| [v, v, v, 0xff])
+            .collect()
+    }
+
+    /// Scales the glyph to a new size, e.g. to bring a bitmap of any size to SYMBOL_WIDTH_PIXELS x SYMBOL_HEIGHT_PIXELS before passing it to `SymbolMap::add_glyph()`.
+    ///
+    /// Each axis that shrinks is box filtered, averaging the source pixels a target pixel covers, so thin strokes fade instead of vanishing. Each axis that grows uses the nearest source pixel, keeping edges sharp.
+    /// # Parameters:
+    /// --- `pixel_format`: Pixel format the glyph's data is in. The result uses the same format;
+    /// --- `to_width`: Width of the new glyph, in pixels;
+    /// --- `to_height`: Height of the new glyph, in pixels;
+    pub fn rescaled(
+        &self,
+        pixel_format: misc::PixelType,
+        to_width: i32,
+        to_height: i32,
+    ) -> Result<Glyph, ChafaError> {
+        if to_width <= 0 || to_height <= 0 {
+            return Err(ChafaError::InvalidInput(format!(
+                "Glyph size must be positive, got {}x{}",
+                to_width, to_height
+            )));
+        }
+        let bpp = pixel_format.bytes_per_pixel();
+        if self.width as usize * bpp > self.rowstride as usize {
+            return Err(ChafaError::InvalidInput(format!(
+                "Rowstride {} is too short for {} pixels of {} bytes",
+                self.rowstride, self.width, bpp
+            )));
+        }
+
+        let pixels = misc::rescale_pixels(
+            &self.pixels,
+            (self.width, self.height, self.rowstride),
+            bpp,
+            to_width,
+            to_height,
+        );
+        Glyph::from_vec(
+            pixels,
+            pixel_format,
+            to_width,
+            to_height,
+            to_width * bpp as i32,
+        )
+    }
+
+    /// Draws the glyph with block characters, one line per pixel row. Each pixel is two characters wide to make up for the shape of terminal cells, and partial coverage is shown with shades.
+    /// # Parameters:
+    /// --- `pixel_format`: Pixel format the glyph's data is in;
+    pub fn preview(&self, pixel_format: misc::PixelType) -> String {
+        const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+        let mut out = String::new();
+        for row in self.coverage(pixel_format).chunks(self.width as usize) {
+            for v in row {
+                let shade = SHADES[(*v as usize * 4 + 127) / 255];
+                out.push(shade);
+                out.push(shade);
             }
+            out.push('\n');
         }
+        out
+    }
+}
+
+#[cfg(feature = "image")]
+impl SymbolMap {
+    /// Returns the glyph for code_point as an image, white where it is inked and transparent elsewhere. Handy for dumping glyphs to PNG while debugging symbol choices.
+    pub fn get_glyph_image(&self, code_point: u32) -> Option<image::RgbaImage> {
+        let glyph = self.get_glyph(code_point, misc::PixelType::RGBA8Unassociated)?;
+        let row_len = glyph.width as usize * 4;
+        let pixels: Vec<u8> = glyph
+            .pixels
+            .chunks(glyph.rowstride as usize)
+            .flat_map(|row| &row[..row_len])
+            .copied()
+            .collect();
+        image::RgbaImage::from_raw(glyph.width as u32, glyph.height as u32, pixels)
+    }
+
+    /// Returns the glyph for code_point as a grayscale image of its coverage, white where it is inked on black.
+    pub fn get_glyph_gray_image(&self, code_point: u32) -> Option<image::GrayImage> {
+        let glyph = self.get_glyph(code_point, misc::PixelType::RGBA8Unassociated)?;
+        image::GrayImage::from_raw(
+            glyph.width as u32,
+            glyph.height as u32,
+            glyph.coverage(misc::PixelType::RGBA8Unassociated),
+        )
     }
 }
 
@@ -229,6 +631,7 @@ pub const SYMBOL_HEIGHT_PIXELS: u32 = ffi::CHAFA_SYMBOL_HEIGHT_PIXELS;
 
 bitflags::bitflags! {
     #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
     pub struct SymbolTags: i32 {
         /// Special value meaning no symbols.
         const None = ffi::ChafaSymbolTags_CHAFA_SYMBOL_TAG_NONE;
@@ -298,3 +701,114 @@ bitflags::bitflags! {
         const All = ffi::ChafaSymbolTags_CHAFA_SYMBOL_TAG_ALL;
     }
 }
+
+impl SymbolTags {
+    /// Selector names for each tag, as used by `SymbolMap::apply_selectors()`. Joint sets come first so that formatting prefers them.
+    const NAMES: [(&'static str, SymbolTags); 33] = [
+        ("all", SymbolTags::All),
+        ("bad", SymbolTags::Bad),
+        ("half", SymbolTags::Half),
+        ("alnum", SymbolTags::AlNum),
+        ("space", SymbolTags::Space),
+        ("solid", SymbolTags::Solid),
+        ("stipple", SymbolTags::Stipple),
+        ("block", SymbolTags::Block),
+        ("border", SymbolTags::Border),
+        ("diagonal", SymbolTags::Diagonal),
+        ("dot", SymbolTags::Dot),
+        ("quad", SymbolTags::Quad),
+        ("hhalf", SymbolTags::Hhalf),
+        ("vhalf", SymbolTags::Vhalf),
+        ("inverted", SymbolTags::Inverted),
+        ("braille", SymbolTags::Braille),
+        ("technical", SymbolTags::Technical),
+        ("geometric", SymbolTags::Geometric),
+        ("ascii", SymbolTags::ASCII),
+        ("alpha", SymbolTags::Alpha),
+        ("digit", SymbolTags::Digit),
+        ("narrow", SymbolTags::Narrow),
+        ("wide", SymbolTags::Wide),
+        ("ambiguous", SymbolTags::Ambiguous),
+        ("ugly", SymbolTags::Ugly),
+        ("legacy", SymbolTags::Legacy),
+        ("sextant", SymbolTags::Sextant),
+        ("wedge", SymbolTags::Wedge),
+        ("latin", SymbolTags::Latin),
+        ("imported", SymbolTags::Imported),
+        ("octant", SymbolTags::Octant),
+        ("extra", SymbolTags::Extra),
+        ("none", SymbolTags::None),
+    ];
+
+    /// Looks up a single tag by its selector name, ignoring ASCII case.
+    pub fn from_selector_name(name: &str) -> Option<SymbolTags> {
+        let name = name.trim().to_ascii_lowercase();
+        let name = if name == "import" { "imported" } else { &name };
+        SymbolTags::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, tags)| *tags)
+    }
+}
+
+impl std::fmt::Display for SymbolTags {
+    /// Formats the tags as a comma-separated list of selector names, e.g. "block,border". Joint sets are named where they apply, and an empty set is "none". Bits without a name are written last in hex, e.g. "0x40000000", which `FromStr` reads back.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+
+        let mut left = *self;
+        let mut names: Vec<String> = Vec::new();
+        for (name, tags) in SymbolTags::NAMES.iter() {
+            if !tags.is_empty() && left.intersects(*tags) && self.contains(*tags) {
+                names.push((*name).to_owned());
+                left.remove(*tags);
+            }
+        }
+        if !left.is_empty() {
+            names.push(format!("{:#x}", left.bits()));
+        }
+        write!(f, "{}", names.join(","))
+    }
+}
+
+impl std::str::FromStr for SymbolTags {
+    type Err = ChafaError;
+
+    /// Parses tags in selector syntax: names after `+` (or at the start) are added and names after `-` are removed, with `,` continuing the current operation, e.g. "block,border-dot,stipple". Matching ignores ASCII case. Raw bits may be given in hex with a `0x` prefix, as `Display` writes bits without a name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = SymbolTags::empty();
+        let mut remove = false;
+        let mut rest = s.trim();
+
+        while !rest.is_empty() {
+            let end = rest.find([',', '+', '-']).unwrap_or(rest.len());
+            let name = rest[..end].trim();
+            if !name.is_empty() {
+                let named = SymbolTags::from_selector_name(name)
+                    .or_else(|| {
+                        let hex = name.strip_prefix("0x").or(name.strip_prefix("0X"))?;
+                        let bits = u32::from_str_radix(hex, 16).ok()?;
+                        Some(SymbolTags::from_bits_retain(bits as i32))
+                    })
+                    .ok_or_else(|| {
+                        ChafaError::InvalidInput(format!("Unknown symbol tag: {}", name))
+                    })?;
+                if remove {
+                    tags.remove(named);
+                } else {
+                    tags.insert(named);
+                }
+            }
+
+            match rest[end..].chars().next() {
+                Some('-') => remove = true,
+                Some('+') => remove = false,
+                _ => {}
+            }
+            rest = rest.get(end + 1..).unwrap_or("");
+        }
+        Ok(tags)
+    }
+}
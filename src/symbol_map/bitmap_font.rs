@@ -0,0 +1,312 @@
+/*
+ * Parses BDF and PSF bitmap fonts so console fonts can be imported into a SymbolMap.
+ */
+
+use crate::{ChafaError, Glyph, PixelType, SymbolMap};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE_512: u8 = 0x01;
+const PSF1_MODE_HAS_TAB: u8 = 0x02;
+const PSF1_MODE_HAS_SEQ: u8 = 0x04;
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+
+/// A bitmap font loaded from a BDF or PSF file. Every glyph is placed in a cell of the font's size, so it can be handed to chafa as-is.
+pub struct BitmapFont {
+    cell_width: i32,
+    cell_height: i32,
+    glyphs: Vec<(char, Glyph)>,
+}
+
+impl BitmapFont {
+    /// Loads a font from a file, telling PSF (versions 1 and 2) and BDF apart by their contents.
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ChafaError> {
+        let data = std::fs::read(path.as_ref()).map_err(|e| {
+            ChafaError::InvalidInput(format!(
+                "Failed to read font {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+        Self::from_bytes(&data)
+    }
+
+    /// Parses a font in any of the supported formats.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ChafaError> {
+        if data.starts_with(&PSF1_MAGIC) || data.starts_with(&PSF2_MAGIC) {
+            Self::parse_psf(data)
+        } else {
+            Self::parse_bdf(data)
+        }
+    }
+
+    /// Parses a font in the X11 Bitmap Distribution Format.
+    ///
+    /// Glyphs are positioned in a cell the size of FONTBOUNDINGBOX according to their own BBX, so glyphs smaller than the cell keep their place relative to the baseline. Glyphs without a Unicode ENCODING are skipped.
+    pub fn parse_bdf(data: &[u8]) -> Result<Self, ChafaError> {
+        let text = String::from_utf8_lossy(data);
+        let mut lines = text.lines().map(str::trim);
+
+        let mut font_bbx: Option<[i32; 4]> = None;
+        let mut glyphs: Vec<(char, Glyph)> = Vec::new();
+
+        while let Some(line) = lines.next() {
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match keyword {
+                "FONTBOUNDINGBOX" => font_bbx = Some(parse_bdf_numbers(rest)?),
+                "STARTCHAR" => {
+                    let [cell_w, cell_h, cell_xoff, cell_yoff] = font_bbx.ok_or_else(|| {
+                        ChafaError::InvalidInput(String::from("BDF glyph before FONTBOUNDINGBOX"))
+                    })?;
+                    if cell_w <= 0 || cell_h <= 0 {
+                        return Err(ChafaError::InvalidInput(format!(
+                            "Invalid BDF bounding box {}x{}",
+                            cell_w, cell_h
+                        )));
+                    }
+
+                    let mut encoding: Option<u32> = None;
+                    let mut bbx = [cell_w, cell_h, cell_xoff, cell_yoff];
+                    let mut rows: Vec<Vec<u8>> = Vec::new();
+                    let mut in_bitmap = false;
+                    for line in lines.by_ref() {
+                        if line == "ENDCHAR" {
+                            break;
+                        }
+                        if in_bitmap {
+                            rows.push(parse_hex_row(line)?);
+                            continue;
+                        }
+                        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+                        match keyword {
+                            "ENCODING" => {
+                                encoding = rest
+                                    .split_whitespace()
+                                    .next()
+                                    .and_then(|n| n.parse::<i64>().ok())
+                                    .and_then(|n| u32::try_from(n).ok())
+                            }
+                            "BBX" => bbx = parse_bdf_numbers(rest)?,
+                            "BITMAP" => in_bitmap = true,
+                            _ => {}
+                        }
+                    }
+
+                    let Some(c) = encoding.and_then(char::from_u32) else {
+                        continue;
+                    };
+                    // Rows count down from the top of the cell; the baseline sits cell_yoff above its bottom.
+                    let [w, h, xoff, yoff] = bbx;
+                    let left = xoff - cell_xoff;
+                    let top = (cell_h + cell_yoff) - (h + yoff);
+                    let glyph = glyph_from_bits(cell_w, cell_h, |x, y| {
+                        let (gx, gy) = (x - left, y - top);
+                        if gx < 0 || gy < 0 || gx >= w || gy >= h {
+                            return false;
+                        }
+                        rows.get(gy as usize)
+                            .and_then(|row| row.get(gx as usize / 8))
+                            .is_some_and(|byte| byte & (0x80 >> (gx % 8)) != 0)
+                    })?;
+                    glyphs.push((c, glyph));
+                }
+                _ => {}
+            }
+        }
+
+        let [cell_width, cell_height, _, _] = font_bbx.ok_or_else(|| {
+            ChafaError::InvalidInput(String::from("Not a BDF font: missing FONTBOUNDINGBOX"))
+        })?;
+        Ok(Self {
+            cell_width,
+            cell_height,
+            glyphs,
+        })
+    }
+
+    /// Parses a Linux console font in PC Screen Font format, version 1 or 2.
+    ///
+    /// Code points come from the font's Unicode table. Fonts without one are assumed to follow the glyph order of Latin-1, so glyph n maps to U+00nn. Multi-character sequences in the table are skipped.
+    pub fn parse_psf(data: &[u8]) -> Result<Self, ChafaError> {
+        let truncated = || ChafaError::InvalidInput(String::from("PSF font is truncated"));
+
+        let (width, height, count, glyph_size, glyphs_start, table) =
+            if data.starts_with(&PSF1_MAGIC) {
+                let mode = *data.get(2).ok_or_else(truncated)?;
+                let height = *data.get(3).ok_or_else(truncated)? as usize;
+                let count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+                let has_table = mode & (PSF1_MODE_HAS_TAB | PSF1_MODE_HAS_SEQ) != 0;
+                (8, height, count, height, 4, has_table.then_some(false))
+            } else if data.starts_with(&PSF2_MAGIC) {
+                let field = |i: usize| -> Result<usize, ChafaError> {
+                    let bytes = data.get(i * 4..i * 4 + 4).ok_or_else(truncated)?;
+                    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+                };
+                let has_table = field(3)? as u32 & PSF2_HAS_UNICODE_TABLE != 0;
+                (
+                    field(7)?,
+                    field(6)?,
+                    field(4)?,
+                    field(5)?,
+                    field(2)?,
+                    has_table.then_some(true),
+                )
+            } else {
+                return Err(ChafaError::InvalidInput(String::from(
+                    "Not a PSF font: bad magic",
+                )));
+            };
+
+        let row_len = width.div_ceil(8);
+        if width == 0 || height == 0 || glyph_size < row_len * height {
+            return Err(ChafaError::InvalidInput(format!(
+                "Invalid PSF glyph size {}x{} in {} bytes",
+                width, height, glyph_size
+            )));
+        }
+        let glyphs_end = count
+            .checked_mul(glyph_size)
+            .and_then(|n| n.checked_add(glyphs_start))
+            .filter(|end| *end <= data.len())
+            .ok_or_else(truncated)?;
+
+        let code_points: Vec<Vec<char>> = match table {
+            Some(utf8) => parse_psf_table(&data[glyphs_end..], count, utf8),
+            None => (0..count)
+                .map(|i| char::from_u32(i as u32).into_iter().collect())
+                .collect(),
+        };
+
+        let mut glyphs: Vec<(char, Glyph)> = Vec::new();
+        for (i, chars) in code_points.iter().enumerate() {
+            let bits = &data[glyphs_start + i * glyph_size..][..glyph_size];
+            let glyph = glyph_from_bits(width as i32, height as i32, |x, y| {
+                bits[y as usize * row_len + x as usize / 8] & (0x80 >> (x % 8)) != 0
+            })?;
+            for c in chars {
+                glyphs.push((*c, glyph.clone()));
+            }
+        }
+
+        Ok(Self {
+            cell_width: width as i32,
+            cell_height: height as i32,
+            glyphs,
+        })
+    }
+
+    /// Returns the size of the font's cell, in pixels, as (width, height).
+    pub fn cell_size(&self) -> (i32, i32) {
+        (self.cell_width, self.cell_height)
+    }
+
+    /// Returns the glyph for a character, if the font has one.
+    pub fn get(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.iter().find(|(gc, _)| *gc == c).map(|(_, g)| g)
+    }
+
+    /// Returns an iterator over the characters in the font along with their glyphs. Glyphs are RGBA8Unassociated, opaque white where the bitmap is set and transparent elsewhere.
+    pub fn glyphs(&self) -> impl Iterator<Item = (char, &Glyph)> + '_ {
+        self.glyphs.iter().map(|(c, g)| (*c, g))
+    }
+}
+
+impl SymbolMap {
+    /// Assigns every glyph in a bitmap font to symbol_map with `add_glyph()`.
+    /// # Returns:
+    /// The number of glyphs added.
+    pub fn add_bitmap_font(&self, font: &BitmapFont) -> Result<usize, ChafaError> {
+        for (c, glyph) in font.glyphs() {
+            self.add_glyph(u32::from(c), PixelType::RGBA8Unassociated, glyph)?;
+        }
+        Ok(font.glyphs.len())
+    }
+}
+
+/// Builds an RGBA8Unassociated glyph from a predicate telling which pixels are set.
+fn glyph_from_bits<F: Fn(i32, i32) -> bool>(
+    width: i32,
+    height: i32,
+    is_set: F,
+) -> Result<Glyph, ChafaError> {
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let alpha = if is_set(x, y) { 0xff } else { 0x00 };
+            pixels.extend_from_slice(&[0xff, 0xff, 0xff, alpha]);
+        }
+    }
+    Glyph::from_vec(
+        pixels,
+        PixelType::RGBA8Unassociated,
+        width,
+        height,
+        width * 4,
+    )
+}
+
+fn parse_bdf_numbers(s: &str) -> Result<[i32; 4], ChafaError> {
+    let numbers: Vec<i32> = s
+        .split_whitespace()
+        .map(|n| n.parse::<i32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| ChafaError::InvalidInput(format!("Invalid BDF bounding box: {}", s)))?;
+    numbers
+        .try_into()
+        .map_err(|_| ChafaError::InvalidInput(format!("Invalid BDF bounding box: {}", s)))
+}
+
+fn parse_hex_row(s: &str) -> Result<Vec<u8>, ChafaError> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return Err(ChafaError::InvalidInput(format!(
+            "Invalid BDF bitmap row: {}",
+            s
+        )));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&s[i..i + 2], 16)
+                .map_err(|_| ChafaError::InvalidInput(format!("Invalid BDF bitmap row: {}", s)))
+        })
+        .collect()
+}
+
+/// Reads the Unicode table of a PSF font: one entry per glyph, listing the code points it stands for. PSF1 tables hold UCS-2 values ending with 0xFFFF, where 0xFFFE starts a sequence; PSF2 tables hold UTF-8 ending with 0xFF, where 0xFE starts a sequence.
+fn parse_psf_table(table: &[u8], count: usize, utf8: bool) -> Vec<Vec<char>> {
+    let mut entries: Vec<Vec<char>> = Vec::with_capacity(count);
+    let mut pos = 0;
+
+    while entries.len() < count && pos < table.len() {
+        let mut chars: Vec<char> = Vec::new();
+        let mut in_seq = false;
+        if utf8 {
+            let end = table[pos..]
+                .iter()
+                .position(|b| *b == 0xff)
+                .map_or(table.len(), |n| pos + n);
+            for part in table[pos..end].split(|b| *b == 0xfe) {
+                if !in_seq {
+                    chars.extend(String::from_utf8_lossy(part).chars());
+                }
+                in_seq = true;
+            }
+            pos = end + 1;
+        } else {
+            while pos + 1 < table.len() {
+                let value = u16::from_le_bytes([table[pos], table[pos + 1]]);
+                pos += 2;
+                match value {
+                    0xffff => break,
+                    0xfffe => in_seq = true,
+                    _ if !in_seq => chars.extend(char::from_u32(u32::from(value))),
+                    _ => {}
+                }
+            }
+        }
+        entries.push(chars);
+    }
+
+    entries.resize(count, Vec::new());
+    entries
+}
@@ -0,0 +1,82 @@
+use crate::{ChafaError, Glyph, PixelType, SelectorError, SymbolMap, SymbolMapChange, SymbolTags};
+
+/// Describes a ChafaSymbolMap step by step. Nothing is created until `build()`, which applies the steps in the order they were given and reports the first one that fails, e.g. `SymbolMap::builder().tags(SymbolTags::Block | SymbolTags::Border).remove(SymbolTags::Dot).build()`.
+#[derive(Default)]
+pub struct SymbolMapBuilder {
+    steps: Vec<SymbolMapChange>,
+}
+
+impl SymbolMapBuilder {
+    /// Creates a builder for an empty symbol map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds symbols matching the set of tags. See `SymbolMap::add_by_tags()`.
+    pub fn tags(mut self, tags: SymbolTags) -> Self {
+        self.steps.push(SymbolMapChange::AddTags(tags));
+        self
+    }
+
+    /// Removes symbols matching the set of tags. See `SymbolMap::remove_by_tags()`.
+    pub fn remove(mut self, tags: SymbolTags) -> Self {
+        self.steps.push(SymbolMapChange::RemoveTags(tags));
+        self
+    }
+
+    /// Adds symbols in the code point range from first to last, inclusive. See `SymbolMap::add_by_range()`.
+    pub fn range(mut self, first: char, last: char) -> Self {
+        self.steps.push(SymbolMapChange::AddRange(first, last));
+        self
+    }
+
+    /// Removes symbols in the code point range from first to last, inclusive. See `SymbolMap::remove_by_range()`.
+    pub fn remove_range(mut self, first: char, last: char) -> Self {
+        self.steps.push(SymbolMapChange::RemoveRange(first, last));
+        self
+    }
+
+    /// Applies a selector string such as "block,border-dot". See `SymbolMap::apply_selectors()`.
+    ///
+    /// A selector string that doesn't begin with + or - clears the map first, including the steps before it.
+    pub fn selectors(mut self, selectors: &str) -> Self {
+        self.steps
+            .push(SymbolMapChange::Selectors(selectors.to_owned()));
+        self
+    }
+
+    /// Assigns a glyph to a code point. See `SymbolMap::add_glyph()`.
+    pub fn glyph(mut self, c: char, pixel_format: PixelType, glyph: Glyph) -> Self {
+        self.steps
+            .push(SymbolMapChange::Glyph(u32::from(c), pixel_format, glyph));
+        self
+    }
+
+    /// Sets whether the built-in glyphs may be used. See `SymbolMap::set_allow_builtin_glyphs()`.
+    pub fn allow_builtin_glyphs(mut self, allow: bool) -> Self {
+        self.steps.push(SymbolMapChange::AllowBuiltinGlyphs(allow));
+        self
+    }
+
+    /// Creates the symbol map and applies every step to it.
+    pub fn build(self) -> Result<SymbolMap, ChafaError> {
+        let map = SymbolMap::new()?;
+        for step in self.steps.iter() {
+            map.apply_change(step)?;
+        }
+        Ok(map)
+    }
+}
+
+impl SymbolMap {
+    /// Returns a builder for configuring a new symbol map in one expression.
+    pub fn builder() -> SymbolMapBuilder {
+        SymbolMapBuilder::new()
+    }
+}
+
+impl From<SelectorError> for ChafaError {
+    fn from(e: SelectorError) -> Self {
+        ChafaError::InvalidInput(format!("Failed to apply selectors: {}", e.message))
+    }
+}
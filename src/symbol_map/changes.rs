@@ -0,0 +1,132 @@
+use crate::{ChafaError, Glyph, PixelType, SymbolMap, SymbolTags};
+
+/// One change made to a symbol map. ChafaSymbolMap can't be inspected, so SymbolMap keeps a record of these in the order they were applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SymbolMapChange {
+    /// Symbols matching the tags were added.
+    AddTags(SymbolTags),
+    /// Symbols matching the tags were removed.
+    RemoveTags(SymbolTags),
+    /// Symbols in the inclusive range were added.
+    AddRange(char, char),
+    /// Symbols in the inclusive range were removed.
+    RemoveRange(char, char),
+    /// A selector string was applied. Unless it begins with + or -, it replaced everything before it.
+    Selectors(String),
+    /// A glyph was assigned to a code point.
+    Glyph(u32, PixelType, Glyph),
+    /// Use of the built-in glyphs was allowed or disallowed.
+    AllowBuiltinGlyphs(bool),
+}
+
+impl std::fmt::Display for SymbolMapChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymbolMapChange::AddTags(tags) => write!(f, "add tags {}", tags),
+            SymbolMapChange::RemoveTags(tags) => write!(f, "remove tags {}", tags),
+            SymbolMapChange::AddRange(first, last) => write!(
+                f,
+                "add range U+{:04X}..U+{:04X}",
+                u32::from(*first),
+                u32::from(*last)
+            ),
+            SymbolMapChange::RemoveRange(first, last) => write!(
+                f,
+                "remove range U+{:04X}..U+{:04X}",
+                u32::from(*first),
+                u32::from(*last)
+            ),
+            SymbolMapChange::Selectors(selectors) => write!(f, "apply selectors \"{}\"", selectors),
+            SymbolMapChange::Glyph(code_point, _, glyph) => write!(
+                f,
+                "add glyph U+{:04X} ({}x{})",
+                code_point,
+                glyph.width(),
+                glyph.height()
+            ),
+            SymbolMapChange::AllowBuiltinGlyphs(allow) => write!(
+                f,
+                "{} built-in glyphs",
+                if *allow { "allow" } else { "disallow" }
+            ),
+        }
+    }
+}
+
+impl SymbolMapChange {
+    /// Whether the change affects which symbols are selected, as opposed to the glyphs available to draw them.
+    pub(crate) fn is_selection(&self) -> bool {
+        !matches!(
+            self,
+            SymbolMapChange::Glyph(..) | SymbolMapChange::AllowBuiltinGlyphs(_)
+        )
+    }
+}
+
+impl SymbolMap {
+    /// Returns the changes made to symbol_map so far, oldest first.
+    ///
+    /// Changes that no longer have an effect are folded away as they're made: a selector string that clears the map drops the selection changes before it, a new glyph for a code point or built-in glyph setting replaces the earlier one in place, overlapping or adjacent ranges added or removed in a row are merged, and a change repeated right after itself is kept once. Replaying the result with `apply_change()` gives the same map.
+    pub fn changes(&self) -> Vec<SymbolMapChange> {
+        self.changes.borrow().clone()
+    }
+
+    /// Returns the code points glyphs have been assigned to with `add_glyph()`, in the order they were added. A code point is listed once even if its glyph was replaced.
+    pub fn imported_code_points(&self) -> Vec<u32> {
+        let mut code_points: Vec<u32> = Vec::new();
+        for change in self.changes.borrow().iter() {
+            if let SymbolMapChange::Glyph(code_point, _, _) = change
+                && !code_points.contains(code_point)
+            {
+                code_points.push(*code_point);
+            }
+        }
+        code_points
+    }
+
+    /// Describes the effective configuration of symbol_map, one change per line, for logging and debugging.
+    pub fn describe(&self) -> String {
+        let changes = self.changes.borrow();
+        if changes.is_empty() {
+            return String::from("empty");
+        }
+        changes
+            .iter()
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Applies a recorded change to symbol_map, as if the corresponding method had been called.
+    pub fn apply_change(&self, change: &SymbolMapChange) -> Result<(), ChafaError> {
+        match change {
+            SymbolMapChange::AddTags(tags) => self.add_by_tags(*tags),
+            SymbolMapChange::RemoveTags(tags) => self.remove_by_tags(*tags),
+            SymbolMapChange::AddRange(first, last) => {
+                check_range(*first, *last)?;
+                self.add_by_range(*first, *last);
+            }
+            SymbolMapChange::RemoveRange(first, last) => {
+                check_range(*first, *last)?;
+                self.remove_by_range(*first, *last);
+            }
+            SymbolMapChange::Selectors(selectors) => self.apply_selectors(selectors)?,
+            SymbolMapChange::Glyph(code_point, pixel_format, glyph) => {
+                self.add_glyph(*code_point, *pixel_format, glyph)?
+            }
+            SymbolMapChange::AllowBuiltinGlyphs(allow) => self.set_allow_builtin_glyphs(*allow),
+        }
+        Ok(())
+    }
+}
+
+fn check_range(first: char, last: char) -> Result<(), ChafaError> {
+    if first > last {
+        return Err(ChafaError::InvalidInput(format!(
+            "Symbol range U+{:04X}..U+{:04X} is reversed",
+            u32::from(first),
+            u32::from(last)
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+/*
+ * A compact fallback glyph set for block elements and sextants, drawn at the internal symbol size.
+ */
+
+use crate::{
+    ChafaError, Glyph, PixelType, SYMBOL_HEIGHT_PIXELS, SYMBOL_WIDTH_PIXELS, SymbolMap, SymbolTags,
+};
+
+const W: i32 = SYMBOL_WIDTH_PIXELS as i32;
+const H: i32 = SYMBOL_HEIGHT_PIXELS as i32;
+
+/// Returns every character the fallback set has a glyph for.
+pub fn chars() -> impl Iterator<Item = char> {
+    ('\u{2580}'..='\u{259f}')
+        .chain('\u{1fb00}'..='\u{1fb3b}')
+        .filter(|c| is_inked(u32::from(*c), 0, 0).is_some())
+}
+
+/// Returns the fallback glyph for a character as RGBA8Unassociated, white where it is inked and transparent elsewhere.
+pub fn glyph(c: char) -> Option<Glyph> {
+    let cp = u32::from(c);
+    is_inked(cp, 0, 0)?;
+
+    let mut pixels = Vec::with_capacity((W * H * 4) as usize);
+    for y in 0..H {
+        for x in 0..W {
+            let alpha = if is_inked(cp, x, y)? { 0xff } else { 0x00 };
+            pixels.extend_from_slice(&[0xff, 0xff, 0xff, alpha]);
+        }
+    }
+    Glyph::from_vec(pixels, PixelType::RGBA8Unassociated, W, H, W * 4).ok()
+}
+
+/// Tells whether pixel (x, y) of the glyph for cp is inked, or None if the fallback set doesn't cover cp.
+fn is_inked(cp: u32, x: i32, y: i32) -> Option<bool> {
+    let inked = match cp {
+        // Upper half, lower eighths and full block.
+        0x2580 => y < H / 2,
+        0x2581..=0x2588 => y >= H - H * (cp - 0x2580) as i32 / 8,
+        // Left eighths, from seven eighths down to one.
+        0x2589..=0x258f => x < W * (0x2590 - cp) as i32 / 8,
+        0x2590 => x >= W / 2,
+        0x2594 => y < H / 8,
+        0x2595 => x >= W - W / 8,
+        // Quadrants; bits are upper left, upper right, lower left, lower right.
+        0x2596..=0x259f => {
+            let bits = [
+                0b0100, 0b1000, 0b0001, 0b1101, 0b1001, 0b0111, 0b1011, 0b0010, 0b0110, 0b1110,
+            ][(cp - 0x2596) as usize];
+            let quadrant = (y >= H / 2) as u32 * 2 + (x >= W / 2) as u32;
+            bits & (1 << quadrant) != 0
+        }
+        // Sextants enumerate the 2x3 mosaics in order, leaving out the ones that already exist as half blocks.
+        0x1fb00..=0x1fb3b => {
+            let mut pattern = cp - 0x1fb00 + 1;
+            if pattern >= 21 {
+                pattern += 1;
+            }
+            if pattern >= 42 {
+                pattern += 1;
+            }
+            let cell = (y * 3 / H) as u32 * 2 + (x >= W / 2) as u32;
+            pattern & (1 << cell) != 0
+        }
+        _ => return None,
+    };
+    Some(inked)
+}
+
+/// Returns the tags chafa gives a character in the fallback set.
+fn tags_of(c: char) -> SymbolTags {
+    match u32::from(c) {
+        0x2580 | 0x2584 => SymbolTags::Block | SymbolTags::Hhalf | SymbolTags::Quad,
+        0x258c | 0x2590 => SymbolTags::Block | SymbolTags::Vhalf | SymbolTags::Quad,
+        0x2596..=0x259f => SymbolTags::Block | SymbolTags::Quad,
+        0x1fb00..=0x1fb3b => SymbolTags::Legacy | SymbolTags::Sextant,
+        _ => SymbolTags::Block,
+    }
+}
+
+impl SymbolMap {
+    /// Imports fallback glyphs for the characters matching any of tags that symbol_map has no glyph for, or whose glyph covers other pixels than the fallback, so block elements and sextants are matched against their true shapes even when chafa's built-in glyphs lack them or are disallowed.
+    ///
+    /// Nothing is imported unless this is called, e.g. right after `add_by_tags()` with the same tags.
+    ///
+    /// The fallback set covers block elements (U+2580 to U+259F, except the shades) and sextants (U+1FB00 to U+1FB3B).
+    /// # Returns:
+    /// The number of glyphs imported.
+    pub fn import_fallback_glyphs(&self, tags: SymbolTags) -> Result<usize, ChafaError> {
+        let mut added = 0;
+        for c in chars().filter(|c| tags_of(*c).intersects(tags)) {
+            let Some(glyph) = glyph(c) else {
+                continue;
+            };
+            let covered = self
+                .get_glyph(u32::from(c), PixelType::RGBA8Unassociated)
+                .is_some_and(|existing| {
+                    inked(&existing.coverage(PixelType::RGBA8Unassociated))
+                        == inked(&glyph.coverage(PixelType::RGBA8Unassociated))
+                });
+            if !covered {
+                self.add_glyph(u32::from(c), PixelType::RGBA8Unassociated, &glyph)?;
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+}
+
+/// Thresholds coverage values, so glyphs drawn with and without antialiasing compare equal.
+fn inked(coverage: &[u8]) -> Vec<bool> {
+    coverage.iter().map(|v| *v >= 0x80).collect()
+}
@@ -0,0 +1,127 @@
+/*
+ * Rasterizes TrueType/OpenType fonts with ab_glyph so a SymbolMap can use the glyphs the terminal actually draws.
+ */
+
+use crate::{ChafaError, Glyph, PixelType, SymbolMap};
+use ab_glyph::{Font as _, FontVec, PxScale, ScaleFont, point};
+
+/// A TrueType or OpenType font whose glyphs can be rasterized at a terminal's cell size.
+///
+/// Chafa picks symbols by comparing image cells against the shape of each glyph. Its built-in glyphs are generic approximations; importing the glyphs from the user's terminal font makes symbol selection match what actually ends up on screen.
+pub struct Font {
+    font: FontVec,
+}
+
+impl Font {
+    /// Loads a font from its file contents.
+    pub fn from_vec(data: Vec<u8>) -> Result<Self, ChafaError> {
+        let font = FontVec::try_from_vec(data)
+            .map_err(|e| ChafaError::InvalidInput(format!("Failed to parse font: {}", e)))?;
+        Ok(Self { font })
+    }
+
+    /// Loads a font from a .ttf or .otf file.
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ChafaError> {
+        let data = std::fs::read(path.as_ref()).map_err(|e| {
+            ChafaError::InvalidInput(format!(
+                "Failed to read font {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+        Self::from_vec(data)
+    }
+
+    /// Returns an iterator over every character the font has a glyph for.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.font.codepoint_ids().map(|(_, c)| c)
+    }
+
+    /// Rasterizes a character into a glyph of one cell. The font is scaled so that its ascent-to-descent height fills the cell, with the baseline at the ascent, the way terminals lay out text.
+    /// # Parameters:
+    /// --- `c`: The character to rasterize;
+    /// --- `cell_width`: Width of a terminal cell, in pixels;
+    /// --- `cell_height`: Height of a terminal cell, in pixels;
+    /// # Returns:
+    /// An RGBA8Unassociated glyph whose alpha channel holds the coverage, or None if the font has no glyph for c.
+    pub fn rasterize(&self, c: char, cell_width: i32, cell_height: i32) -> Option<Glyph> {
+        if cell_width <= 0 || cell_height <= 0 {
+            return None;
+        }
+        let id = self.font.glyph_id(c);
+        if id.0 == 0 {
+            return None;
+        }
+
+        let scaled = self.font.as_scaled(PxScale::from(cell_height as f32));
+        let glyph = id.with_scale_and_position(scaled.scale(), point(0.0, scaled.ascent()));
+
+        let rowstride = cell_width * 4;
+        // White everywhere, with the coverage going into the alpha channel. Whitespace has no outline and stays empty.
+        let mut pixels = [0xff, 0xff, 0xff, 0x00].repeat((cell_width * cell_height) as usize);
+        if let Some(outlined) = self.font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|x, y, coverage| {
+                let px = bounds.min.x as i32 + x as i32;
+                let py = bounds.min.y as i32 + y as i32;
+                if px < 0 || py < 0 || px >= cell_width || py >= cell_height {
+                    return;
+                }
+                let i = (py * rowstride + px * 4) as usize;
+                pixels[i + 3] = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+            });
+        }
+
+        Glyph::from_vec(
+            pixels,
+            PixelType::RGBA8Unassociated,
+            cell_width,
+            cell_height,
+            rowstride,
+        )
+        .ok()
+    }
+}
+
+impl SymbolMap {
+    /// Rasterizes characters from font at the given cell size and assigns the resulting glyphs to symbol_map with `add_glyph()`. Characters the font lacks are skipped.
+    ///
+    /// The cell size should match the terminal's, e.g. from `Capabilities::cell_size_px()`.
+    /// # Parameters:
+    /// --- `font`: The font to take glyphs from;
+    /// --- `chars`: The characters to import, e.g. `font.chars()` or the symbols the map uses;
+    /// --- `cell_width`: Width of a terminal cell, in pixels;
+    /// --- `cell_height`: Height of a terminal cell, in pixels;
+    /// # Returns:
+    /// The number of glyphs added.
+    pub fn add_font_glyphs<I: IntoIterator<Item = char>>(
+        &self,
+        font: &Font,
+        chars: I,
+        cell_width: i32,
+        cell_height: i32,
+    ) -> Result<usize, ChafaError> {
+        if cell_width <= 0 || cell_height <= 0 {
+            return Err(ChafaError::InvalidInput(format!(
+                "Cell size must be positive, got {}x{}",
+                cell_width, cell_height
+            )));
+        }
+
+        let mut added = 0;
+        for c in chars {
+            if let Some(glyph) = font.rasterize(c, cell_width, cell_height) {
+                self.add_glyph(u32::from(c), PixelType::RGBA8Unassociated, &glyph)?;
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Removes every code point font has no glyph for from symbol_map, so output doesn't end up with placeholder boxes on systems where that font is the only one available.
+    ///
+    /// Code points from U+0000 through U+1FFFF are considered, as in `retain_chars()`.
+    pub fn retain_covered_by(&self, font: &Font) {
+        self.retain_chars(font.chars());
+    }
+}
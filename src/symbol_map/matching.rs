@@ -0,0 +1,80 @@
+use crate::canvas::{Canvas, CanvasMode, Config, PixelMode};
+use crate::{ChafaError, PixelType, SYMBOL_HEIGHT_PIXELS, SYMBOL_WIDTH_PIXELS, SymbolMap};
+
+/// Number of pixels in a symbol's coverage bitmap.
+pub const SYMBOL_PIXELS: usize = (SYMBOL_WIDTH_PIXELS * SYMBOL_HEIGHT_PIXELS) as usize;
+
+/// A symbol chafa picked for a bitmap, along with how closely its glyph resembles it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SymbolMatch {
+    /// The matching symbol.
+    pub symbol: char,
+    /// Similarity between the symbol's glyph and the bitmap, from 0.0 (inverse) to 1.0 (identical).
+    pub score: f32,
+}
+
+impl SymbolMap {
+    /// Finds the symbols in symbol_map that chafa considers closest to a coverage bitmap, best first.
+    ///
+    /// Each match is found by letting chafa pick a symbol for the bitmap, then excluding that symbol and asking again, so the order is exactly the one chafa's own selection follows. The scores compare glyph and bitmap pixel by pixel.
+    /// # Parameters:
+    /// --- `coverage`: SYMBOL_WIDTH_PIXELS x SYMBOL_HEIGHT_PIXELS coverage values, row by row, where 0 is background and 255 is foreground;
+    /// --- `n`: The maximum number of matches to return;
+    pub fn best_matches(
+        &self,
+        coverage: &[u8; SYMBOL_PIXELS],
+        n: usize,
+    ) -> Result<Vec<SymbolMatch>, ChafaError> {
+        // Work on a copy so symbols can be excluded one at a time.
+        let scratch = SymbolMap::new()?;
+        for change in self.changes.borrow().iter() {
+            scratch.apply_change(change)?;
+        }
+
+        let pixels: Vec<u8> = coverage.iter().flat_map(|v| [*v, *v, *v, 0xff]).collect();
+        let config = Config::new()?;
+        config.set_geometry(1, 1)?;
+        config.set_pixel_mode(PixelMode::Symbols);
+        config.set_canvas_mode(CanvasMode::FgBg);
+        config.set_preprocessing_enabled(false);
+
+        let mut matches: Vec<SymbolMatch> = Vec::new();
+        while matches.len() < n {
+            // A canvas copies the symbol map when it's made, so each pick needs a new one.
+            config.set_symbol_map(&scratch);
+            let canvas = Canvas::new(&config)?;
+            canvas.set_pixels(
+                &pixels,
+                PixelType::RGBA8Unassociated,
+                SYMBOL_WIDTH_PIXELS as i32,
+                SYMBOL_HEIGHT_PIXELS as i32,
+                SYMBOL_WIDTH_PIXELS as i32 * 4,
+            )?;
+
+            // Chafa falls back to a space once the map runs out of symbols, and only symbols in the map have a glyph there.
+            let symbol = canvas.get_char_at(0, 0);
+            let Some(glyph) = scratch.get_glyph(u32::from(symbol), PixelType::RGBA8Unassociated)
+            else {
+                break;
+            };
+            if matches.iter().any(|m| m.symbol == symbol) {
+                break;
+            }
+
+            let glyph_coverage = glyph.coverage(PixelType::RGBA8Unassociated);
+            let score = if glyph_coverage.len() == SYMBOL_PIXELS {
+                let distance: u32 = glyph_coverage
+                    .iter()
+                    .zip(coverage.iter())
+                    .map(|(a, b)| a.abs_diff(*b) as u32)
+                    .sum();
+                1.0 - distance as f32 / (SYMBOL_PIXELS as f32 * 255.0)
+            } else {
+                0.0
+            };
+            matches.push(SymbolMatch { symbol, score });
+            scratch.remove_by_range(symbol, symbol);
+        }
+        Ok(matches)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{ChafaError, SymbolMap, SymbolMapBuilder, SymbolTags};
+
+/// Ready-made symbol selections, matching common choices for the chafa CLI's `--symbols` option.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum MapPreset {
+    /// Block elements and space; works with nearly every font. Like `--symbols block+space-wide`.
+    Blocks,
+    /// Braille patterns, which give the most detail per cell for line art. Like `--symbols braille+space`.
+    Braille,
+    /// Printable ASCII only, for output that must stay 7-bit. Like `--symbols ascii`.
+    AsciiOnly,
+    /// Legacy computing symbols (sextants, wedges and more) along with blocks. Needs a font with Unicode 13 coverage. Like `--symbols legacy+block+space-wide`.
+    Legacy,
+    /// Sextant 2x3 mosaics along with blocks. Needs a font with Unicode 13 coverage. Like `--symbols sextant+block+space-wide`.
+    Sextants,
+}
+
+impl MapPreset {
+    /// Returns the tags that make up the preset.
+    pub fn tags(&self) -> SymbolTags {
+        match self {
+            MapPreset::Blocks => SymbolTags::Block | SymbolTags::Space,
+            MapPreset::Braille => SymbolTags::Braille | SymbolTags::Space,
+            MapPreset::AsciiOnly => SymbolTags::ASCII,
+            MapPreset::Legacy => SymbolTags::Legacy | SymbolTags::Block | SymbolTags::Space,
+            MapPreset::Sextants => SymbolTags::Sextant | SymbolTags::Block | SymbolTags::Space,
+        }
+    }
+
+    /// Returns a builder set up with the preset, so it can be adjusted further before building.
+    pub fn builder(&self) -> SymbolMapBuilder {
+        let builder = SymbolMapBuilder::new().tags(self.tags());
+        match self {
+            MapPreset::Braille | MapPreset::AsciiOnly => builder,
+            _ => builder.remove(SymbolTags::Wide),
+        }
+    }
+}
+
+impl SymbolMap {
+    /// Creates a new symbol map holding one of the preset selections.
+    pub fn preset(preset: MapPreset) -> Result<Self, ChafaError> {
+        preset.builder().build()
+    }
+}
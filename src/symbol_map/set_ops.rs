@@ -0,0 +1,219 @@
+use crate::{ChafaError, SymbolMap, SymbolMapChange};
+
+/// The highest Unicode code point.
+const MAX_CODE_POINT: u32 = 0x10ffff;
+
+/// Splits a selector string into its terms, each with whether it removes symbols. `+` and `-` set the operation for the terms after them and `,` continues it; the `+` of a `U+` code point stays part of its term.
+fn selector_terms(selectors: &str) -> Vec<(bool, &str)> {
+    let mut terms = Vec::new();
+    let mut remove = false;
+    let mut start = 0;
+    let bytes = selectors.as_bytes();
+
+    for (i, b) in bytes.iter().enumerate() {
+        let in_code_point =
+            *b == b'+' && i > start && bytes[i - 1].eq_ignore_ascii_case(&b'u') && {
+                let before = selectors[start..i - 1].trim_end();
+                before.is_empty() || before.ends_with("..")
+            };
+        if !matches!(b, b',' | b'+' | b'-') || in_code_point {
+            continue;
+        }
+        let term = selectors[start..i].trim();
+        if !term.is_empty() {
+            terms.push((remove, term));
+        }
+        match b {
+            b'-' => remove = true,
+            b'+' => remove = false,
+            _ => {}
+        }
+        start = i + 1;
+    }
+    let term = selectors[start..].trim();
+    if !term.is_empty() {
+        terms.push((remove, term));
+    }
+    terms
+}
+
+/// Whether applying a selector string clears the map first, i.e. it doesn't begin with + or -.
+pub(super) fn is_clearing(selectors: &str) -> bool {
+    !selectors.trim_start().starts_with(['+', '-'])
+}
+
+impl SymbolMap {
+    /// Creates a new symbol map holding the symbols of both symbol_map and other.
+    ///
+    /// Chafa can't list the symbols in a map, so the result is built by replaying the recorded changes (see `changes()`): those of symbol_map, followed by the additions of other. This is exact as long as one of the two maps only ever added symbols since it was last cleared; otherwise an error is returned. Imported glyphs of both maps are kept.
+    pub fn union(&self, other: &SymbolMap) -> Result<SymbolMap, ChafaError> {
+        let (base, extra) = match (self.additions(), other.additions()) {
+            (_, Some(additions)) => (self, additions),
+            (Some(additions), None) => (other, additions),
+            (None, None) => {
+                return Err(ChafaError::InvalidInput(String::from(
+                    "Can't unite symbol maps that both remove symbols",
+                )));
+            }
+        };
+
+        let map = self.new_with_glyphs(other)?;
+        for change in base.selection_changes().iter().chain(extra.iter()) {
+            map.apply_change(change)?;
+        }
+        Ok(map)
+    }
+
+    /// Creates a new symbol map holding the symbols of symbol_map that other doesn't hold.
+    ///
+    /// The result is built by replaying the changes of symbol_map and then removing what other added, which requires other to only ever have added symbols since it was last cleared. Imported glyphs of symbol_map are kept.
+    pub fn difference(&self, other: &SymbolMap) -> Result<SymbolMap, ChafaError> {
+        let removals: Vec<SymbolMapChange> = other
+            .additions()
+            .ok_or_else(|| {
+                ChafaError::InvalidInput(String::from(
+                    "Can't subtract a symbol map that removes symbols",
+                ))
+            })?
+            .into_iter()
+            .map(|change| match change {
+                SymbolMapChange::AddTags(tags) => SymbolMapChange::RemoveTags(tags),
+                SymbolMapChange::AddRange(first, last) => SymbolMapChange::RemoveRange(first, last),
+                SymbolMapChange::Selectors(selectors) => SymbolMapChange::Selectors(
+                    selector_terms(&selectors)
+                        .into_iter()
+                        .map(|(_, term)| format!("-{}", term))
+                        .collect(),
+                ),
+                change => change,
+            })
+            .collect();
+
+        let map = self.new_with_glyphs(self)?;
+        for change in self.selection_changes().iter().chain(removals.iter()) {
+            map.apply_change(change)?;
+        }
+        Ok(map)
+    }
+
+    /// Creates a new symbol map holding the symbols that are in both symbol_map and other.
+    ///
+    /// Symbols can't be intersected by tag, so one of the two maps must have been built from code point ranges alone (e.g. with `add_by_range()`); the other map is replayed and everything outside those ranges is removed. Otherwise an error is returned. Imported glyphs of both maps are kept.
+    pub fn intersection(&self, other: &SymbolMap) -> Result<SymbolMap, ChafaError> {
+        let (base, ranges) = match (self.range_additions(), other.range_additions()) {
+            (_, Some(ranges)) => (self, ranges),
+            (Some(ranges), None) => (other, ranges),
+            (None, None) => {
+                return Err(ChafaError::InvalidInput(String::from(
+                    "Can't intersect symbol maps unless one is made of code point ranges only",
+                )));
+            }
+        };
+
+        let map = self.new_with_glyphs(other)?;
+        for change in base.selection_changes().iter() {
+            map.apply_change(change)?;
+        }
+        map.remove_outside(ranges, MAX_CODE_POINT);
+        Ok(map)
+    }
+
+    /// Removes every code point from U+0000 through U+1FFFF that isn't one of chars, e.g. the characters a font has glyphs for. That range covers every symbol chafa knows about.
+    pub fn retain_chars(&self, chars: impl IntoIterator<Item = char>) {
+        let ranges = chars
+            .into_iter()
+            .map(|c| (u32::from(c), u32::from(c)))
+            .collect();
+        self.remove_outside(ranges, 0x1ffff);
+    }
+
+    /// Removes every code point from 0 through last that isn't in one of the inclusive ranges.
+    pub(crate) fn remove_outside(&self, mut ranges: Vec<(u32, u32)>, last: u32) {
+        ranges.retain(|(first, end)| first <= end);
+        ranges.sort_unstable();
+
+        let mut next = 0u32;
+        for (first, end) in ranges
+            .into_iter()
+            .chain(std::iter::once((last + 1, last + 1)))
+        {
+            if first > next {
+                // Surrogates aren't chars, so gaps starting or ending in them are trimmed to the nearest valid code point.
+                let gap_first = char::from_u32(next).or(char::from_u32(0xe000));
+                let gap_last = char::from_u32(first - 1).or(char::from_u32(0xd7ff));
+                if let (Some(gap_first), Some(gap_last)) = (gap_first, gap_last)
+                    && gap_first <= gap_last
+                {
+                    self.remove_by_range(gap_first, gap_last);
+                }
+            }
+            next = next.max(end.saturating_add(1));
+        }
+    }
+
+    /// Returns the changes that determine which symbols are selected: everything since the map was last cleared, without glyph imports.
+    fn selection_changes(&self) -> Vec<SymbolMapChange> {
+        let changes = self.changes.borrow();
+        let start = changes
+            .iter()
+            .rposition(|change| matches!(change, SymbolMapChange::Selectors(s) if is_clearing(s)))
+            .unwrap_or(0);
+        changes[start..]
+            .iter()
+            .filter(|change| change.is_selection())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the selection changes if they only add symbols, with selector strings made relative so they can be applied to a map that isn't empty.
+    fn additions(&self) -> Option<Vec<SymbolMapChange>> {
+        self.selection_changes()
+            .into_iter()
+            .map(|change| match change {
+                SymbolMapChange::AddTags(_) | SymbolMapChange::AddRange(..) => Some(change),
+                SymbolMapChange::Selectors(s)
+                    if selector_terms(&s).iter().all(|(remove, _)| !remove) =>
+                {
+                    let s = s.trim_start();
+                    Some(SymbolMapChange::Selectors(if is_clearing(s) {
+                        format!("+{}", s)
+                    } else {
+                        s.to_owned()
+                    }))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the code point ranges the map was built from, if it was built from ranges alone.
+    fn range_additions(&self) -> Option<Vec<(u32, u32)>> {
+        self.selection_changes()
+            .into_iter()
+            .map(|change| match change {
+                SymbolMapChange::AddRange(first, last) => Some((u32::from(first), u32::from(last))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Creates an empty map with the glyph imports and built-in glyph setting of symbol_map, followed by the glyph imports of other.
+    fn new_with_glyphs(&self, other: &SymbolMap) -> Result<SymbolMap, ChafaError> {
+        let map = SymbolMap::new()?;
+        let mut sources = vec![self];
+        if !std::ptr::eq(self, other) {
+            sources.push(other);
+        }
+
+        for (i, source) in sources.into_iter().enumerate() {
+            for change in source.changes.borrow().iter() {
+                match change {
+                    SymbolMapChange::Glyph(..) => map.apply_change(change)?,
+                    SymbolMapChange::AllowBuiltinGlyphs(_) if i == 0 => map.apply_change(change)?,
+                    _ => {}
+                }
+            }
+        }
+        Ok(map)
+    }
+}
@@ -0,0 +1,44 @@
+use crate::SymbolMap;
+use unicode_width::UnicodeWidthChar;
+
+/// Blocks of symbols where emoji with Emoji_Presentation live. Terminals with Unicode tables from before emoji were made wide still draw these one cell wide.
+const EMOJI_BLOCKS: [std::ops::RangeInclusive<u32>; 2] = [0x2300..=0x2bff, 0x1f000..=0x1faff];
+
+impl SymbolMap {
+    /// Removes code points whose rendered width varies between terminals, so output lines up everywhere. This complements CHAFA_SYMBOL_TAG_AMBIGUOUS with the Unicode tables this crate is built with.
+    ///
+    /// Code points from U+0000 through U+1FFFF are considered. Removed are: control and zero-width characters; emoji that Unicode made wide, since older terminals draw them narrow; and, if east_asian is set, characters of ambiguous East Asian width. The code points are removed with one selector string, which is recorded as a single change.
+    /// # Parameters:
+    /// --- `east_asian`: Whether output may be shown in a terminal that draws ambiguous-width characters two cells wide, as is common in CJK locales;
+    pub fn remove_width_unsafe(&self, east_asian: bool) {
+        let is_unsafe = |c: char| {
+            let cp = u32::from(c);
+            match c.width() {
+                None | Some(0) => true,
+                Some(2) => EMOJI_BLOCKS.iter().any(|block| block.contains(&cp)),
+                Some(width) => east_asian && c.width_cjk() != Some(width),
+            }
+        };
+
+        let mut runs: Vec<(char, char)> = Vec::new();
+        for c in (0..=0x1ffff).filter_map(char::from_u32) {
+            if !is_unsafe(c) {
+                continue;
+            }
+            match runs.last_mut() {
+                Some((_, last)) if u32::from(*last) + 1 == u32::from(c) => *last = c,
+                _ => runs.push((c, c)),
+            }
+        }
+
+        // There are hundreds of runs; removing them with a single selector string keeps them to one entry in changes().
+        let selectors: Vec<String> = runs
+            .iter()
+            .map(|(first, last)| format!("U+{:04X}..U+{:04X}", u32::from(*first), u32::from(*last)))
+            .collect();
+        if !selectors.is_empty() {
+            // The selectors are well-formed code point ranges, so applying them can't fail.
+            let _ = self.apply_selectors(&format!("-{}", selectors.join(",")));
+        }
+    }
+}
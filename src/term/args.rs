@@ -0,0 +1,66 @@
+use crate::ChafaError;
+use crate::term::{CHAFA_TERM_SEQ_ARGS_MAX, Info, Seq, SeqBuffer};
+
+/// Typed arguments for a family of sequences with the same argument shape, e.g. `CursorPos` for CHAFA_TERM_SEQ_CURSOR_TO_POS or `ColorDirect` for the direct color sequences.
+///
+/// The structs implementing this are generated from chafa's emitters, so their fields always have the right number, order and range.
+pub trait SeqArgs {
+    /// The sequences that take these arguments.
+    const SEQS: &'static [Seq];
+
+    /// Writes the arguments to `args` in the order chafa expects them and returns how many there are.
+    fn fill_args(&self, args: &mut [u32; CHAFA_TERM_SEQ_ARGS_MAX as usize]) -> usize;
+}
+
+impl Info {
+    /// Formats seq with typed arguments and appends the result to `out`.
+    ///
+    /// Fails if seq doesn't take arguments of this shape (see `SeqArgs::SEQS`), or if term_info can't emit it.
+    pub fn emit_args<A: SeqArgs, B: SeqBuffer>(
+        &self,
+        seq: Seq,
+        args: &A,
+        out: &mut B,
+    ) -> Result<(), ChafaError> {
+        if !A::SEQS.contains(&seq) {
+            return Err(ChafaError::InvalidInput(format!(
+                "{:?} doesn't take {}",
+                seq,
+                std::any::type_name::<A>()
+                    .rsplit("::")
+                    .next()
+                    .unwrap_or_default()
+            )));
+        }
+        let mut buf = [0u32; CHAFA_TERM_SEQ_ARGS_MAX as usize];
+        let n = args.fill_args(&mut buf);
+        let args = &buf[..n];
+
+        self.check_emit_args(seq, args)?;
+        self.emit_seq_dispatch(seq, args, out)
+    }
+}
+
+macro_rules! gen_seq_args {
+    ($($name:ident($($field:ident: $ty:ty),*) => [$($seq:ident),*],)*) => {
+        $(
+            #[doc = concat!("Arguments for ", $("`Seq::", stringify!($seq), "` "),*, ".")]
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+            pub struct $name {
+                $(pub $field: $ty,)*
+            }
+
+            impl SeqArgs for $name {
+                const SEQS: &'static [Seq] = &[$(Seq::$seq),*];
+
+                fn fill_args(&self, args: &mut [u32; CHAFA_TERM_SEQ_ARGS_MAX as usize]) -> usize {
+                    let values = [$(self.$field as u32),*];
+                    args[..values.len()].copy_from_slice(&values);
+                    values.len()
+                }
+            }
+        )*
+    };
+}
+
+include!("term_args.rs");
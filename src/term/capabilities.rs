@@ -0,0 +1,156 @@
+use crate::canvas::{CanvasMode, Passthrough, PixelMode};
+use crate::term::{Info, Quirks, Seq};
+
+/// A summary of what a terminal can do, derived from the sequences and quirks stored in a `term::Info`.
+///
+/// This is a snapshot: changes made to the Info afterwards are not reflected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    sixel: bool,
+    kitty: bool,
+    iterm2: bool,
+    synchronized_update: bool,
+    max_colors: u32,
+    canvas_mode: CanvasMode,
+    pixel_mode: PixelMode,
+    passthrough: Passthrough,
+    needs_passthrough: bool,
+    quirks: Quirks,
+    cell_size_px: Option<(u32, u32)>,
+    default_bg_color: Option<u32>,
+}
+
+impl Capabilities {
+    /// Checks if the terminal accepts sixel graphics.
+    pub fn supports_sixel(&self) -> bool {
+        self.sixel
+    }
+
+    /// Checks if the terminal accepts images through the kitty graphics protocol.
+    pub fn supports_kitty_graphics(&self) -> bool {
+        self.kitty
+    }
+
+    /// Checks if the terminal accepts iTerm2 inline images.
+    pub fn supports_iterm2_images(&self) -> bool {
+        self.iterm2
+    }
+
+    /// Checks if the terminal supports DEC synchronized updates (mode 2026). Known after probing with a `ProbeSession`, or if set on the term info with `Info::set_synchronized_update()`.
+    pub fn supports_synchronized_update(&self) -> bool {
+        self.synchronized_update
+    }
+
+    /// Checks if the terminal can display pixel graphics with any protocol.
+    pub fn supports_pixel_graphics(&self) -> bool {
+        self.sixel || self.kitty || self.iterm2
+    }
+
+    /// Gets the number of colors the terminal can display: 16777216 for truecolor, 256, 16 or 8 for indexed colors, and 2 when only the default colors are available.
+    pub fn max_colors(&self) -> u32 {
+        self.max_colors
+    }
+
+    /// Gets the best canvas mode for symbol output.
+    pub fn best_canvas_mode(&self) -> CanvasMode {
+        self.canvas_mode
+    }
+
+    /// Gets the best pixel mode, or `PixelMode::Symbols` if the terminal has no pixel graphics.
+    pub fn best_pixel_mode(&self) -> PixelMode {
+        self.pixel_mode
+    }
+
+    /// Gets the multiplexer passthrough the terminal is chained inside of, if any.
+    pub fn passthrough(&self) -> Passthrough {
+        self.passthrough
+    }
+
+    /// Checks if pixel data in the best pixel mode must be wrapped in passthrough guards to reach the terminal.
+    pub fn needs_passthrough(&self) -> bool {
+        self.needs_passthrough
+    }
+
+    /// Checks if sixel images may extend one row past their nominal height (CHAFA_TERM_QUIRK_SIXEL_OVERSHOOT).
+    pub fn sixel_overshoots(&self) -> bool {
+        self.quirks.contains(Quirks::SixelOvershoot)
+    }
+
+    /// Gets the raw quirks of the terminal.
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    /// Gets the width and height of a character cell in pixels, if the terminal reported it. Only known after probing with a `ProbeSession`.
+    pub fn cell_size_px(&self) -> Option<(u32, u32)> {
+        self.cell_size_px
+    }
+
+    /// Gets the font's width divided by its height, from the cell size the terminal reported. Only known after probing with a `ProbeSession`.
+    pub fn font_ratio(&self) -> Option<f32> {
+        self.cell_size_px
+            .filter(|&(width, height)| width > 0 && height > 0)
+            .map(|(width, height)| width as f32 / height as f32)
+    }
+
+    /// Gets the terminal's default background color as packed 8bpc RGB, i.e. 0x00RRGGBB hex, if the terminal reported it. Only known after probing with a `ProbeSession`.
+    pub fn default_bg_color(&self) -> Option<u32> {
+        self.default_bg_color
+    }
+
+    /// Merges what the terminal reported about itself into the capabilities derived from its term info.
+    pub(crate) fn apply_replies(
+        &mut self,
+        sixel: bool,
+        kitty: bool,
+        synchronized_update: bool,
+        cell_size_px: Option<(u32, u32)>,
+        default_bg_color: Option<u32>,
+    ) {
+        self.sixel |= sixel;
+        self.kitty |= kitty;
+        self.synchronized_update |= synchronized_update;
+        if self.pixel_mode == PixelMode::Symbols {
+            if self.kitty {
+                self.pixel_mode = PixelMode::Kitty;
+            } else if self.sixel {
+                self.pixel_mode = PixelMode::Sixels;
+            }
+        }
+        self.cell_size_px = cell_size_px.or(self.cell_size_px);
+        self.default_bg_color = default_bg_color.or(self.default_bg_color);
+    }
+}
+
+impl From<&Info> for Capabilities {
+    fn from(info: &Info) -> Self {
+        let max_colors = if info.have_seq(Seq::SetColorFgDirect) {
+            1 << 24
+        } else if info.have_seq(Seq::SetColorFg256) {
+            256
+        } else if info.have_seq(Seq::SetColorFg16) {
+            16
+        } else if info.have_seq(Seq::SetColorFg8) {
+            8
+        } else {
+            2
+        };
+        let pixel_mode = info.get_best_pixel_mode();
+
+        Self {
+            sixel: info.have_seq(Seq::BeginSixels),
+            kitty: info.have_seq(Seq::BeginKittyImmediateImageV1),
+            iterm2: info.have_seq(Seq::BeginIterm2Image),
+            synchronized_update: info.supports_synchronized_update(),
+            max_colors,
+            canvas_mode: info.get_best_canvas_mode(),
+            pixel_mode,
+            passthrough: info.get_passthrough_type(),
+            needs_passthrough: pixel_mode != PixelMode::Symbols
+                && info.get_is_pixel_passthrough_needed(pixel_mode),
+            quirks: info.get_quirks(),
+            cell_size_px: None,
+            default_bg_color: None,
+        }
+    }
+}
@@ -2,68 +2,287 @@
  * https://hpjansson.org/chafa/ref/chafa-ChafaTermDb.html
  */
 
-use crate::ffi;
-use crate::term::Info;
+use crate::term::{Env, Info, Seq};
+use crate::{ChafaError, ffi};
 
 /// A ChafaTermDb contains information on terminals, and can be used to obtain a suitable ChafaTermInfo for a terminal environment.
 pub struct Db {
     pub raw: *mut ffi::ChafaTermDb,
+    custom: Vec<(String, Info)>,
 }
 
 impl Db {
     /// Creates a new, blank ChafaTermDb.
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, ChafaError> {
         let raw = unsafe { ffi::chafa_term_db_new() };
         if raw.is_null() {
-            Err("Chafa -> Failed to create Db")
+            Err(ChafaError::Failed("Chafa -> Failed to create Db"))
         } else {
-            Ok(Self { raw })
+            Ok(Self {
+                raw,
+                custom: Vec::new(),
+            })
         }
     }
 
     /// Gets the global ChafaTermDb. This can normally be used safely in a read-only capacity. The caller should not unref the returned object.
-    pub fn default() -> Result<Self, &'static str> {
+    pub fn default() -> Result<Self, ChafaError> {
         let raw = unsafe { ffi::chafa_term_db_get_default() };
         if raw.is_null() {
-            Err("Chafa -> Failed to retrieve default Db")
+            Err(ChafaError::Failed("Chafa -> Failed to retrieve default Db"))
         } else {
-            Ok(Self { raw })
+            // The default Db belongs to chafa; keep our own reference.
+            unsafe {
+                ffi::chafa_term_db_ref(raw);
+            }
+            Ok(Self {
+                raw,
+                custom: Vec::new(),
+            })
         }
     }
 
+    /// Registers a custom terminal definition under `name`, replacing any earlier definition with the same name. The name of `info` is set to `name`.
+    ///
+    /// Custom definitions take precedence over chafa's built-in database in `detect()`. They are matched against the TERM variable (exactly, or as a prefix followed by `-`) and the TERM_PROGRAM variable.
+    pub fn add_terminal(&mut self, name: &str, info: Info) -> Result<(), ChafaError> {
+        info.set_name(name)?;
+        self.custom.retain(|(n, _)| n != name);
+        self.custom.push((name.to_owned(), info));
+        Ok(())
+    }
+
+    /// Removes the custom terminal definition registered under `name`, returning it if it existed.
+    pub fn remove_terminal(&mut self, name: &str) -> Option<Info> {
+        let pos = self.custom.iter().position(|(n, _)| n == name)?;
+        Some(self.custom.remove(pos).1)
+    }
+
+    /// Returns a copy of the custom terminal definition registered under `name`.
+    pub fn lookup(&self, name: &str) -> Option<Info> {
+        let (_, info) = self.custom.iter().find(|(n, _)| n == name)?;
+        info.try_clone().ok()
+    }
+
+    /// Finds the custom terminal definition matching the TERM and TERM_PROGRAM variables, if any.
+    fn detect_custom(&self, env: &Env) -> Option<Info> {
+        let term = env.get("TERM").unwrap_or_default();
+        let term_program = env.get("TERM_PROGRAM").unwrap_or_default();
+
+        let name = self.custom.iter().map(|(n, _)| n).find(|n| {
+            term == **n
+                || term
+                    .strip_prefix(n.as_str())
+                    .is_some_and(|rest| rest.starts_with('-'))
+                || term_program.eq_ignore_ascii_case(n)
+        })?;
+        self.lookup(name)
+    }
+
     /// Builds a new ChafaTermInfo with capabilities implied by the provided environment variables (principally the TERM variable, but also others).
     ///
-    /// envp can be gotten from g_get_environ().
-    pub fn detect(&self) -> Result<Info, &'static str> {
-        unsafe {
-            let envp = ffi::g_get_environ();
-            if envp.is_null() {
-                return Err("Glib -> Failed to retrieve envp");
+    /// Terminals registered with `add_terminal()` are checked first. If the environment identifies iTerm2 (ITERM_SESSION_ID, LC_TERMINAL or TERM_PROGRAM), its inline image sequences are added when chafa's database lacks them.
+    ///
+    /// Windows Terminal and Windows consoles don't set TERM; they are detected as xterm-compatible, and on Windows VT processing is enabled on the console first.
+    ///
+    /// This reads the process environment; use `detect_env()` to detect from another one.
+    pub fn detect(&self) -> Result<Info, ChafaError> {
+        self.detect_env(&Env::capture())
+    }
+
+    /// Like `detect()`, but reads the variables from env instead of the process environment.
+    pub fn detect_env(&self, env: &Env) -> Result<Info, ChafaError> {
+        if let Some(info) = self.detect_custom(env) {
+            return Ok(info);
+        }
+        let mut env = env.clone();
+        for (name, value) in console_env(&env) {
+            env.set(name, value);
+        }
+        let info = self.detect_raw(&env)?;
+        add_iterm2_seqs(&info, &env);
+        Ok(info)
+    }
+
+    /// Runs chafa's detection on env with some variables replaced (`Some`) or removed (`None`). env itself is left alone.
+    pub(crate) fn detect_with_env(
+        &self,
+        env: &Env,
+        changes: &[(&str, Option<&str>)],
+    ) -> Result<Info, ChafaError> {
+        let mut env = env.clone();
+        for (name, value) in changes {
+            match value {
+                Some(value) => env.set(name, value),
+                None => {
+                    env.remove(name);
+                }
             }
-            let info = ffi::chafa_term_db_detect(self.raw, envp);
-            ffi::g_strfreev(envp);
+        }
+        self.detect_raw(&env)
+    }
 
-            if info.is_null() {
-                Err("Chafa -> Failed to detect term info")
-            } else {
-                Ok(Info { raw: info })
+    /// Runs chafa's own detection on env, without custom definitions or fixes.
+    fn detect_raw(&self, env: &Env) -> Result<Info, ChafaError> {
+        let envp = env.to_envp()?;
+        let info = unsafe { ffi::chafa_term_db_detect(self.raw, envp.0) };
+        if info.is_null() {
+            Err(ChafaError::Failed("Chafa -> Failed to detect term info"))
+        } else {
+            Ok(Info::from_raw(info))
+        }
+    }
+
+    /// Returns the names of the terminals known to the database, custom definitions first. See `infos()`.
+    pub fn terminal_names(&self) -> Vec<String> {
+        self.infos().into_iter().map(|(name, _)| name).collect()
+    }
+
+    /// Returns every terminal known to the database along with its ChafaTermInfo, custom definitions first.
+    ///
+    /// Chafa has no API to list its built-in entries, so they are found by running detection on a list of well-known TERM values, with other identifying variables cleared. Entries are named after their ChafaTermInfo. Detected entries are told apart by their sequences and quirks rather than their names, so each distinct entry is listed once.
+    pub fn infos(&self) -> Vec<(String, Info)> {
+        let mut infos: Vec<(String, Info)> = self
+            .custom
+            .iter()
+            .filter_map(|(name, info)| Some((name.clone(), info.try_clone().ok()?)))
+            .collect();
+
+        let process_env = Env::capture();
+        let mut env: Vec<(&str, Option<&str>)> =
+            IDENTIFYING_VARS.iter().map(|v| (*v, None)).collect();
+        env.push(("TERM", None));
+        let term_idx = env.len() - 1;
+        let mut seen = Vec::new();
+
+        for term in KNOWN_TERMS {
+            env[term_idx].1 = Some(term);
+            let Ok(info) = self.detect_with_env(&process_env, &env) else {
+                continue;
+            };
+            let definition = capability_definition(&info);
+            if !seen.contains(&definition) {
+                seen.push(definition);
+                infos.push((info.get_name().unwrap_or_else(|| term.to_string()), info));
             }
         }
+        infos
     }
 
     /// Builds a new ChafaTermInfo with fallback control sequences. This can be used with unknown but presumably modern terminals, or to supplement missing capabilities in a detected terminal.
     ///
     /// Fallback control sequences may cause unpredictable behavior and should only be used as a last resort.
-    pub fn get_fallback_info(&self) -> Result<Info, &'static str> {
+    pub fn get_fallback_info(&self) -> Result<Info, ChafaError> {
         let info = unsafe { ffi::chafa_term_db_get_fallback_info(self.raw) };
         if info.is_null() {
-            Err("Chafa -> Failed to retrieve fallback term info")
+            Err(ChafaError::Failed(
+                "Chafa -> Failed to retrieve fallback term info",
+            ))
         } else {
-            Ok(Info { raw: info })
+            Ok(Info::from_raw(info))
         }
     }
 }
 
+/// TERM values to run detection on when listing chafa's built-in database. They are only candidates for finding entries; what a terminal supports is read from the detected ChafaTermInfo.
+const KNOWN_TERMS: &[&str] = &[
+    "xterm-256color",
+    "xterm-kitty",
+    "xterm-ghostty",
+    "alacritty",
+    "contour",
+    "foot",
+    "wezterm",
+    "mintty",
+    "mlterm",
+    "konsole",
+    "st-256color",
+    "rxvt-unicode-256color",
+    "rxvt",
+    "eterm-color",
+    "terminology",
+    "linux",
+    "fbterm",
+    "vt220",
+    "screen-256color",
+    "tmux-256color",
+    "xterm",
+];
+
+/// Returns the definition string of info without its name, describing only what it can do.
+fn capability_definition(info: &Info) -> String {
+    info.to_definition_string()
+        .lines()
+        .filter(|line| !line.starts_with("name="))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Variables besides TERM that chafa uses to identify a terminal.
+const IDENTIFYING_VARS: &[&str] = &[
+    "COLORTERM",
+    "TERM_PROGRAM",
+    "VTE_VERSION",
+    "KONSOLE_VERSION",
+    "KITTY_WINDOW_ID",
+    "WT_SESSION",
+    "LC_TERMINAL",
+    "MLTERM",
+    "TMUX",
+    "STY",
+];
+
+/// Returns environment variables to add before detection when TERM is missing but the process is attached to Windows Terminal or a Windows console with VT processing.
+fn console_env(env: &Env) -> Vec<(&'static str, &'static str)> {
+    if env.contains("TERM") {
+        return Vec::new();
+    }
+
+    #[cfg(windows)]
+    let vt = crate::term::enable_vt_processing();
+    #[cfg(not(windows))]
+    let vt = false;
+
+    // VS Code's integrated terminal only goes without TERM on Windows, where it hosts ConPTY.
+    let windows_terminal =
+        env.contains("WT_SESSION") || (cfg!(windows) && env.get("TERM_PROGRAM") == Some("vscode"));
+    if !vt && !windows_terminal {
+        return Vec::new();
+    }
+
+    let mut vars = vec![("TERM", "xterm-256color")];
+    // Windows Terminal and the Windows 10 console both render 24-bit color.
+    if !env.contains("COLORTERM") {
+        vars.push(("COLORTERM", "truecolor"));
+    }
+    vars
+}
+
+/// Sequences used by iTerm2's inline image protocol.
+const ITERM2_BEGIN_IMAGE: &str =
+    "\x1b]1337;File=inline=1;width=%1;height=%2;preserveAspectRatio=0:";
+const ITERM2_END_IMAGE: &str = "\x07";
+
+/// Checks if the environment variables identify iTerm2. LC_TERMINAL is forwarded by iTerm2's ssh integration, so this also works on remote hosts.
+fn env_is_iterm2(env: &Env) -> bool {
+    env.contains("ITERM_SESSION_ID")
+        || env.get("LC_TERMINAL") == Some("iTerm2")
+        || env.get("TERM_PROGRAM") == Some("iTerm.app")
+}
+
+/// Gives info the iTerm2 inline image sequences if the environment points to iTerm2 and they're missing.
+fn add_iterm2_seqs(info: &Info, env: &Env) {
+    if info.have_seq(Seq::BeginIterm2Image) || !env_is_iterm2(env) {
+        return;
+    }
+    if info
+        .set_seq(Seq::BeginIterm2Image, Some(ITERM2_BEGIN_IMAGE))
+        .is_ok()
+    {
+        let _ = info.set_seq(Seq::EndIterm2Image, Some(ITERM2_END_IMAGE));
+    }
+}
+
 impl Drop for Db {
     fn drop(&mut self) {
         if !self.raw.is_null() {
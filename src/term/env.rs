@@ -0,0 +1,130 @@
+use crate::{ChafaError, ffi};
+use std::collections::BTreeMap;
+use std::ffi::{CString, NulError, OsStr, OsString};
+
+/// A set of environment variables to detect a terminal from, e.g. with `Db::detect_env()`.
+///
+/// Take a snapshot of the process environment with `capture()`, or build one by hand to see what detection makes of another terminal's environment, such as one forwarded over SSH or made up in a test.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Env {
+    vars: BTreeMap<OsString, OsString>,
+}
+
+impl Env {
+    /// Creates an empty environment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copies the process environment, including variables whose name or value isn't valid Unicode.
+    pub fn capture() -> Self {
+        std::env::vars_os().collect()
+    }
+
+    /// Gets the value of a variable, or `None` if it isn't set or isn't valid Unicode.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.get_os(name)?.to_str()
+    }
+
+    /// Gets the value of a variable as it was set, Unicode or not.
+    pub fn get_os(&self, name: impl AsRef<OsStr>) -> Option<&OsStr> {
+        self.vars.get(name.as_ref()).map(OsString::as_os_str)
+    }
+
+    /// Checks if a variable is set, even if empty.
+    pub fn contains(&self, name: impl AsRef<OsStr>) -> bool {
+        self.vars.contains_key(name.as_ref())
+    }
+
+    /// Sets a variable, replacing any earlier value.
+    pub fn set(&mut self, name: impl AsRef<OsStr>, value: impl AsRef<OsStr>) {
+        self.vars
+            .insert(name.as_ref().to_owned(), value.as_ref().to_owned());
+    }
+
+    /// Removes a variable, returning its value if it was set. Values that aren't valid Unicode are converted lossily.
+    pub fn remove(&mut self, name: impl AsRef<OsStr>) -> Option<String> {
+        self.vars
+            .remove(name.as_ref())
+            .map(|value| value.to_string_lossy().into_owned())
+    }
+
+    /// Sets a variable and returns the environment, for building one up in a chain.
+    pub fn var(mut self, name: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Returns the variables whose name and value are valid Unicode as (name, value) pairs, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.iter_os()
+            .filter_map(|(name, value)| Some((name.to_str()?, value.to_str()?)))
+    }
+
+    /// Returns all variables as (name, value) pairs, sorted by name.
+    pub fn iter_os(&self) -> impl Iterator<Item = (&OsStr, &OsStr)> {
+        self.vars
+            .iter()
+            .map(|(name, value)| (name.as_os_str(), value.as_os_str()))
+    }
+
+    /// Builds the NULL-terminated "NAME=value" array GLib functions take.
+    pub(crate) fn to_envp(&self) -> Result<Envp, ChafaError> {
+        let mut envp = Envp(std::ptr::null_mut());
+        for (name, value) in self.iter_os() {
+            let name = c_string(name)?;
+            let value = c_string(value)?;
+            envp.0 = unsafe { ffi::g_environ_setenv(envp.0, name.as_ptr(), value.as_ptr(), 1) };
+        }
+        Ok(envp)
+    }
+}
+
+/// Converts a variable to the bytes GLib expects: as they are on Unix, and as UTF-8 elsewhere, where GLib converts the environment to UTF-8 itself.
+fn c_string(s: &OsStr) -> Result<CString, NulError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        CString::new(s.as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        CString::new(s.to_string_lossy().as_bytes())
+    }
+}
+
+impl<K: Into<OsString>, V: Into<OsString>> FromIterator<(K, V)> for Env {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            vars: iter
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+impl From<std::collections::HashMap<String, String>> for Env {
+    fn from(vars: std::collections::HashMap<String, String>) -> Self {
+        vars.into_iter().collect()
+    }
+}
+
+impl From<BTreeMap<String, String>> for Env {
+    fn from(vars: BTreeMap<String, String>) -> Self {
+        vars.into_iter().collect()
+    }
+}
+
+/// An environment array allocated by GLib, freed with g_strfreev() on drop. A null pointer stands for an empty environment.
+pub(crate) struct Envp(pub(crate) *mut *mut ffi::gchar);
+
+impl Drop for Envp {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            unsafe {
+                ffi::g_strfreev(self.0);
+            }
+        }
+    }
+}
@@ -1,20 +1,43 @@
-use crate::{SymbolTags, ffi};
+use crate::canvas::{CanvasMode, Passthrough, PixelMode};
+use crate::{ChafaError, SymbolTags, ffi};
+use std::cell::Cell;
 
 /// A ChafaTermInfo describes the characteristics of one particular kind of display terminal. It stores control sequences that can be used to move the cursor, change text attributes, mark the beginning and end of sixel graphics data, etc.
 ///
 /// ChafaTermInfo also implements an efficient low-level API for formatting these sequences with marshaled arguments so they can be sent to the terminal.
 pub struct Info {
     pub raw: *mut ffi::ChafaTermInfo,
+    synchronized_update: Cell<bool>,
 }
 
 impl Info {
+    /// Wraps a ChafaTermInfo, taking over one reference to it.
+    pub(crate) fn from_raw(raw: *mut ffi::ChafaTermInfo) -> Self {
+        Self {
+            raw,
+            synchronized_update: Cell::new(false),
+        }
+    }
+
     /// Creates a new, blank ChafaTermInfo.
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, ChafaError> {
         let raw = unsafe { ffi::chafa_term_info_new() };
         if raw.is_null() {
-            Err("Chafa -> Failed to create term info")
+            Err(ChafaError::Failed("Chafa -> Failed to create term info"))
         } else {
-            Ok(Self { raw })
+            Ok(Self::from_raw(raw))
+        }
+    }
+
+    /// Creates a deep copy of term_info, so changes to the copy don't affect the original.
+    pub fn try_clone(&self) -> Result<Self, ChafaError> {
+        let raw = unsafe { ffi::chafa_term_info_copy(self.raw) };
+        if raw.is_null() {
+            Err(ChafaError::Failed("Chafa -> Failed to copy term info"))
+        } else {
+            let info = Self::from_raw(raw);
+            info.set_synchronized_update(self.supports_synchronized_update());
+            Ok(info)
         }
     }
 
@@ -36,18 +59,32 @@ impl Info {
     ///
     /// The merged ChafaTermInfo is a new instance, with the initial reference owned by the caller.
     ///
+    /// The merged info supports synchronized updates if inner does, since the inner terminal is the one that takes them.
+    ///
     /// This function can be used repeatedly to create chains that're arbitrarily long, but is unlikely to be useful beyond three levels (terminal emulator, multiplexer, application).
-    pub fn chain(outer: Self, inner: Self) -> Result<Self, &'static str> {
+    pub fn chain(outer: &Self, inner: &Self) -> Result<Self, ChafaError> {
         let raw = unsafe { ffi::chafa_term_info_chain(outer.raw, inner.raw) };
         if raw.is_null() {
-            Err("Chafa -> Failed to merge term info")
+            Err(ChafaError::Failed("Chafa -> Failed to merge term info"))
         } else {
-            Ok(Self { raw })
+            let info = Self::from_raw(raw);
+            info.set_synchronized_update(inner.supports_synchronized_update());
+            Ok(info)
         }
     }
 
+    /// Checks if the terminal is known to support DEC synchronized updates (mode 2026). Chafa doesn't track this, so it is `false` until set with `set_synchronized_update()`, e.g. from `Capabilities::supports_synchronized_update()` or `probe_synchronized_update()`.
+    pub fn supports_synchronized_update(&self) -> bool {
+        self.synchronized_update.get()
+    }
+
+    /// Sets whether the terminal supports DEC synchronized updates. Output is only wrapped in them when this is set; see `PrintOptions::synchronized_update`.
+    pub fn set_synchronized_update(&self, supported: bool) {
+        self.synchronized_update.set(supported);
+    }
+
     /// Supplements missing sequences in term_info with ones copied from source .
-    pub fn supplement(&self, source: Self) {
+    pub fn supplement(&self, source: &Self) {
         unsafe {
             ffi::chafa_term_info_supplement(self.raw, source.raw);
         }
@@ -67,15 +104,12 @@ impl Info {
     }
 
     /// Assigns a new name to term_info . The name should be a short lowercase ASCII string that uniquely identifies the terminal or program described by term_info.
-    pub fn set_name(&self, name: &str) {
+    pub fn set_name(&self, name: &str) -> Result<(), ChafaError> {
+        let c_name = std::ffi::CString::new(name)?;
         unsafe {
-            ffi::chafa_term_info_set_name(
-                self.raw,
-                std::ffi::CString::new(name)
-                    .expect("Chafa -> Failed to create CString")
-                    .as_ptr() as *const i8,
-            );
+            ffi::chafa_term_info_set_name(self.raw, c_name.as_ptr());
         }
+        Ok(())
     }
 
     /// Gets the quirks associated with term_info.
@@ -112,10 +146,10 @@ impl Info {
             if seq_str.is_null() {
                 None
             } else {
+                // The string belongs to term_info and must not be freed.
                 let s = std::ffi::CStr::from_ptr(seq_str as *const std::os::raw::c_char)
                     .to_string_lossy()
                     .into_owned();
-                ffi::g_free(seq_str as *mut _);
                 Some(s)
             }
         }
@@ -132,26 +166,26 @@ impl Info {
     /// Passing NULL for str clears the corresponding control sequence.
     /// # Parameters:
     /// --- `seq_str`: A control sequence string, or None to clear;
-    pub fn set_seq(&self, seq: Seq, seq_str: Option<&str>) -> Result<(), String> {
-        let mut seq_str_ptr: *mut std::os::raw::c_char = std::ptr::null_mut();
-        let c_str: std::ffi::CString;
-        if let Some(s) = seq_str {
-            c_str = std::ffi::CString::new(s).expect("Chafa -> Failed to create CString");
-            seq_str_ptr = c_str.as_ptr() as *mut _;
-        }
+    pub fn set_seq(&self, seq: Seq, seq_str: Option<&str>) -> Result<(), ChafaError> {
+        // Keep the CString alive until chafa has copied the sequence.
+        let c_str = seq_str.map(std::ffi::CString::new).transpose()?;
+        let seq_str_ptr = c_str.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
         let mut error: *mut ffi::GError = std::ptr::null_mut();
 
         unsafe {
-            ffi::chafa_term_info_set_seq(self.raw, seq as u32, seq_str_ptr, &mut error);
+            let ok = ffi::chafa_term_info_set_seq(self.raw, seq as u32, seq_str_ptr, &mut error);
 
             if !error.is_null() {
-                let msg = std::ffi::CStr::from_ptr((*error).message as *const std::os::raw::c_char)
-                    .to_string_lossy()
-                    .into_owned();
-                let err_msg = format!("Chafa -> Failed to apply selectors: {}", &msg);
-
-                ffi::g_error_free(error);
-                return Err(err_msg);
+                if (*error).code as u32
+                    == ffi::ChafaTermInfoError_CHAFA_TERM_INFO_ERROR_SEQ_TOO_LONG
+                {
+                    ffi::g_error_free(error);
+                    return Err(ChafaError::SeqTooLong);
+                }
+                return Err(ChafaError::from_gerror(error));
+            }
+            if ok == 0 {
+                return Err(ChafaError::SeqTooLong);
             }
         }
 
@@ -167,6 +201,16 @@ impl Info {
         }
     }
 
+    /// Iterates over every sequence term_info can emit, in enum order.
+    pub fn supported_seqs(&self) -> impl Iterator<Item = Seq> + '_ {
+        Seq::all().filter(|seq| self.have_seq(*seq))
+    }
+
+    /// Iterates over every sequence term_info lacks, in enum order. Useful to find out why a terminal gets fallback output.
+    pub fn missing_seqs(&self) -> impl Iterator<Item = Seq> + '_ {
+        Seq::all().filter(|seq| !self.have_seq(*seq))
+    }
+
     /// Gets whether seq can be inherited from the outer ChafaTermInfo when chaining with chafa_term_info_chain().
     pub fn get_inherit_seq(&self, seq: Seq) -> bool {
         if unsafe { ffi::chafa_term_info_get_inherit_seq(self.raw, seq as u32) } == 0 {
@@ -183,18 +227,316 @@ impl Info {
         }
     }
 
-    // TODO: I honestly have no idea how to implement these easily.
-    // pub fn emit_seq(&self) {}
-    // pub fn emit_seq_valist(&self) {}
+    /// Gets the best canvas mode supported by term_info. Note that this does not take into account any pixel modes.
+    pub fn get_best_canvas_mode(&self) -> CanvasMode {
+        match unsafe { ffi::chafa_term_info_get_best_canvas_mode(self.raw) } {
+            ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_TRUECOLOR => CanvasMode::TrueColor,
+            ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_256 => CanvasMode::Indexed256,
+            ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_240 => CanvasMode::Indexed240,
+            ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_16 => CanvasMode::Indexed16,
+            ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_FGBG_BGFG => CanvasMode::FgbgBgfg,
+            ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_8 => CanvasMode::Indexed8,
+            ffi::ChafaCanvasMode_CHAFA_CANVAS_MODE_INDEXED_16_8 => CanvasMode::Indexed168,
+            _ => CanvasMode::FgBg,
+        }
+    }
+
+    /// Gets the best pixel mode supported by term_info. Note that this does not take into account the canvas mode.
+    pub fn get_best_pixel_mode(&self) -> PixelMode {
+        match unsafe { ffi::chafa_term_info_get_best_pixel_mode(self.raw) } {
+            ffi::ChafaPixelMode_CHAFA_PIXEL_MODE_SIXELS => PixelMode::Sixels,
+            ffi::ChafaPixelMode_CHAFA_PIXEL_MODE_KITTY => PixelMode::Kitty,
+            ffi::ChafaPixelMode_CHAFA_PIXEL_MODE_ITERM2 => PixelMode::Iterm2,
+            _ => PixelMode::Symbols,
+        }
+    }
+
+    /// Gets the type of passthrough used by term_info, i.e. whether it is chained inside a terminal multiplexer.
+    pub fn get_passthrough_type(&self) -> Passthrough {
+        match unsafe { ffi::chafa_term_info_get_passthrough_type(self.raw) } {
+            ffi::ChafaPassthrough_CHAFA_PASSTHROUGH_SCREEN => Passthrough::Screen,
+            ffi::ChafaPassthrough_CHAFA_PASSTHROUGH_TMUX => Passthrough::Tmux,
+            _ => Passthrough::None,
+        }
+    }
+
+    /// Checks if term_info needs passthrough guards around pixel data encoded with pixel_mode.
+    pub fn get_is_pixel_passthrough_needed(&self, pixel_mode: PixelMode) -> bool {
+        unsafe {
+            ffi::chafa_term_info_get_is_pixel_passthrough_needed(self.raw, pixel_mode as u32) != 0
+        }
+    }
+
+    /// Formats a sixel raster attributes command (`"Pan;Pad;Ph;Pv`) declaring a 1:1 pixel aspect ratio and the image size, and appends it to `out`. It goes right after the sequence emitted by `emit_begin_sixels()`.
+    ///
+    /// Nothing is appended if term_info can't emit sixels.
+    pub fn emit_sixel_raster_attributes<B: SeqBuffer>(&self, out: &mut B, width: u32, height: u32) {
+        if self.have_seq(Seq::BeginSixels) {
+            out.push_seq(format!("\"1;1;{};{}", width, height).as_bytes());
+        }
+    }
+
+    /// Formats seq with args and appends the result to `out`.
+    ///
+    /// The number of arguments must match the sequence's definition (see `Seq::n_args()`). Sequences that take a variable number of arguments accept up to CHAFA_TERM_SEQ_ARGS_MAX of them.
+    /// # Returns:
+    /// The number of bytes appended to `out`.
+    pub fn emit_seq(&self, seq: Seq, args: &[u32], out: &mut Vec<u8>) -> Result<usize, ChafaError> {
+        self.check_emit_args(seq, args)?;
+
+        let start = out.len();
+        self.emit_seq_dispatch(seq, args, out)?;
+        Ok(out.len() - start)
+    }
 
-    /// Attempts to parse a terminal sequence from an input data array. If successful, CHAFA_PARSE_SUCCESS will be returned, the input pointer will be advanced and the parsed length will be subtracted from input_len .
+    /// Formats seq with args like `emit_seq()`, but writes the result to `w` instead of appending to a buffer. No heap allocation takes place.
+    /// # Returns:
+    /// The number of bytes written. Invalid arguments are reported as `io::ErrorKind::InvalidInput`.
+    pub fn emit_seq_to<W: std::io::Write>(
+        &self,
+        seq: Seq,
+        args: &[u32],
+        w: &mut W,
+    ) -> std::io::Result<usize> {
+        let mut buf = [0u8; CHAFA_TERM_SEQ_LENGTH_MAX as usize];
+        let len = self
+            .emit_seq_into(seq, args, &mut buf)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        w.write_all(&buf[..len])?;
+        Ok(len)
+    }
+
+    /// Formats seq with args into a caller-provided fixed-size buffer, without any heap allocation. Chafa guarantees that a formatted sequence never exceeds CHAFA_TERM_SEQ_LENGTH_MAX bytes, so the buffer always fits the result.
     ///
-    /// Any numeric parsed arguments are returned as an array starting at args_out , which must have room for up to CHAFA_TERM_SEQ_ARGS_MAX elements.
+    /// Arguments are validated as in `emit_seq()`.
+    /// # Returns:
+    /// The number of bytes written to the start of `buf`.
+    pub fn emit_seq_into(
+        &self,
+        seq: Seq,
+        args: &[u32],
+        buf: &mut [u8; CHAFA_TERM_SEQ_LENGTH_MAX as usize],
+    ) -> Result<usize, ChafaError> {
+        self.check_emit_args(seq, args)?;
+        let mut out = SliceSeqBuffer { buf, len: 0 };
+        self.emit_seq_dispatch(seq, args, &mut out)?;
+        Ok(out.len)
+    }
+
+    /// Checks that seq can be emitted by term_info with the given number of arguments.
+    pub(crate) fn check_emit_args(&self, seq: Seq, args: &[u32]) -> Result<(), ChafaError> {
+        match seq.n_args() {
+            Some(n) if n != args.len() => {
+                return Err(ChafaError::InvalidInput(format!(
+                    "{:?} takes {} argument(s), got {}",
+                    seq,
+                    n,
+                    args.len()
+                )));
+            }
+            None if args.len() > CHAFA_TERM_SEQ_ARGS_MAX as usize => {
+                return Err(ChafaError::InvalidInput(format!(
+                    "{:?} takes at most {} arguments, got {}",
+                    seq,
+                    CHAFA_TERM_SEQ_ARGS_MAX,
+                    args.len()
+                )));
+            }
+            _ => {}
+        }
+        if !self.have_seq(seq) {
+            return Err(ChafaError::InvalidInput(format!(
+                "Terminal can't emit {:?}",
+                seq
+            )));
+        }
+        Ok(())
+    }
+
+    /// Attempts to parse a terminal sequence from an input data array. If successful, CHAFA_PARSE_SUCCESS will be returned along with the number of bytes consumed from input.
+    ///
+    /// Any numeric parsed arguments are returned in a vector, which is useful for seqs with a variable number of arguments, like CHAFA_TERM_SEQ_PRIMARY_DEVICE_ATTRIBUTES.
+    ///
+    /// CHAFA_PARSE_AGAIN means input holds the beginning of a matching sequence, but more data is needed to complete it.
+    /// # Parameters:
+    /// --- `seq`: The sequence to parse;
+    /// --- `input`: Input data;
+    /// # Returns:
+    /// A tuple containing the parse result, the number of bytes consumed and the parsed arguments, respectively.
+    pub fn parse_seq(&self, seq: Seq, input: &[u8]) -> (ParseResult, usize, Vec<u32>) {
+        let mut args = [0u32; CHAFA_TERM_SEQ_ARGS_MAX as usize];
+        let mut n_args: i32 = 0;
+
+        let len = input.len().min(i32::MAX as usize) as i32;
+        let mut input_len = len;
+        let mut input_ptr = input.as_ptr() as *mut ffi::gchar;
+
+        let result = unsafe {
+            ParseResult::from(ffi::chafa_term_info_parse_seq_varargs(
+                self.raw,
+                seq as u32,
+                &mut input_ptr,
+                &mut input_len,
+                args.as_mut_ptr(),
+                &mut n_args,
+            ))
+        };
+
+        match result {
+            ParseResult::Success => {
+                let n_args = (n_args.max(0) as usize).min(args.len());
+                (result, (len - input_len) as usize, args[..n_args].to_vec())
+            }
+            _ => (result, 0, Vec::new()),
+        }
+    }
+
+    /// Serializes term_info to a text definition holding its name, quirks, safe symbol tags, the sequences marked as inherited and every stored sequence.
     ///
-    /// The number of parsed arguments is returned in n_args_out . This is useful for seqs with a variable number of arguments, like CHAFA_TERM_SEQ_PRIMARY_DEVICE_ATTRIBUTES.
+    /// The result is one `key=value` pair per line, with sequences keyed by their canonical names (see `Seq::name()`) and the inherited ones listed by name under `inherit`, separated by commas. Control characters are escaped, so the text can be stored in a config file and loaded back with `from_definition_string()`.
+    pub fn to_definition_string(&self) -> String {
+        let mut out = String::new();
+
+        if let Some(name) = self.get_name() {
+            out.push_str(&format!("name={}\n", escape_definition(&name)));
+        }
+        out.push_str(&format!("quirks=0x{:x}\n", self.get_quirks().bits()));
+        out.push_str(&format!(
+            "safe_tags=0x{:x}\n",
+            self.get_safe_symbol_tags().bits()
+        ));
+        let inherited: Vec<&str> = Seq::all()
+            .filter(|seq| self.get_inherit_seq(*seq))
+            .map(|seq| seq.name())
+            .collect();
+        out.push_str(&format!("inherit={}\n", inherited.join(",")));
+
+        for seq in Seq::all() {
+            if let Some(seq_str) = self.get_seq(seq) {
+                out.push_str(&format!("{}={}\n", seq, escape_definition(&seq_str)));
+            }
+        }
+
+        out
+    }
+
+    /// Builds a new ChafaTermInfo from a text definition produced by `to_definition_string()`.
     ///
-    /// Either or both of args_out and n_args_out can be NULL, in which case nothing is returned for that parameter.
-    pub fn parse_seq_varargs(&self, seq: Seq) {}
+    /// Empty lines and lines starting with `#` are ignored. Keys that are left out keep the defaults of a blank ChafaTermInfo; an `inherit` line marks exactly the sequences it lists as inherited.
+    pub fn from_definition_string(definition: &str) -> Result<Self, ChafaError> {
+        let info = Info::new()?;
+
+        for (i, line) in definition.lines().enumerate() {
+            let line = line.trim_start();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid =
+                |what: &str| ChafaError::InvalidInput(format!("line {}: {}", i + 1, what));
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("expected key=value"))?;
+            let key = key.trim();
+
+            match key {
+                "name" => info
+                    .set_name(&unescape_definition(value).ok_or_else(|| invalid("bad escape"))?)?,
+                "quirks" => info.set_quirks(Quirks::from_bits_truncate(
+                    parse_definition_bits(value).ok_or_else(|| invalid("bad quirks value"))?,
+                )),
+                "safe_tags" => info.set_safe_symbol_tags(SymbolTags::from_bits_truncate(
+                    parse_definition_bits(value).ok_or_else(|| invalid("bad safe_tags value"))?
+                        as i32,
+                )),
+                "inherit" => {
+                    let mut inherited = Vec::new();
+                    for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                        let seq: Seq = name
+                            .parse()
+                            .map_err(|_| invalid(&format!("unknown sequence {}", name)))?;
+                        inherited.push(seq);
+                    }
+                    for seq in Seq::all() {
+                        info.set_inherit_seq(seq, inherited.contains(&seq));
+                    }
+                }
+                _ => {
+                    let seq: Seq = key
+                        .parse()
+                        .map_err(|_| invalid(&format!("unknown key {}", key)))?;
+                    let seq_str =
+                        unescape_definition(value).ok_or_else(|| invalid("bad escape"))?;
+                    info.set_seq(seq, Some(&seq_str))?;
+                }
+            }
+        }
+
+        Ok(info)
+    }
+}
+
+/// Escapes backslashes and control characters for `Info::to_definition_string()`.
+fn escape_definition(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\x1b' => out.push_str("\\e"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_ascii_control() => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Reverses `escape_definition()`. `\x` escapes give single bytes, so several of them can spell out a UTF-8 character. Returns None on a malformed escape or if the bytes aren't valid UTF-8.
+fn unescape_definition(s: &str) -> Option<String> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next()? {
+            '\\' => out.push(b'\\'),
+            'e' => out.push(0x1b),
+            'n' => out.push(b'\n'),
+            'r' => out.push(b'\r'),
+            't' => out.push(b'\t'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                if hex.len() != 2 {
+                    return None;
+                }
+                out.push(u8::from_str_radix(&hex, 16).ok()?);
+            }
+            _ => return None,
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Parses a flags value written as decimal or `0x`-prefixed hexadecimal.
+fn parse_definition_bits(s: &str) -> Option<u32> {
+    let s = s.trim();
+    match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+impl Clone for Info {
+    /// Creates a deep copy of term_info, as `try_clone()` does, but panics if chafa fails to copy it. Use `try_clone()` to get the error instead.
+    fn clone(&self) -> Self {
+        self.try_clone().expect("Chafa -> Failed to copy term info")
+    }
 }
 
 impl Drop for Info {
@@ -212,12 +554,14 @@ pub const CHAFA_TERM_SEQ_LENGTH_MAX: u32 = 96;
 
 bitflags::bitflags! {
     #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
     pub struct Quirks: u32 {
         const SixelOvershoot = ffi::ChafaTermQuirks_CHAFA_TERM_QUIRK_SIXEL_OVERSHOOT;
     }
 }
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 /// An enumeration of the possible return values from the parsing function.
 pub enum ParseResult {
     Success = ffi::ChafaParseResult_CHAFA_PARSE_SUCCESS,
@@ -253,12 +597,30 @@ impl From<ParseResult> for u32 {
  */
 
 macro_rules! gen_termseq {
-    ($($name:ident = $ffi_const:path,)+) => {
+    ($($name:ident = $ffi_const:path => $canon:literal,)+) => {
         #[repr(u32)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
         pub enum Seq {
             $($name = $ffi_const,)+
         }
 
+        impl Seq {
+            /// Gets chafa's canonical name for the sequence, e.g. `cursor_to_pos`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Seq::$name => $canon,)+
+                }
+            }
+
+            /// Gets the sequence with chafa's value for it, or `None` if the value isn't one this crate knows, e.g. because the chafa library in use is newer than the headers it was built with.
+            pub fn from_raw(value: u32) -> Option<Seq> {
+                match value {
+                    $($ffi_const => Some(Seq::$name),)*
+                    _ => None,
+                }
+            }
+        }
+
         impl From<Seq> for u32 {
             fn from(value: Seq) -> u32 {
                 match value {
@@ -268,14 +630,180 @@ macro_rules! gen_termseq {
         }
 
         impl From<u32> for Seq {
+            /// Panics if value isn't a known sequence; see `Seq::from_raw()` for a fallible conversion.
             fn from(value: u32) -> Seq {
-                match value {
-                    $($ffi_const => Seq::$name,)*
-                    _ => panic!("Invalid value for ChafaTermSeq: {value}")
-                }
+                Seq::from_raw(value)
+                    .unwrap_or_else(|| panic!("Invalid value for ChafaTermSeq: {value}"))
             }
         }
     };
 }
 
 include!("term_seq.rs");
+
+impl Seq {
+    /// Iterates over every sequence known to chafa, in enum order. `Seq::Max` is not included.
+    pub fn all() -> impl Iterator<Item = Seq> {
+        (0..ffi::ChafaTermSeq_CHAFA_TERM_SEQ_MAX).map(Seq::from)
+    }
+}
+
+impl std::fmt::Display for Seq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Seq {
+    type Err = ChafaError;
+
+    /// Looks up a sequence by its canonical name. Matching ignores ASCII case and accepts `-` in place of `_`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted = s.trim().to_ascii_lowercase().replace('-', "_");
+        Seq::all()
+            .find(|seq| seq.name() == wanted)
+            .ok_or_else(|| ChafaError::InvalidInput(format!("Unknown sequence name: {}", s)))
+    }
+}
+
+/// A buffer that formatted control sequences can be appended to.
+pub trait SeqBuffer {
+    /// Appends the bytes of a formatted control sequence.
+    fn push_seq(&mut self, seq: &[u8]);
+}
+
+impl SeqBuffer for Vec<u8> {
+    fn push_seq(&mut self, seq: &[u8]) {
+        self.extend_from_slice(seq);
+    }
+}
+
+impl SeqBuffer for String {
+    fn push_seq(&mut self, seq: &[u8]) {
+        self.push_str(&String::from_utf8_lossy(seq));
+    }
+}
+
+/// Fills a borrowed buffer from the start, used to format sequences without allocating.
+struct SliceSeqBuffer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl SeqBuffer for SliceSeqBuffer<'_> {
+    fn push_seq(&mut self, seq: &[u8]) {
+        // Chafa guarantees a single sequence fits; anything beyond that is dropped rather than overflowing.
+        let n = seq.len().min(self.buf.len() - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&seq[..n]);
+        self.len += n;
+    }
+}
+
+/*
+ *
+ * Typed wrappers for the `chafa_term_info_emit_*()` family are generated at build time.
+ *
+ */
+
+macro_rules! gen_emitters {
+    ($($name:ident($($arg:ident: $ty:ty),*) / $name_to:ident = $ffi_fn:path, $seq:literal,)*) => {
+        impl Info {
+            $(
+                #[doc = concat!("Formats ", $seq, " and appends it to `out`. Nothing is appended if term_info can't emit the sequence.")]
+                #[allow(clippy::too_many_arguments)]
+                pub fn $name<B: SeqBuffer>(&self, out: &mut B, $($arg: $ty),*) {
+                    let mut buf = [0u8; CHAFA_TERM_SEQ_LENGTH_MAX as usize];
+                    let start = buf.as_mut_ptr() as *mut ffi::gchar;
+                    let end = unsafe { $ffi_fn(self.raw, start, $($arg as _),*) };
+                    let len = end as usize - start as usize;
+                    out.push_seq(&buf[..len]);
+                }
+
+                #[doc = concat!("Formats ", $seq, " and writes it to `w` without any intermediate allocation. Nothing is written if term_info can't emit the sequence.")]
+                #[allow(clippy::too_many_arguments)]
+                pub fn $name_to<W: std::io::Write>(&self, w: &mut W, $($arg: $ty),*) -> std::io::Result<()> {
+                    let mut buf = [0u8; CHAFA_TERM_SEQ_LENGTH_MAX as usize];
+                    let start = buf.as_mut_ptr() as *mut ffi::gchar;
+                    let end = unsafe { $ffi_fn(self.raw, start, $($arg as _),*) };
+                    let len = end as usize - start as usize;
+                    w.write_all(&buf[..len])
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! gen_emitters_varargs {
+    ($($name:ident / $name_to:ident = $ffi_fn:path, $seq:literal,)*) => {
+        impl Info {
+            $(
+                #[doc = concat!("Formats ", $seq, " with a variable number of arguments and appends it to `out`. At most CHAFA_TERM_SEQ_ARGS_MAX arguments are used.")]
+                pub fn $name<B: SeqBuffer>(&self, out: &mut B, args: &[u32]) {
+                    let args = &args[..args.len().min(CHAFA_TERM_SEQ_ARGS_MAX as usize)];
+                    let mut buf = [0u8; CHAFA_TERM_SEQ_LENGTH_MAX as usize];
+                    let start = buf.as_mut_ptr() as *mut ffi::gchar;
+                    let end = unsafe {
+                        $ffi_fn(self.raw, start, args.as_ptr() as _, args.len() as _)
+                    };
+                    let len = end as usize - start as usize;
+                    out.push_seq(&buf[..len]);
+                }
+
+                #[doc = concat!("Formats ", $seq, " with a variable number of arguments and writes it to `w` without any intermediate allocation. At most CHAFA_TERM_SEQ_ARGS_MAX arguments are used.")]
+                pub fn $name_to<W: std::io::Write>(&self, w: &mut W, args: &[u32]) -> std::io::Result<()> {
+                    let args = &args[..args.len().min(CHAFA_TERM_SEQ_ARGS_MAX as usize)];
+                    let mut buf = [0u8; CHAFA_TERM_SEQ_LENGTH_MAX as usize];
+                    let start = buf.as_mut_ptr() as *mut ffi::gchar;
+                    let end = unsafe {
+                        $ffi_fn(self.raw, start, args.as_ptr() as _, args.len() as _)
+                    };
+                    let len = end as usize - start as usize;
+                    w.write_all(&buf[..len])
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! gen_seq_dispatch {
+    (
+        $($name:ident($n_args:literal) => $emit_fn:ident($($idx:literal: $ty:ty),*),)*
+        ;
+        $($va_name:ident => $va_emit_fn:ident,)*
+    ) => {
+        impl Seq {
+            /// Returns the number of arguments seq takes when formatted, or `None` if it takes a variable number of arguments.
+            pub fn n_args(&self) -> Option<usize> {
+                match self {
+                    $(Seq::$name => Some($n_args),)*
+                    #[allow(unreachable_patterns)]
+                    _ => None,
+                }
+            }
+        }
+
+        impl Info {
+            /// Formats seq with args through its typed emitter. Argument counts must already have been validated.
+            pub(crate) fn emit_seq_dispatch<B: SeqBuffer>(&self, seq: Seq, args: &[u32], out: &mut B) -> Result<(), ChafaError> {
+                match seq {
+                    $(
+                        Seq::$name => self.$emit_fn(
+                            out,
+                            $(
+                                <$ty>::try_from(args[$idx]).map_err(|_| {
+                                    ChafaError::InvalidInput(format!("Argument {} of {:?} is out of range: {}", $idx + 1, seq, args[$idx]))
+                                })?
+                            ),*
+                        ),
+                    )*
+                    $(Seq::$va_name => self.$va_emit_fn(out, args),)*
+                    #[allow(unreachable_patterns)]
+                    _ => return Err(ChafaError::InvalidInput(format!("{:?} can't be emitted", seq))),
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+include!("term_emit.rs");
@@ -1,4 +1,26 @@
+mod args;
+mod capabilities;
 mod db;
+mod env;
 mod info;
+mod multiplexer;
+mod parser;
+mod passthrough;
+mod probe;
+mod probe_session;
+mod terminal;
+#[cfg(windows)]
+mod windows;
+pub use args::*;
+pub use capabilities::*;
 pub use db::*;
+pub use env::*;
 pub use info::*;
+pub use multiplexer::*;
+pub use parser::*;
+pub use passthrough::*;
+pub use probe::*;
+pub use probe_session::*;
+pub use terminal::*;
+#[cfg(windows)]
+pub use windows::*;
@@ -0,0 +1,92 @@
+use crate::ChafaError;
+use crate::canvas::Passthrough;
+use crate::term::{Db, Env, Info, in_zellij, tmux_command};
+
+/// A terminal multiplexer that can sit between the process and the terminal displaying its output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultiplexerKind {
+    /// tmux, detected through the TMUX variable.
+    Tmux,
+    /// GNU Screen, detected through the STY variable.
+    Screen,
+    /// Zellij, detected through the ZELLIJ or ZELLIJ_SESSION_NAME variables. It doesn't support passthrough, but draws sixel images itself; see `zellij_pixel_mode()`.
+    Zellij,
+}
+
+/// A multiplexer the process runs inside of, along with the term infos needed to talk through it.
+pub struct Multiplexer {
+    /// Which multiplexer was found.
+    pub kind: MultiplexerKind,
+    /// Passthrough mode for pixel data to reach the outer terminal.
+    pub passthrough: Passthrough,
+    /// Describes the multiplexer itself.
+    pub inner: Info,
+    /// Describes the terminal the multiplexer is attached to, as far as it can be determined.
+    pub outer: Info,
+}
+
+impl Multiplexer {
+    /// Chains the outer and inner term infos, yielding one that emits the multiplexer's sequences with the outer terminal's added through passthrough. See `Info::chain()`.
+    pub fn chain(&self) -> Result<Info, ChafaError> {
+        Info::chain(&self.outer, &self.inner)
+    }
+}
+
+/// Checks env for tmux, GNU Screen or Zellij. Pass `Env::capture()` to check the process environment.
+///
+/// tmux is recognized by the TMUX variable, but only if the server socket it names still exists; stale variables are common after detaching or over SSH. For tmux, the outer terminal's TERM is read from the attached client. For the others, the outer terminal is assumed to be xterm-compatible.
+pub fn detect_multiplexer(env: &Env) -> Option<Multiplexer> {
+    let kind = detect_kind(env)?;
+    let db = Db::default().ok()?;
+
+    let (passthrough, inner_term, outer_term) = match kind {
+        MultiplexerKind::Tmux => {
+            let outer_term = tmux_command(env, &["display-message", "-p", "#{client_termname}"])
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| String::from("xterm-256color"));
+            (Passthrough::Tmux, String::from("tmux-256color"), outer_term)
+        }
+        MultiplexerKind::Screen => (
+            Passthrough::Screen,
+            String::from("screen-256color"),
+            String::from("xterm-256color"),
+        ),
+        MultiplexerKind::Zellij => (
+            Passthrough::None,
+            String::from("xterm-256color"),
+            String::from("xterm-256color"),
+        ),
+    };
+
+    // Keep chafa from chaining infos on its own while detecting each side.
+    const MUX_VARS: [&str; 4] = ["TMUX", "STY", "ZELLIJ", "ZELLIJ_SESSION_NAME"];
+    let mut inner_env: Vec<(&str, Option<&str>)> = MUX_VARS.iter().map(|v| (*v, None)).collect();
+    inner_env.push(("TERM", Some(&inner_term)));
+    let mut outer_env: Vec<(&str, Option<&str>)> = MUX_VARS.iter().map(|v| (*v, None)).collect();
+    outer_env.push(("TERM", Some(&outer_term)));
+    outer_env.push(("TERM_PROGRAM", None));
+
+    Some(Multiplexer {
+        kind,
+        passthrough,
+        inner: db.detect_with_env(env, &inner_env).ok()?,
+        outer: db.detect_with_env(env, &outer_env).ok()?,
+    })
+}
+
+fn detect_kind(env: &Env) -> Option<MultiplexerKind> {
+    if let Some(tmux) = env.get("TMUX") {
+        // TMUX holds "socket_path,server_pid,session_id".
+        let socket = tmux.split(',').next().unwrap_or_default();
+        if !socket.is_empty() && std::path::Path::new(socket).exists() {
+            return Some(MultiplexerKind::Tmux);
+        }
+    }
+    if in_zellij(env) {
+        return Some(MultiplexerKind::Zellij);
+    }
+    if env.contains("STY") {
+        return Some(MultiplexerKind::Screen);
+    }
+    None
+}
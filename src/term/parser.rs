@@ -0,0 +1,262 @@
+use crate::term::{Info, ParseResult, Seq};
+
+/// A piece of terminal input recognized by a `Parser`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+    /// A control sequence known to the parser's terminal info, along with its parsed arguments.
+    Seq(Seq, Vec<u32>),
+    /// A run of input that didn't match any sequence, e.g. typed text.
+    Bytes(Vec<u8>),
+}
+
+/// A streaming parser for terminal input. It accepts arbitrary chunks of data and splits them into the control sequences described by its ChafaTermInfo and plain data in between.
+///
+/// Incomplete sequences at the end of a chunk are buffered until more data arrives (see CHAFA_PARSE_AGAIN), so input can be fed as it is read from the terminal.
+pub struct Parser {
+    info: Info,
+    seqs: Vec<Seq>,
+    starts: [bool; 256],
+    buf: Vec<u8>,
+}
+
+impl Parser {
+    /// Creates a new parser recognizing every sequence term_info can emit.
+    pub fn new(info: Info) -> Self {
+        let seqs: Vec<Seq> = Seq::all().filter(|seq| info.have_seq(*seq)).collect();
+
+        // Only bytes that can begin a sequence need to be tried against the full set.
+        let mut starts = [false; 256];
+        for seq in seqs.iter() {
+            match info.get_seq(*seq).and_then(|s| s.bytes().next()) {
+                Some(b) if b != b'%' => starts[b as usize] = true,
+                _ => starts = [true; 256],
+            }
+        }
+
+        Self {
+            info,
+            seqs,
+            starts,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Returns the terminal info used by the parser.
+    pub fn info(&self) -> &Info {
+        &self.info
+    }
+
+    /// Returns the input that has been buffered while waiting for an incomplete sequence to finish.
+    pub fn pending(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Feeds a chunk of input to the parser and returns the tokens that could be recognized so far.
+    pub fn feed(&mut self, data: &[u8]) -> Vec<Token> {
+        self.buf.extend_from_slice(data);
+        self.parse(false)
+    }
+
+    /// Feeds a chunk of input to the parser like `feed()`, and returns the recognized input as typed events.
+    pub fn feed_events(&mut self, data: &[u8]) -> Vec<TermEvent> {
+        let tokens = self.feed(data);
+        tokens.into_iter().flat_map(TermEvent::from_token).collect()
+    }
+
+    /// Returns all events from buffered input like `flush()`.
+    pub fn flush_events(&mut self) -> Vec<TermEvent> {
+        let tokens = self.flush();
+        tokens.into_iter().flat_map(TermEvent::from_token).collect()
+    }
+
+    /// Returns all tokens from buffered input, treating any incomplete sequence as plain data.
+    ///
+    /// Call this when no more input is expected for a while, e.g. after a read timeout, so that a lone ESC key press is not held back.
+    pub fn flush(&mut self) -> Vec<Token> {
+        self.parse(true)
+    }
+
+    fn parse(&mut self, flush: bool) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut text: Vec<u8> = Vec::new();
+        let mut pos = 0;
+
+        while pos < self.buf.len() {
+            let input = &self.buf[pos..];
+            let mut best: Option<(Seq, usize, Vec<u32>)> = None;
+            let mut again = false;
+
+            if self.starts[input[0] as usize] {
+                for seq in self.seqs.iter() {
+                    let (result, consumed, args) = self.info.parse_seq(*seq, input);
+                    match result {
+                        ParseResult::Success => {
+                            if best.as_ref().is_none_or(|(_, n, _)| consumed > *n) {
+                                best = Some((*seq, consumed, args));
+                            }
+                        }
+                        ParseResult::Again => again = true,
+                        ParseResult::Failure => {}
+                    }
+                }
+            }
+
+            if again && !flush && best.is_none() {
+                break;
+            }
+
+            match best {
+                Some((seq, consumed, args)) if consumed > 0 => {
+                    if !text.is_empty() {
+                        tokens.push(Token::Bytes(std::mem::take(&mut text)));
+                    }
+                    tokens.push(Token::Seq(seq, args));
+                    pos += consumed;
+                }
+                _ => {
+                    text.push(input[0]);
+                    pos += 1;
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Bytes(text));
+        }
+        self.buf.drain(..pos);
+        tokens
+    }
+}
+
+/// A key that is reported with a control sequence rather than as a character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Return,
+    Backspace,
+    Tab,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Insert,
+    Delete,
+    /// A function key, F1 through F12.
+    F(u8),
+}
+
+/// Terminal input decoded into keys, text and replies to queries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TermEvent {
+    /// A typed character.
+    Char(char),
+    /// A special key, along with the modifiers chafa can tell apart.
+    Key { key: Key, ctrl: bool, shift: bool },
+    /// Reply to CHAFA_TERM_SEQ_QUERY_PRIMARY_DEVICE_ATTRIBUTES, listing the attributes the terminal advertises (e.g. `4` for sixels).
+    PrimaryDeviceAttributes(Vec<u32>),
+    /// Reply to CHAFA_TERM_SEQ_QUERY_TEXT_AREA_SIZE_CELLS.
+    TextAreaSizeCells { width: u32, height: u32 },
+    /// Reply to CHAFA_TERM_SEQ_QUERY_TEXT_AREA_SIZE_PX.
+    TextAreaSizePx { width: u32, height: u32 },
+    /// Reply to CHAFA_TERM_SEQ_QUERY_CELL_SIZE_PX.
+    CellSizePx { width: u32, height: u32 },
+    /// Reply to CHAFA_TERM_SEQ_QUERY_DEFAULT_FG, as packed 8bpc RGB (0x00RRGGBB).
+    DefaultFg(u32),
+    /// Reply to CHAFA_TERM_SEQ_QUERY_DEFAULT_BG, as packed 8bpc RGB (0x00RRGGBB).
+    DefaultBg(u32),
+    /// Any other recognized sequence, along with its arguments.
+    Seq(Seq, Vec<u32>),
+}
+
+impl TermEvent {
+    /// Converts a parser token to events. Plain data becomes one `Char` event per character; invalid UTF-8 is replaced with U+FFFD.
+    pub fn from_token(token: Token) -> Vec<TermEvent> {
+        let (seq, args) = match token {
+            Token::Bytes(bytes) => {
+                return String::from_utf8_lossy(&bytes)
+                    .chars()
+                    .map(TermEvent::Char)
+                    .collect();
+            }
+            Token::Seq(seq, args) => (seq, args),
+        };
+
+        if let Some((key, ctrl, shift)) = key_from_seq(seq) {
+            return vec![TermEvent::Key { key, ctrl, shift }];
+        }
+
+        let event = match (seq, args.as_slice()) {
+            (Seq::PrimaryDeviceAttributes, _) => TermEvent::PrimaryDeviceAttributes(args),
+            (Seq::TextAreaSizeCells, [height, width]) => TermEvent::TextAreaSizeCells {
+                width: *width,
+                height: *height,
+            },
+            (Seq::TextAreaSizePx, [height, width]) => TermEvent::TextAreaSizePx {
+                width: *width,
+                height: *height,
+            },
+            (Seq::CellSizePx, [height, width]) => TermEvent::CellSizePx {
+                width: *width,
+                height: *height,
+            },
+            (Seq::SetDefaultFg, [r, g, b]) => TermEvent::DefaultFg(rgb_from_report(*r, *g, *b)),
+            (Seq::SetDefaultBg, [r, g, b]) => TermEvent::DefaultBg(rgb_from_report(*r, *g, *b)),
+            _ => TermEvent::Seq(seq, args),
+        };
+        vec![event]
+    }
+}
+
+/// Packs color channels from a terminal report into 0x00RRGGBB. Channels may be reported with up to 16 bits.
+pub(crate) fn rgb_from_report(r: u32, g: u32, b: u32) -> u32 {
+    let c = |v: u32| if v > 0xff { (v >> 8) & 0xff } else { v };
+    (c(r) << 16) | (c(g) << 8) | c(b)
+}
+
+/// Maps the key sequences chafa models to a key and its (ctrl, shift) modifiers.
+fn key_from_seq(seq: Seq) -> Option<(Key, bool, bool)> {
+    macro_rules! keys {
+        ($($key:expr => $plain:ident, $ctrl:ident, $shift:ident;)*) => {
+            match seq {
+                $(
+                    Seq::$plain => Some(($key, false, false)),
+                    Seq::$ctrl => Some(($key, true, false)),
+                    Seq::$shift => Some(($key, false, true)),
+                )*
+                Seq::ReturnKey => Some((Key::Return, false, false)),
+                Seq::BackspaceKey => Some((Key::Backspace, false, false)),
+                Seq::TabKey => Some((Key::Tab, false, false)),
+                Seq::TabShiftKey => Some((Key::Tab, false, true)),
+                _ => None,
+            }
+        };
+    }
+
+    keys! {
+        Key::Up => UpKey, UpCtrlKey, UpShiftKey;
+        Key::Down => DownKey, DownCtrlKey, DownShiftKey;
+        Key::Left => LeftKey, LeftCtrlKey, LeftShiftKey;
+        Key::Right => RightKey, RightCtrlKey, RightShiftKey;
+        Key::PageUp => PageUpKey, PageUpCtrlKey, PageUpShiftKey;
+        Key::PageDown => PageDownKey, PageDownCtrlKey, PageDownShiftKey;
+        Key::Home => HomeKey, HomeCtrlKey, HomeShiftKey;
+        Key::End => EndKey, EndCtrlKey, EndShiftKey;
+        Key::Insert => InsertKey, InsertCtrlKey, InsertShiftKey;
+        Key::Delete => DeleteKey, DeleteCtrlKey, DeleteShiftKey;
+        Key::F(1) => F1Key, F1CtrlKey, F1ShiftKey;
+        Key::F(2) => F2Key, F2CtrlKey, F2ShiftKey;
+        Key::F(3) => F3Key, F3CtrlKey, F3ShiftKey;
+        Key::F(4) => F4Key, F4CtrlKey, F4ShiftKey;
+        Key::F(5) => F5Key, F5CtrlKey, F5ShiftKey;
+        Key::F(6) => F6Key, F6CtrlKey, F6ShiftKey;
+        Key::F(7) => F7Key, F7CtrlKey, F7ShiftKey;
+        Key::F(8) => F8Key, F8CtrlKey, F8ShiftKey;
+        Key::F(9) => F9Key, F9CtrlKey, F9ShiftKey;
+        Key::F(10) => F10Key, F10CtrlKey, F10ShiftKey;
+        Key::F(11) => F11Key, F11CtrlKey, F11ShiftKey;
+        Key::F(12) => F12Key, F12CtrlKey, F12ShiftKey;
+    }
+}
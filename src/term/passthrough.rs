@@ -0,0 +1,114 @@
+use crate::canvas::{Passthrough, PixelMode};
+use crate::term::Env;
+
+/// Largest payload forwarded in a single tmux passthrough sequence, before escape doubling.
+const TMUX_CHUNK_MAX: usize = 4096;
+/// Largest payload forwarded in a single GNU Screen DCS string. Screen drops strings longer than 768 bytes.
+const SCREEN_CHUNK_MAX: usize = 512;
+
+/// Wraps data so a terminal multiplexer forwards it untouched to the terminal it runs in.
+///
+/// For tmux, each chunk is enclosed in `ESC P tmux; ... ESC \` and every ESC in it is doubled. For GNU Screen, each chunk is enclosed in `ESC P ... ESC \`. Data is split into several passthrough sequences to stay within the multiplexers' length limits; the outer terminal sees the chunks as one contiguous stream.
+///
+/// Note that tmux only forwards passthrough sequences when its `allow-passthrough` option is enabled.
+/// # Parameters:
+/// --- `data`: Bytes to forward, e.g. an image or control sequence;
+/// --- `passthrough`: The multiplexer to wrap for. `Passthrough::None` returns the data as-is;
+pub fn wrap_passthrough(data: &[u8], passthrough: Passthrough) -> Vec<u8> {
+    match passthrough {
+        Passthrough::Tmux => {
+            let n_esc = data.iter().filter(|b| **b == 0x1b).count();
+            let n_chunks = data.len().div_ceil(TMUX_CHUNK_MAX);
+            let mut out = Vec::with_capacity(data.len() + n_esc + n_chunks * 9);
+
+            for chunk in data.chunks(TMUX_CHUNK_MAX) {
+                out.extend_from_slice(b"\x1bPtmux;");
+                for b in chunk {
+                    if *b == 0x1b {
+                        out.push(0x1b);
+                    }
+                    out.push(*b);
+                }
+                out.extend_from_slice(b"\x1b\\");
+            }
+            out
+        }
+        Passthrough::Screen => {
+            let n_chunks = data.len().div_ceil(SCREEN_CHUNK_MAX);
+            let mut out = Vec::with_capacity(data.len() + n_chunks * 4);
+
+            for chunk in data.chunks(SCREEN_CHUNK_MAX) {
+                out.extend_from_slice(b"\x1bP");
+                out.extend_from_slice(chunk);
+                out.extend_from_slice(b"\x1b\\");
+            }
+            out
+        }
+        Passthrough::None | Passthrough::Max => data.to_vec(),
+    }
+}
+
+/// Checks whether the tmux session env belongs to forwards passthrough sequences. Pass `Env::capture()` to check the session the process runs in.
+///
+/// tmux 3.3 and later silently drop passthrough sequences unless the `allow-passthrough` option is `on` or `all`; earlier versions always forward them. The option is read for the current pane, falling back to the global value.
+/// # Returns:
+/// `None` if env isn't inside tmux or tmux could not be queried.
+pub fn tmux_allows_passthrough(env: &Env) -> Option<bool> {
+    env.get_os("TMUX")?;
+
+    let version = tmux_command(env, &["-V"])?;
+    if parse_tmux_version(&version).is_some_and(|v| v < (3, 3)) {
+        return Some(true);
+    }
+
+    let value = tmux_command(env, &["show-options", "-pqv", "allow-passthrough"])
+        .filter(|v| !v.is_empty())
+        .or_else(|| tmux_command(env, &["show-options", "-gqv", "allow-passthrough"]))?;
+    Some(matches!(value.as_str(), "on" | "all"))
+}
+
+/// Checks whether env belongs to a Zellij session, which sets the ZELLIJ and ZELLIJ_SESSION_NAME variables in every pane.
+pub fn in_zellij(env: &Env) -> bool {
+    env.contains("ZELLIJ") || env.contains("ZELLIJ_SESSION_NAME")
+}
+
+/// Picks the pixel mode to use inside Zellij in place of pixel_mode.
+///
+/// Zellij has no passthrough, so nothing is wrapped for it. Instead it decodes sixel images itself and draws them again on the terminal it runs in, as long as that terminal shows sixels; kitty graphics and iTerm2 images are dropped. Detection inside Zellij often sees the outer terminal through inherited variables, so a kitty or iTerm2 mode is replaced by sixels if the terminal shows them, and by symbols otherwise.
+/// # Parameters:
+/// --- `pixel_mode`: The pixel mode detected for the terminal;
+/// --- `sixel`: Whether the terminal Zellij runs in shows sixel images;
+pub fn zellij_pixel_mode(pixel_mode: PixelMode, sixel: bool) -> PixelMode {
+    match pixel_mode {
+        PixelMode::Symbols => PixelMode::Symbols,
+        _ if sixel => PixelMode::Sixels,
+        _ => PixelMode::Symbols,
+    }
+}
+
+/// Runs tmux with args and returns its trimmed standard output if it succeeded. tmux finds its server through the TMUX variable, which is taken from env.
+pub(crate) fn tmux_command(env: &Env, args: &[&str]) -> Option<String> {
+    let mut command = std::process::Command::new("tmux");
+    match env.get_os("TMUX") {
+        Some(tmux) => command.env("TMUX", tmux),
+        None => command.env_remove("TMUX"),
+    };
+    let output = command
+        .args(args)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Extracts (major, minor) from `tmux -V` output such as `tmux 3.3a` or `tmux next-3.4`.
+fn parse_tmux_version(version: &str) -> Option<(u32, u32)> {
+    let start = version.find(|c: char| c.is_ascii_digit())?;
+    let mut parts = version[start..].split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|m| m.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
@@ -0,0 +1,249 @@
+use crate::ChafaError;
+use crate::term::{Event, Seq, Terminal};
+use std::time::{Duration, Instant};
+
+/// Sends the primary device attributes query (DA1) to the default terminal and checks whether the reply advertises sixel graphics (attribute `4`).
+///
+/// Environment-based detection misses many terminals that support sixels, so this asks the terminal directly. The tty is switched to raw mode while waiting for the reply. Any other input received in the meantime is discarded.
+/// # Parameters:
+/// --- `timeout`: Maximum time to wait for the reply;
+/// # Returns:
+/// `false` if the terminal replied without `4`, or did not reply in time.
+pub fn probe_sixel(timeout: Duration) -> Result<bool, ChafaError> {
+    if !is_interactive() {
+        return Ok(false);
+    }
+    let term = Terminal::get_default()?;
+    let info = term.get_term_info()?;
+    let _raw = RawMode::enable(STDIN_FD);
+
+    let mut query: Vec<u8> = Vec::new();
+    info.emit_query_primary_device_attributes(&mut query);
+    if query.is_empty() {
+        return Err(ChafaError::Failed(
+            "Chafa -> Terminal has no device attributes query",
+        ));
+    }
+    send(&term, &query)?;
+
+    let reply = wait_for_event(&term, timeout, |event| match event {
+        Event::Seq(Seq::PrimaryDeviceAttributes, args) => Some(args.contains(&4)),
+        _ => None,
+    });
+    Ok(reply.unwrap_or(false))
+}
+
+/// Sends a kitty graphics protocol query (`a=q`) to the default terminal and checks whether it is acknowledged.
+///
+/// This works even when TERM and other environment variables have been scrubbed, e.g. over SSH. The query is followed by a device attributes request, which all terminals answer, so terminals without kitty graphics don't make the caller wait for the full timeout.
+/// # Parameters:
+/// --- `timeout`: Maximum time to wait for the reply;
+/// # Returns:
+/// `true` if the terminal acknowledged the query.
+pub fn probe_kitty_graphics(timeout: Duration) -> Result<bool, ChafaError> {
+    if !is_interactive() {
+        return Ok(false);
+    }
+    let term = Terminal::get_default()?;
+    let info = term.get_term_info()?;
+    let _raw = RawMode::enable(STDIN_FD);
+
+    let mut query: Vec<u8> = KITTY_GRAPHICS_QUERY.to_vec();
+    info.emit_query_primary_device_attributes(&mut query);
+    send(&term, &query)?;
+
+    // The APC reply isn't a sequence chafa knows about, so it arrives as plain characters.
+    let mut reply = String::new();
+    let acked = wait_for_event(&term, timeout, |event| match event {
+        Event::Char(c) => {
+            reply.push(*c);
+            reply.ends_with(KITTY_GRAPHICS_ACK).then_some(true)
+        }
+        Event::Seq(Seq::PrimaryDeviceAttributes, _) => Some(false),
+        _ => None,
+    });
+    Ok(acked.unwrap_or(false))
+}
+
+/// Sends iTerm2's proprietary cell size report request (OSC 1337) to the default terminal and checks whether it is answered, which identifies terminals implementing the iTerm2 protocol.
+///
+/// Like `probe_kitty_graphics()`, the query is followed by a device attributes request to bound the wait on other terminals.
+/// # Parameters:
+/// --- `timeout`: Maximum time to wait for the reply;
+/// # Returns:
+/// `true` if the terminal sent a cell size report.
+pub fn probe_iterm2(timeout: Duration) -> Result<bool, ChafaError> {
+    if !is_interactive() {
+        return Ok(false);
+    }
+    let term = Terminal::get_default()?;
+    let info = term.get_term_info()?;
+    let _raw = RawMode::enable(STDIN_FD);
+
+    let mut query: Vec<u8> = ITERM2_CELL_SIZE_QUERY.to_vec();
+    info.emit_query_primary_device_attributes(&mut query);
+    send(&term, &query)?;
+
+    let mut reply = String::new();
+    let answered = wait_for_event(&term, timeout, |event| match event {
+        Event::Char(c) => {
+            reply.push(*c);
+            reply.ends_with(ITERM2_CELL_SIZE_REPLY).then_some(true)
+        }
+        Event::Seq(Seq::PrimaryDeviceAttributes, _) => Some(false),
+        _ => None,
+    });
+    Ok(answered.unwrap_or(false))
+}
+
+/// Asks the default terminal whether it supports DEC synchronized updates (mode 2026), by requesting the mode's state with DECRQM.
+///
+/// Terminals that know the mode report it as set or reset; others report it as unrecognized or don't answer at all. Like `probe_kitty_graphics()`, the query is followed by a device attributes request to bound the wait.
+/// # Parameters:
+/// --- `timeout`: Maximum time to wait for the reply;
+/// # Returns:
+/// `true` if the terminal reported the mode as one it recognizes. Pass it to `Info::set_synchronized_update()` to have output wrapped in synchronized updates.
+pub fn probe_synchronized_update(timeout: Duration) -> Result<bool, ChafaError> {
+    if !is_interactive() {
+        return Ok(false);
+    }
+    let term = Terminal::get_default()?;
+    let info = term.get_term_info()?;
+    let _raw = RawMode::enable(STDIN_FD);
+
+    let mut query: Vec<u8> = SYNC_UPDATE_QUERY.to_vec();
+    info.emit_query_primary_device_attributes(&mut query);
+    send(&term, &query)?;
+
+    // The DECRPM reply isn't a sequence chafa knows about either.
+    let mut reply = String::new();
+    let supported = wait_for_event(&term, timeout, |event| match event {
+        Event::Char(c) => {
+            reply.push(*c);
+            sync_update_reply(&reply)
+        }
+        Event::Seq(Seq::PrimaryDeviceAttributes, _) => Some(false),
+        _ => None,
+    });
+    Ok(supported.unwrap_or(false))
+}
+
+/// DECRQM for mode 2026, synchronized updates.
+pub(crate) const SYNC_UPDATE_QUERY: &[u8] = b"\x1b[?2026$p";
+
+/// Finds the DECRPM reply to `SYNC_UPDATE_QUERY` in text, `CSI ? 2026 ; Ps $ y`, and tells whether Ps says the mode is recognized: set (1), reset (2) or permanently set (3), but not unrecognized (0) or permanently reset (4).
+pub(crate) fn sync_update_reply(text: &str) -> Option<bool> {
+    let rest = &text[text.find("[?2026;")? + 7..];
+    let state = rest.chars().next()?;
+    rest[state.len_utf8()..]
+        .starts_with("$y")
+        .then_some(matches!(state, '1' | '2' | '3'))
+}
+
+const ITERM2_CELL_SIZE_QUERY: &[u8] = b"\x1b]1337;ReportCellSize\x07";
+const ITERM2_CELL_SIZE_REPLY: &str = "1337;ReportCellSize=";
+
+/// Queries support by transmitting a 1x1 RGB image with a reserved id; the terminal neither stores nor displays it.
+pub(crate) const KITTY_GRAPHICS_QUERY: &[u8] = b"\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\";
+pub(crate) const KITTY_GRAPHICS_ACK: &str = "Gi=31;OK";
+
+pub(crate) const STDIN_FD: i32 = 0;
+pub(crate) const STDOUT_FD: i32 = 1;
+
+/// Checks if queries can be sent to the default terminal and answered, i.e. both stdin and stdout are connected to it.
+pub(crate) fn is_interactive() -> bool {
+    is_tty(STDIN_FD) && is_tty(STDOUT_FD)
+}
+
+/// Checks if fd refers to a terminal. Always true on platforms where this can't be determined.
+pub(crate) fn is_tty(fd: i32) -> bool {
+    #[cfg(unix)]
+    {
+        unsafe { libc::isatty(fd) != 0 }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = fd;
+        true
+    }
+}
+
+/// Writes a query to the terminal and flushes it right away.
+pub(crate) fn send(term: &Terminal, query: &[u8]) -> Result<(), ChafaError> {
+    term.write_bytes(query)?;
+    term.flush_output()
+}
+
+/// Reads events from the terminal until `matcher` accepts one or `timeout` expires.
+pub(crate) fn wait_for_event<T>(
+    term: &Terminal,
+    timeout: Duration,
+    mut matcher: impl FnMut(&Event) -> Option<T>,
+) -> Option<T> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return None;
+        }
+        match term.read_event(Some(left)) {
+            Some(Event::Eof) | None => return None,
+            Some(event) => {
+                if let Some(found) = matcher(&event) {
+                    return Some(found);
+                }
+            }
+        }
+    }
+}
+
+/// Switches a tty to non-canonical mode without echo for as long as the guard lives, so replies to queries can be read as soon as they arrive and don't show up on screen.
+///
+/// Does nothing if the descriptor is not a tty.
+pub(crate) struct RawMode {
+    #[cfg(unix)]
+    fd: i32,
+    #[cfg(unix)]
+    saved: Option<libc::termios>,
+}
+
+impl RawMode {
+    #[cfg(unix)]
+    pub(crate) fn enable(fd: i32) -> Self {
+        unsafe {
+            let mut saved: libc::termios = std::mem::zeroed();
+            if libc::isatty(fd) == 0 || libc::tcgetattr(fd, &mut saved) != 0 {
+                return Self { fd, saved: None };
+            }
+
+            let mut raw = saved;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(fd, libc::TCSANOW, &raw) != 0 {
+                return Self { fd, saved: None };
+            }
+            Self {
+                fd,
+                saved: Some(saved),
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn enable(_fd: i32) -> Self {
+        Self {}
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(saved) = self.saved.as_ref() {
+            unsafe {
+                libc::tcsetattr(self.fd, libc::TCSANOW, saved);
+            }
+        }
+    }
+}
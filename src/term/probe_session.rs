@@ -0,0 +1,157 @@
+use crate::ChafaError;
+use crate::term::{
+    Capabilities, Db, Info, KITTY_GRAPHICS_ACK, KITTY_GRAPHICS_QUERY, Parser, RawMode, STDIN_FD,
+    SYNC_UPDATE_QUERY, Seq, Token, is_interactive, rgb_from_report, sync_update_reply,
+};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Asks the controlling terminal about itself and combines the answers with what its term info says.
+///
+/// A session sends a batch of queries at once: kitty graphics support, synchronized update support (DECRQM 2026), cell size in pixels, default background color and finally primary device attributes (DA1). Every terminal answers DA1, and replies arrive in order, so the session stops waiting as soon as the DA1 reply comes in, or when the timeout expires. Replies are split out of the input with a `Parser`; anything else typed in the meantime is discarded.
+///
+/// The tty is in raw mode for the duration of `run()`. If stdin or stdout isn't a terminal, or on platforms other than Unix, no queries are sent and the capabilities come from the term info alone.
+pub struct ProbeSession {
+    info: Info,
+    timeout: Duration,
+}
+
+impl ProbeSession {
+    /// Creates a new session for the terminal described by term_info, with a timeout of 250 ms.
+    pub fn new(info: Info) -> Self {
+        Self {
+            info,
+            timeout: Duration::from_millis(250),
+        }
+    }
+
+    /// Creates a new session for the terminal detected from the environment.
+    pub fn detect() -> Result<Self, ChafaError> {
+        let info = Db::default().and_then(|db| db.detect())?;
+        Ok(Self::new(info))
+    }
+
+    /// Sets how long to wait for replies in total.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the queries, collects the replies and returns the resulting capabilities.
+    pub fn run(&self) -> Result<Capabilities, ChafaError> {
+        let mut caps = Capabilities::from(&self.info);
+        // Replies can't be read back on other platforms yet, so don't send queries there.
+        if !is_interactive() || !cfg!(unix) {
+            return Ok(caps);
+        }
+
+        // Replies are parsed with fallback definitions for anything the term info lacks.
+        let info = self.info.try_clone()?;
+        if let Ok(fallback) = Db::default().and_then(|db| db.get_fallback_info()) {
+            info.supplement(&fallback);
+        }
+
+        let mut query: Vec<u8> = KITTY_GRAPHICS_QUERY.to_vec();
+        query.extend_from_slice(SYNC_UPDATE_QUERY);
+        info.emit_query_cell_size_px(&mut query);
+        info.emit_query_default_bg(&mut query);
+        info.emit_query_primary_device_attributes(&mut query);
+
+        let _raw = RawMode::enable(STDIN_FD);
+        let mut stdout = std::io::stdout();
+        stdout
+            .write_all(&query)
+            .and_then(|_| stdout.flush())
+            .map_err(|_| ChafaError::Failed("Chafa -> Failed to send terminal queries"))?;
+
+        let replies = collect_replies(Parser::new(info), self.timeout);
+        caps.apply_replies(
+            replies.sixel,
+            replies.kitty,
+            replies.synchronized_update,
+            replies.cell_size_px,
+            replies.default_bg_color,
+        );
+        Ok(caps)
+    }
+}
+
+#[derive(Default)]
+struct Replies {
+    sixel: bool,
+    kitty: bool,
+    synchronized_update: bool,
+    cell_size_px: Option<(u32, u32)>,
+    default_bg_color: Option<u32>,
+}
+
+/// Reads stdin until the DA1 reply arrives or the timeout expires.
+fn collect_replies(mut parser: Parser, timeout: Duration) -> Replies {
+    let mut replies = Replies::default();
+    let mut text: Vec<u8> = Vec::new();
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break;
+        }
+        let Some(input) = read_input(left) else {
+            break;
+        };
+
+        let mut done = false;
+        for token in parser.feed(&input) {
+            match token {
+                Token::Seq(Seq::PrimaryDeviceAttributes, args) => {
+                    replies.sixel = args.contains(&4);
+                    done = true;
+                }
+                Token::Seq(Seq::CellSizePx, args) if args.len() == 2 => {
+                    replies.cell_size_px = Some((args[1], args[0]));
+                }
+                Token::Seq(Seq::SetDefaultBg, args) if args.len() == 3 => {
+                    replies.default_bg_color = Some(rgb_from_report(args[0], args[1], args[2]));
+                }
+                // The kitty and DECRPM replies aren't modeled as sequences.
+                Token::Bytes(bytes) => text.extend_from_slice(&bytes),
+                Token::Seq(..) => {}
+            }
+        }
+        if done {
+            break;
+        }
+    }
+
+    let text = String::from_utf8_lossy(&text);
+    replies.kitty = text.contains(KITTY_GRAPHICS_ACK);
+    replies.synchronized_update = sync_update_reply(&text).unwrap_or(false);
+    replies
+}
+
+/// Waits up to `timeout` for input on stdin and returns what could be read.
+#[cfg(unix)]
+fn read_input(timeout: Duration) -> Option<Vec<u8>> {
+    let mut pfd = libc::pollfd {
+        fd: STDIN_FD,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout_ms = timeout.as_millis().clamp(1, i32::MAX as u128) as i32;
+    if unsafe { libc::poll(&mut pfd, 1, timeout_ms) } <= 0 {
+        return None;
+    }
+
+    let mut buf = [0u8; 1024];
+    let n = unsafe { libc::read(STDIN_FD, buf.as_mut_ptr() as *mut _, buf.len()) };
+    if n <= 0 {
+        return None;
+    }
+    Some(buf[..n as usize].to_vec())
+}
+
+/// Reading with a timeout is only implemented for Unix.
+#[cfg(not(unix))]
+fn read_input(_timeout: Duration) -> Option<Vec<u8>> {
+    None
+}
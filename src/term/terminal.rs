@@ -0,0 +1,376 @@
+/*
+ * https://hpjansson.org/chafa/ref/chafa-ChafaTerm.html
+ */
+
+use crate::canvas::Canvas;
+use crate::term::{CHAFA_TERM_SEQ_ARGS_MAX, Info, Seq};
+use crate::{ChafaError, ffi};
+
+/// A ChafaTerm represents a terminal the application is connected to. It combines a ChafaTermInfo with input and output file descriptors, and takes care of buffering output and decoding input events.
+///
+/// The default terminal is set up for the controlling terminal of the process and is normally what an application wants.
+pub struct Terminal {
+    pub raw: *mut ffi::ChafaTerm,
+}
+
+impl Terminal {
+    /// Creates a new ChafaTerm from a ChafaTermInfo and a set of file descriptors. Any descriptor can be -1 to leave it unused.
+    /// # Parameters:
+    /// --- `term_info`: Terminal capabilities to use;
+    /// --- `in_fd`: File descriptor to read input from;
+    /// --- `out_fd`: File descriptor to write output to;
+    /// --- `err_fd`: File descriptor to write errors to.
+    pub fn new(term_info: &Info, in_fd: i32, out_fd: i32, err_fd: i32) -> Result<Self, ChafaError> {
+        let raw = unsafe { ffi::chafa_term_new(term_info.raw, in_fd, out_fd, err_fd) };
+        if raw.is_null() {
+            Err(ChafaError::Failed("Chafa -> Failed to create Terminal"))
+        } else {
+            Ok(Self { raw })
+        }
+    }
+
+    /// Gets the default ChafaTerm, which is connected to stdin, stdout and stderr and uses detected terminal capabilities. It is created on first use.
+    pub fn get_default() -> Result<Self, ChafaError> {
+        let raw = unsafe { ffi::chafa_term_get_default() };
+        if raw.is_null() {
+            Err(ChafaError::Failed(
+                "Chafa -> Failed to retrieve default Terminal",
+            ))
+        } else {
+            // The default terminal belongs to chafa; keep our own reference.
+            unsafe {
+                ffi::chafa_term_ref(raw);
+            }
+            Ok(Self { raw })
+        }
+    }
+
+    /// Gets the ChafaTermInfo describing the terminal's capabilities.
+    pub fn get_term_info(&self) -> Result<Info, ChafaError> {
+        let raw = unsafe { ffi::chafa_term_get_term_info(self.raw) };
+        if raw.is_null() {
+            Err(ChafaError::Failed("Chafa -> Failed to retrieve term info"))
+        } else {
+            unsafe {
+                ffi::chafa_term_info_ref(raw);
+            }
+            Ok(Info::from_raw(raw))
+        }
+    }
+
+    /// Queues data for writing to the terminal. The data may be buffered until `flush()` is called or the buffer fills up.
+    pub fn write_bytes(&self, data: &[u8]) -> Result<(), ChafaError> {
+        let ok = unsafe {
+            ffi::chafa_term_write(
+                self.raw,
+                data.as_ptr() as *const _,
+                data.len().min(i32::MAX as usize) as _,
+            )
+        };
+        if ok == 0 {
+            Err(ChafaError::Failed("Chafa -> Failed to write to terminal"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Formats seq with args using the terminal's ChafaTermInfo and queues it for writing.
+    pub fn write_seq(&self, seq: Seq, args: &[u32]) -> Result<(), ChafaError> {
+        let info = self.get_term_info()?;
+        let mut out: Vec<u8> = Vec::new();
+        info.emit_seq(seq, args, &mut out)?;
+        self.write_bytes(&out)
+    }
+
+    /// Saves the cursor position, so it can be returned to with `restore_cursor()`.
+    /// # Returns:
+    /// `false` if the terminal has no sequence for this, in which case nothing is written.
+    pub fn save_cursor(&self) -> Result<bool, ChafaError> {
+        self.write_emitted(|info, out| info.emit_save_cursor_pos(out))
+    }
+
+    /// Moves the cursor back to the position stored by `save_cursor()`.
+    /// # Returns:
+    /// `false` if the terminal has no sequence for this, in which case nothing is written.
+    pub fn restore_cursor(&self) -> Result<bool, ChafaError> {
+        self.write_emitted(|info, out| info.emit_restore_cursor_pos(out))
+    }
+
+    /// Hides the cursor, e.g. while an animation is playing.
+    /// # Returns:
+    /// `false` if the terminal has no sequence for this, in which case nothing is written.
+    pub fn hide_cursor(&self) -> Result<bool, ChafaError> {
+        self.write_emitted(|info, out| info.emit_disable_cursor(out))
+    }
+
+    /// Makes the cursor visible again after `hide_cursor()`.
+    /// # Returns:
+    /// `false` if the terminal has no sequence for this, in which case nothing is written.
+    pub fn show_cursor(&self) -> Result<bool, ChafaError> {
+        self.write_emitted(|info, out| info.emit_enable_cursor(out))
+    }
+
+    /// Switches to the alternate screen, leaving the contents of the normal screen untouched.
+    /// # Returns:
+    /// `false` if the terminal has no sequence for this, in which case nothing is written.
+    pub fn enter_alt_screen(&self) -> Result<bool, ChafaError> {
+        self.write_emitted(|info, out| info.emit_enable_alt_screen(out))
+    }
+
+    /// Switches back from the alternate screen to the normal screen.
+    /// # Returns:
+    /// `false` if the terminal has no sequence for this, in which case nothing is written.
+    pub fn leave_alt_screen(&self) -> Result<bool, ChafaError> {
+        self.write_emitted(|info, out| info.emit_disable_alt_screen(out))
+    }
+
+    /// Chooses whether sixel images scroll the screen when they extend past the bottom row (sixel scrolling, the default), or are drawn in place at the top left corner and clipped.
+    /// # Returns:
+    /// `false` if the terminal has no sequence for this, in which case nothing is written.
+    pub fn set_sixel_scrolling(&self, enabled: bool) -> Result<bool, ChafaError> {
+        self.write_emitted(|info, out| {
+            if enabled {
+                info.emit_enable_sixel_scrolling(out)
+            } else {
+                info.emit_disable_sixel_scrolling(out)
+            }
+        })
+    }
+
+    /// Chooses where the cursor ends up after a sixel image is drawn.
+    /// # Returns:
+    /// `false` if the terminal has no sequence for this, in which case nothing is written.
+    pub fn set_sixel_advance(&self, advance: SixelAdvance) -> Result<bool, ChafaError> {
+        self.write_emitted(|info, out| match advance {
+            SixelAdvance::Down => info.emit_set_sixel_advance_down(out),
+            SixelAdvance::Right => info.emit_set_sixel_advance_right(out),
+        })
+    }
+
+    /// Formats a sequence with one of the terminal's typed emitters and queues it for writing. Returns whether anything was emitted.
+    fn write_emitted(&self, emit: impl FnOnce(&Info, &mut Vec<u8>)) -> Result<bool, ChafaError> {
+        let info = self.get_term_info()?;
+        let mut out: Vec<u8> = Vec::new();
+        emit(&info, &mut out);
+        if out.is_empty() {
+            return Ok(false);
+        }
+        self.write_bytes(&out)?;
+        Ok(true)
+    }
+
+    /// Formats canvas for the terminal and queues the result for writing.
+    pub fn write_canvas(&self, canvas: &Canvas) -> Result<(), ChafaError> {
+        let info = self.get_term_info()?;
+        let out = canvas.create_bytes(Some(&info))?;
+        self.write_bytes(&out)
+    }
+
+    /// Writes any buffered output to the terminal.
+    pub fn flush_output(&self) -> Result<(), ChafaError> {
+        if unsafe { ffi::chafa_term_flush(self.raw) } == 0 {
+            Err(ChafaError::Failed(
+                "Chafa -> Failed to flush terminal output",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Waits for an input event from the terminal. Events of types this crate doesn't know, sequences it doesn't know (from a chafa library newer than its headers) and characters that aren't valid Unicode scalar values are skipped.
+    /// # Parameters:
+    /// --- `timeout`: Maximum time to wait, or `None` to wait indefinitely;
+    /// # Returns:
+    /// The next event, or `None` if the timeout expired.
+    pub fn read_event(&self, timeout: Option<std::time::Duration>) -> Option<Event> {
+        let deadline = timeout.map(|t| std::time::Instant::now() + t);
+        loop {
+            let timeout_ms = match deadline {
+                Some(deadline) => deadline
+                    .saturating_duration_since(std::time::Instant::now())
+                    .as_millis()
+                    .min(i32::MAX as u128) as i32,
+                None => -1,
+            };
+            let event = unsafe { ffi::chafa_term_read_event(self.raw, timeout_ms as _) };
+            if event.is_null() {
+                return None;
+            }
+
+            let ev = unsafe {
+                match ffi::chafa_event_get_type(event) {
+                    ffi::ChafaEventType_CHAFA_EVENT_EOF => Some(Event::Eof),
+                    ffi::ChafaEventType_CHAFA_EVENT_UNICHAR => {
+                        char::from_u32(ffi::chafa_event_get_unichar(event)).map(Event::Char)
+                    }
+                    ffi::ChafaEventType_CHAFA_EVENT_SEQ => {
+                        Seq::from_raw(ffi::chafa_event_get_seq(event)).map(|seq| {
+                            let n_args = seq.n_args().unwrap_or(CHAFA_TERM_SEQ_ARGS_MAX as usize);
+                            let mut args: Vec<u32> = Vec::new();
+                            for i in 0..n_args {
+                                let arg = ffi::chafa_event_get_seq_arg(event, i as i32);
+                                if arg < 0 {
+                                    break;
+                                }
+                                args.push(arg as u32);
+                            }
+                            Event::Seq(seq, args)
+                        })
+                    }
+                    _ => None,
+                }
+            };
+            unsafe {
+                ffi::chafa_event_unref(event);
+            }
+            if ev.is_some() {
+                return ev;
+            }
+        }
+    }
+
+    /// Gets the maximum number of bytes that will be buffered before output is flushed automatically.
+    pub fn get_buffer_max(&self) -> i32 {
+        unsafe { ffi::chafa_term_get_buffer_max(self.raw) }
+    }
+
+    /// Sets the maximum number of bytes that will be buffered before output is flushed automatically.
+    pub fn set_buffer_max(&self, max: i32) {
+        unsafe {
+            ffi::chafa_term_set_buffer_max(self.raw, max);
+        }
+    }
+
+    /// Returns a tuple containing the terminal's width and height in pixels, or -1 for unknown dimensions.
+    pub fn get_size_px(&self) -> (i32, i32) {
+        let mut width: i32 = -1;
+        let mut height: i32 = -1;
+
+        unsafe {
+            ffi::chafa_term_get_size_px(self.raw, &mut width, &mut height);
+        }
+        (width, height)
+    }
+
+    /// Returns a tuple containing the terminal's width and height in character cells, or -1 for unknown dimensions.
+    pub fn get_size_cells(&self) -> (i32, i32) {
+        let mut width: i32 = -1;
+        let mut height: i32 = -1;
+
+        unsafe {
+            ffi::chafa_term_get_size_cells(self.raw, &mut width, &mut height);
+        }
+
+        // Chafa can't query the size of a Windows console.
+        #[cfg(windows)]
+        if (width < 0 || height < 0)
+            && let Some((w, h)) = crate::term::console_size_cells()
+        {
+            (width, height) = (w, h);
+        }
+
+        (width, height)
+    }
+
+    /// Returns the size of a character cell in pixels, worked out from the terminal's size in pixels and in cells, or `None` if either is unknown.
+    pub fn get_cell_size_px(&self) -> Option<(i32, i32)> {
+        let (width_px, height_px) = self.get_size_px();
+        let (width, height) = self.get_size_cells();
+        if width_px <= 0 || height_px <= 0 || width <= 0 || height <= 0 {
+            return None;
+        }
+        Some((width_px / width, height_px / height))
+    }
+
+    /// Returns the font's width divided by its height, measured from the terminal's cell size, or `None` if that is unknown. This is the font_ratio to pass to `calc_canvas_geometry()`.
+    pub fn get_font_ratio(&self) -> Option<f32> {
+        let (width_px, height_px) = self.get_size_px();
+        let (width, height) = self.get_size_cells();
+        if width_px <= 0 || height_px <= 0 || width <= 0 || height <= 0 {
+            return None;
+        }
+        // Divide whole sizes rather than rounded cell sizes, for precision.
+        Some((width_px as f32 / width as f32) / (height_px as f32 / height as f32))
+    }
+
+    /// Sends queries to the terminal and waits up to `timeout` for the replies, updating the terminal's known size and colors.
+    pub fn sync_probe(&self, timeout: std::time::Duration) {
+        unsafe {
+            ffi::chafa_term_sync_probe(self.raw, timeout.as_millis().min(i32::MAX as u128) as i32);
+        }
+    }
+
+    /// Gets the terminal's default foreground color as packed 8bpc RGB, i.e. 0x00RRGGBB hex, or `None` if unknown.
+    pub fn get_default_fg_color(&self) -> Option<u32> {
+        let color = unsafe { ffi::chafa_term_get_default_fg_color(self.raw) };
+        if color < 0 { None } else { Some(color as u32) }
+    }
+
+    /// Gets the terminal's default background color as packed 8bpc RGB, i.e. 0x00RRGGBB hex, or `None` if unknown.
+    pub fn get_default_bg_color(&self) -> Option<u32> {
+        let color = unsafe { ffi::chafa_term_get_default_bg_color(self.raw) };
+        if color < 0 { None } else { Some(color as u32) }
+    }
+}
+
+/// How long `detect_font_ratio()` waits for the terminal to report its size in pixels.
+const FONT_RATIO_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Measures the font ratio of the controlling terminal: the width of a character cell divided by its height. Images drawn with the typical 0.5 come out squashed or stretched on fonts with other proportions.
+///
+/// If the terminal's pixel size isn't known yet, it is queried, waiting briefly for the reply. A ratio in the CHAFA_FONT_RATIO environment variable takes precedence over the measured one, for terminals that report wrong sizes.
+/// # Returns:
+/// The font ratio, or `None` if the terminal doesn't report its size in pixels.
+pub fn detect_font_ratio() -> Option<f32> {
+    if let Some(font_ratio) = crate::misc::env_font_ratio() {
+        return Some(font_ratio);
+    }
+    let term = Terminal::get_default().ok()?;
+    term.get_font_ratio().or_else(|| {
+        term.sync_probe(FONT_RATIO_PROBE_TIMEOUT);
+        term.get_font_ratio()
+    })
+}
+
+impl std::io::Write for Terminal {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(i32::MAX as usize);
+        self.write_bytes(&buf[..len])
+            .map_err(std::io::Error::other)?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_output().map_err(std::io::Error::other)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if !self.raw.is_null() {
+            unsafe {
+                ffi::chafa_term_unref(self.raw);
+            }
+        }
+    }
+}
+
+/// Cursor placement after a sixel image has been drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SixelAdvance {
+    /// The cursor moves to the line below the image.
+    Down,
+    /// The cursor stays on the image's last text row, to the right of it.
+    Right,
+}
+
+/// An input event read from a terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The input was closed.
+    Eof,
+    /// A character that is not part of a control sequence.
+    Char(char),
+    /// A control sequence, along with its parsed arguments.
+    Seq(Seq, Vec<u32>),
+}
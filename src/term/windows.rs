@@ -0,0 +1,51 @@
+/*
+ * Windows console support. ConPTY and Windows Terminal understand the same
+ * control sequences as xterm once VT processing is enabled on the console.
+ */
+
+use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+use windows_sys::Win32::System::Console::{
+    CONSOLE_SCREEN_BUFFER_INFO, ENABLE_VIRTUAL_TERMINAL_PROCESSING, GetConsoleMode,
+    GetConsoleScreenBufferInfo, GetStdHandle, STD_OUTPUT_HANDLE, SetConsoleMode,
+};
+
+/// Enables virtual terminal processing on the console attached to stdout, so control sequences are interpreted instead of printed.
+/// # Returns:
+/// `true` if VT processing is enabled, `false` if stdout isn't a console or the console doesn't support it (Windows 10 before 1511).
+pub fn enable_vt_processing() -> bool {
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+            return false;
+        }
+
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            return false;
+        }
+        if mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0 {
+            return true;
+        }
+        SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+    }
+}
+
+/// Gets the width and height in character cells of the visible window of the console attached to stdout.
+pub fn console_size_cells() -> Option<(i32, i32)> {
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+            return None;
+        }
+
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = std::mem::zeroed();
+        if GetConsoleScreenBufferInfo(handle, &mut info) == 0 {
+            return None;
+        }
+        let window = info.srWindow;
+        Some((
+            (window.Right - window.Left + 1) as i32,
+            (window.Bottom - window.Top + 1) as i32,
+        ))
+    }
+}
@@ -0,0 +1,112 @@
+use crate::canvas::{Canvas, CanvasMode};
+use crate::term::Info;
+use ::termion::color::{self, AnsiValue, Rgb};
+use ::termion::cursor::Goto;
+use std::io::Write;
+
+/// Writes a canvas with its top left corner at goto, as `Canvas::print_at()` does. termion counts cells from 1, chafa from 0; this converts between them.
+/// # Parameters:
+/// --- `canvas`: Canvas to write;
+/// --- `goto`: Cell of the top left corner;
+/// --- `term_info`: Terminal to format for. Must be able to emit CHAFA_TERM_SEQ_CURSOR_TO_POS;
+/// --- `writer`: Destination of the output, e.g. termion's raw stdout;
+pub fn write_at<W: Write>(
+    canvas: &Canvas,
+    goto: Goto,
+    term_info: &Info,
+    writer: &mut W,
+) -> std::io::Result<()> {
+    let (x, y) = from_goto(goto);
+    canvas.print_at(x, y, term_info, writer)
+}
+
+/// Writes the symbols and colors of a canvas in CHAFA_PIXEL_MODE_SYMBOLS with termion's own sequences, with its top left corner at goto. Unlike `write_at()`, this needs no term info. Colors follow the canvas mode: 24-bit in CHAFA_CANVAS_MODE_TRUECOLOR and palette indices otherwise, with transparent cells and the default color pens of the FGBG modes in the terminal's default colors.
+/// # Parameters:
+/// --- `canvas`: Canvas to write;
+/// --- `goto`: Cell of the top left corner;
+/// --- `writer`: Destination of the output;
+pub fn write_cells<W: Write>(canvas: &Canvas, goto: Goto, writer: &mut W) -> std::io::Result<()> {
+    let config = canvas.config().map_err(std::io::Error::other)?;
+    let (columns, rows) = config.get_geometry();
+    let truecolor = config.get_canvas_mode() == CanvasMode::TrueColor;
+    let (left, top) = from_goto(goto);
+    for y in 0..rows {
+        write!(writer, "{}", to_goto(left, top + y))?;
+        for x in 0..columns {
+            let c = canvas.get_char_at(x, y);
+            // The right half of a wide character holds 0.
+            if c == '\0' {
+                continue;
+            }
+            if truecolor {
+                let (fg, bg) = colors_at(canvas, x, y);
+                write_colors(writer, fg, bg)?;
+            } else {
+                let (fg, bg) = canvas.get_raw_colors_at(x, y);
+                write_colors(writer, ansi(fg), ansi(bg))?;
+            }
+            write!(writer, "{}", c)?;
+        }
+    }
+    write!(
+        writer,
+        "{}{}",
+        color::Fg(color::Reset),
+        color::Bg(color::Reset)
+    )?;
+    writer.flush()
+}
+
+/// Writes a foreground and background color, resetting to the terminal's default for `None`.
+fn write_colors<W: Write, C: color::Color>(
+    writer: &mut W,
+    fg: Option<C>,
+    bg: Option<C>,
+) -> std::io::Result<()> {
+    match fg {
+        Some(fg) => write!(writer, "{}", color::Fg(fg))?,
+        None => write!(writer, "{}", color::Fg(color::Reset))?,
+    }
+    match bg {
+        Some(bg) => write!(writer, "{}", color::Bg(bg))?,
+        None => write!(writer, "{}", color::Bg(color::Reset))?,
+    }
+    Ok(())
+}
+
+/// Converts a packed 0x00RRGGBB color, as returned by `Canvas::get_colors_at()`, to a termion color. Transparency (-1) gives `None`.
+pub fn rgb(color: i32) -> Option<Rgb> {
+    (color >= 0).then_some(Rgb((color >> 16) as u8, (color >> 8) as u8, color as u8))
+}
+
+/// Converts a pen, as returned by `Canvas::get_raw_colors_at()` in indexed modes, to a termion color. Transparency (-1) and the pens beyond the palette that stand for the default colors give `None`.
+pub fn ansi(pen: i32) -> Option<AnsiValue> {
+    u8::try_from(pen).ok().map(AnsiValue)
+}
+
+/// Gets the foreground and background colors at cell (x, y) of a canvas as termion colors. The coordinates are zero-indexed.
+pub fn colors_at(canvas: &Canvas, x: i32, y: i32) -> (Option<Rgb>, Option<Rgb>) {
+    let (fg, bg) = canvas.get_colors_at(x, y);
+    (rgb(fg), rgb(bg))
+}
+
+/// Converts a zero-indexed cell, as chafa counts, to a termion `Goto`.
+pub fn to_goto(x: i32, y: i32) -> Goto {
+    let one_based = |n: i32| n.clamp(0, i32::from(u16::MAX) - 1) as u16 + 1;
+    Goto(one_based(x), one_based(y))
+}
+
+/// Converts a termion `Goto` to a zero-indexed cell, as chafa counts.
+pub fn from_goto(goto: Goto) -> (i32, i32) {
+    (i32::from(goto.0.max(1)) - 1, i32::from(goto.1.max(1)) - 1)
+}
+
+/// Gets the size of a character cell in pixels from termion's view of the terminal, for `Config::set_cell_geometry()`. `None` if the terminal doesn't report its size in pixels.
+pub fn cell_size_px() -> Option<(i32, i32)> {
+    let (columns, rows) = ::termion::terminal_size().ok()?;
+    let (width, height) = ::termion::terminal_size_pixels().ok()?;
+    if columns == 0 || rows == 0 || width == 0 || height == 0 {
+        return None;
+    }
+    Some((i32::from(width / columns), i32::from(height / rows)))
+}
@@ -0,0 +1,97 @@
+use crate::{CHAFA_MAJOR_VERSION, CHAFA_MICRO_VERSION, CHAFA_MINOR_VERSION, ChafaError};
+
+/// A chafa release that added API this crate wraps. Levels are ordered, so `ApiLevel::V1_14 < ApiLevel::V1_16`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiLevel {
+    /// ChafaFrame, ChafaImage and ChafaPlacement.
+    V1_14,
+    /// ChafaTerm and terminal probing.
+    V1_16,
+}
+
+impl ApiLevel {
+    /// Every level, oldest first.
+    pub const ALL: [ApiLevel; 2] = [ApiLevel::V1_14, ApiLevel::V1_16];
+
+    /// Returns the (major, minor) version of the release.
+    pub fn version(&self) -> (u32, u32) {
+        match self {
+            ApiLevel::V1_14 => (1, 14),
+            ApiLevel::V1_16 => (1, 16),
+        }
+    }
+
+    /// A function first exported in this release, looked up to tell whether the library has it.
+    fn symbol(&self) -> &'static std::ffi::CStr {
+        match self {
+            ApiLevel::V1_14 => c"chafa_placement_new",
+            ApiLevel::V1_16 => c"chafa_term_sync_probe",
+        }
+    }
+}
+
+/// Checks if the chafa library loaded at runtime provides the API added in level. It may be older than the headers the bindings were generated from, if it was swapped after building.
+///
+/// On platforms where the loaded library can't be inspected, this compares against the headers instead.
+pub fn has_api(level: ApiLevel) -> bool {
+    #[cfg(unix)]
+    {
+        !unsafe { libc::dlsym(libc::RTLD_DEFAULT, level.symbol().as_ptr()) }.is_null()
+    }
+    #[cfg(not(unix))]
+    {
+        level.version() <= (CHAFA_MAJOR_VERSION, CHAFA_MINOR_VERSION)
+    }
+}
+
+/// Gets the newest `ApiLevel` the chafa library loaded at runtime provides, or None if it predates all of them.
+///
+/// Chafa has no call to report its version, so this is all that can be told about the loaded library: a level only says the library is at least that release, not which one it is. `CHAFA_MAJOR_VERSION` and friends give the version of the headers the bindings were generated from, which may differ.
+pub fn runtime_api_level() -> Option<ApiLevel> {
+    ApiLevel::ALL
+        .iter()
+        .rev()
+        .copied()
+        .find(|level| has_api(*level))
+}
+
+/// What `assert_abi_compatible()` could verify about the loaded library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbiCheck {
+    /// The library has the API of every `ApiLevel` up to the headers' version.
+    Compatible,
+    /// The library has the API of every `ApiLevel` that could be checked, but that doesn't cover the headers: they are newer than any level this crate knows, or the platform can't inspect the loaded library. Missing API can't be ruled out.
+    Unknown,
+}
+
+/// Checks that the chafa library loaded at runtime has the API of the headers the bindings were generated from. Calling into a library that lacks some of it misbehaves or aborts, so applications can call this at startup and report the error instead.
+///
+/// Only the `ApiLevel`s up to the headers' major and minor version are probed, so API added in releases newer than the last level can't be checked; the result is then `AbiCheck::Unknown` rather than `Compatible`. It is always `Unknown` where the loaded library can't be inspected, e.g. on Windows.
+pub fn assert_abi_compatible() -> Result<AbiCheck, ChafaError> {
+    if cfg!(not(unix)) {
+        return Ok(AbiCheck::Unknown);
+    }
+    let headers = (
+        CHAFA_MAJOR_VERSION,
+        CHAFA_MINOR_VERSION,
+        CHAFA_MICRO_VERSION,
+    );
+    let covered = ApiLevel::ALL
+        .iter()
+        .copied()
+        .filter(|level| level.version() <= (headers.0, headers.1));
+    for level in covered {
+        if !has_api(level) {
+            return Err(ChafaError::VersionMismatch {
+                missing: level,
+                headers,
+            });
+        }
+    }
+    let newest = ApiLevel::ALL[ApiLevel::ALL.len() - 1].version();
+    if (headers.0, headers.1) > newest {
+        Ok(AbiCheck::Unknown)
+    } else {
+        Ok(AbiCheck::Compatible)
+    }
+}
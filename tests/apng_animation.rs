@@ -0,0 +1,52 @@
+#![cfg(feature = "apng")]
+
+#[cfg(test)]
+mod tests {
+    use libchafa::Animation;
+    use png::{BitDepth, BlendOp, ColorType, DisposeOp, Encoder};
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    #[test]
+    fn frames_are_blended_and_disposed() {
+        let mut data = Vec::new();
+        {
+            let mut encoder = Encoder::new(&mut data, 2, 2);
+            encoder.set_color(ColorType::Rgba);
+            encoder.set_depth(BitDepth::Eight);
+            encoder.set_animated(3, 0).unwrap();
+            let mut writer = encoder.write_header().unwrap();
+
+            writer.set_frame_delay(1, 10).unwrap();
+            writer.set_dispose_op(DisposeOp::None).unwrap();
+            writer
+                .write_image_data(&[255, 0, 0, 255].repeat(4))
+                .unwrap();
+
+            // Half transparent blue over red, restored once shown.
+            writer.set_frame_dimension(1, 1).unwrap();
+            writer.set_frame_position(1, 1).unwrap();
+            writer.set_blend_op(BlendOp::Over).unwrap();
+            writer.set_dispose_op(DisposeOp::Previous).unwrap();
+            writer.set_frame_delay(0, 0).unwrap();
+            writer.write_image_data(&[0, 0, 255, 128]).unwrap();
+
+            writer.set_frame_position(0, 0).unwrap();
+            writer.set_blend_op(BlendOp::Source).unwrap();
+            writer.set_dispose_op(DisposeOp::None).unwrap();
+            writer.write_image_data(&[0, 0, 0, 0]).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let animation = Animation::from_apng(Cursor::new(data)).unwrap();
+        assert_eq!(animation.len(), 3);
+        assert_eq!(animation.loop_count(), None);
+
+        let frames = animation.frames();
+        assert_eq!(&frames[1].pixels[12..16], &[127, 0, 128, 255]);
+        assert_eq!(&frames[2].pixels[12..16], &[255, 0, 0, 255]);
+        assert_eq!(&frames[2].pixels[0..4], &[0, 0, 0, 0]);
+        assert_eq!(frames[0].delay, Duration::from_millis(100));
+        assert_eq!(frames[1].delay, Duration::ZERO);
+    }
+}
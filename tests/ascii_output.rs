@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::PixelType;
+    use libchafa::canvas::{Canvas, Config, Optimizations, is_ascii_sgr_only};
+    use libchafa::term::Db;
+
+    #[test]
+    fn accepts_text_and_sgr() {
+        assert!(is_ascii_sgr_only(b"\x1b[31;40m#:\x1b[0m\n. \x1b[m"));
+    }
+
+    #[test]
+    fn rejects_other_output() {
+        assert!(!is_ascii_sgr_only("▀".as_bytes()));
+        assert!(!is_ascii_sgr_only(b"\x1b[?25l"));
+        assert!(!is_ascii_sgr_only(b"\x1b[2J"));
+        assert!(!is_ascii_sgr_only(b"\x1b[31"));
+        assert!(!is_ascii_sgr_only(b"\x07"));
+    }
+
+    #[test]
+    fn rejects_repeated_cells() {
+        assert!(!is_ascii_sgr_only(b"#\x1b[79b"));
+    }
+
+    #[test]
+    fn long_uniform_rows_are_not_compressed() {
+        let config = Config::new_ascii().unwrap();
+        assert_eq!(config.get_optimizations(), Optimizations::None);
+        config.set_geometry(80, 2).unwrap();
+        let canvas = Canvas::new(&config).unwrap();
+        canvas
+            .set_pixels(
+                &[0x40; 160 * 8 * 4],
+                PixelType::RGBA8Unassociated,
+                160,
+                8,
+                160 * 4,
+            )
+            .unwrap();
+        // xterm can repeat cells, so this would use REP if it were enabled.
+        let db = Db::default().unwrap();
+        let xterm = db.lookup("xterm").unwrap();
+        let out = canvas.create_string(Some(xterm)).unwrap();
+        assert!(is_ascii_sgr_only(out.as_bytes()));
+    }
+}
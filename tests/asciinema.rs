@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::export::asciinema::Cast;
+    use std::time::Duration;
+
+    #[test]
+    fn cast_header_and_events() {
+        let mut cast = Cast::new(80, 24);
+        cast.term = Some(String::from("xterm-256color"));
+        cast.home_cursor = false;
+        cast.push_frame(Duration::from_millis(500), "b\n\"c\"");
+        cast.push_frame(Duration::ZERO, "\x1b[0ma");
+
+        let out = cast.to_cast_string();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "{\"version\": 2, \"width\": 80, \"height\": 24, \"env\": {\"TERM\": \"xterm-256color\"}}"
+        );
+        assert_eq!(lines[1], "[0.000000, \"o\", \"\\u001b[0ma\"]");
+        assert_eq!(lines[2], "[0.500000, \"o\", \"b\\r\\n\\\"c\\\"\"]");
+    }
+
+    #[test]
+    fn rows_are_separated_by_crlf() {
+        let mut cast = Cast::new(4, 3);
+        cast.push_frame(Duration::ZERO, "ab\ncd\r\nef");
+
+        let out = cast.to_cast_string();
+        assert_eq!(
+            out.lines().nth(1),
+            Some("[0.000000, \"o\", \"\\u001b[Hab\\r\\ncd\\r\\nef\"]")
+        );
+    }
+}
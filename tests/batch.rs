@@ -0,0 +1,56 @@
+#![cfg(feature = "rayon")]
+
+#[cfg(test)]
+mod tests {
+    use libchafa::canvas::Config;
+    use libchafa::{ChafaError, PixelType, RenderJob, render_batch};
+
+    fn job(config: &Config, shade: u8) -> Result<RenderJob, ChafaError> {
+        RenderJob::new(
+            config,
+            vec![shade; 8 * 8 * 4],
+            PixelType::RGBA8Unassociated,
+            8,
+            8,
+            32,
+        )
+    }
+
+    #[test]
+    fn outputs_keep_the_order_of_items() {
+        let config = Config::new().unwrap();
+        config.set_geometry(4, 2).unwrap();
+        let shades: Vec<u8> = (0..20).map(|i| i * 12).collect();
+        let expected: Vec<String> = shades
+            .iter()
+            .map(|shade| job(&config, *shade).unwrap().render().unwrap())
+            .collect();
+
+        for max_in_flight in [0, 1, 3, 64] {
+            let outputs = render_batch(
+                shades.iter().map(|shade| job(&config, *shade)),
+                max_in_flight,
+            );
+            let outputs: Vec<String> = outputs.into_iter().map(Result::unwrap).collect();
+            assert_eq!(outputs, expected);
+        }
+    }
+
+    #[test]
+    fn errors_stay_with_their_item() {
+        let config = Config::new().unwrap();
+        let short = RenderJob::new(&config, vec![0; 3], PixelType::RGBA8Unassociated, 8, 8, 32);
+        assert!(short.is_err());
+
+        let outputs = render_batch([job(&config, 0), short, job(&config, 0xff)], 2);
+        assert_eq!(outputs.len(), 3);
+        assert!(outputs[0].is_ok());
+        assert!(matches!(outputs[1], Err(ChafaError::InvalidInput(_))));
+        assert!(outputs[2].is_ok());
+    }
+
+    #[test]
+    fn no_items_give_no_outputs() {
+        assert!(render_batch(std::iter::empty(), 4).is_empty());
+    }
+}
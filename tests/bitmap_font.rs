@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::bitmap_font::BitmapFont;
+
+    const BDF: &str = "STARTFONT 2.1
+FONTBOUNDINGBOX 4 4 0 -1
+CHARS 2
+STARTCHAR A
+ENCODING 65
+BBX 2 2 1 0
+BITMAP
+C0
+40
+ENDCHAR
+STARTCHAR unmapped
+ENCODING -1
+BBX 4 4 0 -1
+BITMAP
+F0
+F0
+F0
+F0
+ENDCHAR
+ENDFONT
+";
+
+    fn alpha(glyph: &libchafa::Glyph) -> Vec<u8> {
+        glyph.as_slice().chunks(4).map(|px| px[3] / 0xff).collect()
+    }
+
+    #[test]
+    fn bdf_glyphs_are_placed_in_the_cell() {
+        let font = BitmapFont::parse_bdf(BDF.as_bytes()).unwrap();
+        assert_eq!(font.cell_size(), (4, 4));
+        assert_eq!(font.glyphs().count(), 1);
+
+        // The 2x2 glyph sits one pixel right, on the baseline one row above the bottom.
+        let glyph = font.get('A').unwrap();
+        assert_eq!(
+            alpha(glyph),
+            [0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn preview_draws_blocks() {
+        let font = BitmapFont::parse_bdf(BDF.as_bytes()).unwrap();
+        let preview = font
+            .get('A')
+            .unwrap()
+            .preview(libchafa::PixelType::RGBA8Unassociated);
+        assert_eq!(preview, "        \n  ████  \n    ██  \n        \n");
+    }
+
+    #[test]
+    fn psf2_uses_unicode_table() {
+        let mut data: Vec<u8> = Vec::new();
+        for field in [0x864ab572u32, 0, 32, 1, 2, 2, 2, 8] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(&[0x80, 0x00, 0x00, 0x80]);
+        data.extend_from_slice("é".as_bytes());
+        data.push(0xff);
+        data.extend_from_slice(b"x\xfexy\xff");
+
+        let font = BitmapFont::from_bytes(&data).unwrap();
+        assert_eq!(font.cell_size(), (8, 2));
+        assert_eq!(font.glyphs().map(|(c, _)| c).collect::<String>(), "éx");
+        assert_eq!(alpha(font.get('é').unwrap())[0], 1);
+        assert_eq!(alpha(font.get('x').unwrap())[8], 1);
+    }
+
+    #[test]
+    fn psf1_without_table_maps_latin1() {
+        let mut data = vec![0x36, 0x04, 0x00, 1];
+        data.extend((0..256).map(|i| i as u8));
+
+        let font = BitmapFont::parse_psf(&data).unwrap();
+        assert_eq!(font.glyphs().count(), 256);
+        assert_eq!(alpha(font.get('\u{80}').unwrap())[0], 1);
+    }
+
+    #[test]
+    fn truncated_psf_is_rejected() {
+        assert!(BitmapFont::parse_psf(&[0x36, 0x04, 0x00, 16, 0x00]).is_err());
+    }
+}
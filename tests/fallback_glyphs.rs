@@ -0,0 +1,59 @@
+#![cfg(feature = "fallback-glyphs")]
+
+#[cfg(test)]
+mod tests {
+    use libchafa::{PixelType, SYMBOL_HEIGHT_PIXELS, SymbolMap, SymbolTags, fallback};
+
+    #[test]
+    fn upper_half_is_inked_on_top() {
+        let glyph = fallback::glyph('▀').unwrap();
+        let coverage = glyph.coverage(PixelType::RGBA8Unassociated);
+        let half = coverage.len() / 2;
+        assert!(coverage[..half].iter().all(|v| *v == 0xff));
+        assert!(coverage[half..].iter().all(|v| *v == 0));
+        assert_eq!(glyph.height(), SYMBOL_HEIGHT_PIXELS as i32);
+    }
+
+    #[test]
+    fn covers_blocks_and_sextants_only() {
+        assert!(fallback::glyph('▒').is_none());
+        assert!(fallback::glyph('a').is_none());
+        assert!(fallback::glyph('\u{1fb00}').is_some());
+        assert_eq!(fallback::chars().count(), 29 + 60);
+    }
+
+    #[test]
+    fn builtin_glyphs_are_kept() {
+        let map = SymbolMap::new().unwrap();
+        map.add_by_tags(SymbolTags::Block);
+        map.import_fallback_glyphs(SymbolTags::Block).unwrap();
+        assert!(
+            !map.imported_code_points().contains(&0x2580),
+            "chafa's own upper half block has the same shape"
+        );
+    }
+
+    #[test]
+    fn adding_tags_imports_nothing() {
+        let map = SymbolMap::new().unwrap();
+        map.set_allow_builtin_glyphs(false);
+        map.add_by_tags(SymbolTags::Vhalf);
+        assert!(map.imported_code_points().is_empty());
+    }
+
+    #[test]
+    fn missing_glyphs_are_imported() {
+        let map = SymbolMap::new().unwrap();
+        map.set_allow_builtin_glyphs(false);
+        map.add_by_tags(SymbolTags::Vhalf);
+        assert_eq!(map.import_fallback_glyphs(SymbolTags::Vhalf).unwrap(), 2);
+
+        let imported = map.imported_code_points();
+        assert!(imported.contains(&0x258c));
+        assert!(imported.contains(&0x2590));
+        assert!(!imported.contains(&0x2580));
+
+        // Everything needed is in place, so asking again imports nothing.
+        assert_eq!(map.import_fallback_glyphs(SymbolTags::Vhalf).unwrap(), 0);
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::features::FeatureWarning;
+    use libchafa::{FeatureReport, Features};
+
+    #[test]
+    fn report_compares_builtin_and_supported() {
+        let report = FeatureReport {
+            builtin: Features::MMX | Features::AVX2,
+            supported: Features::MMX | Features::SSE41,
+        };
+        assert!(report.active() == Features::MMX);
+        assert!(report.unsupported() == Features::AVX2);
+        assert!(report.unused() == Features::SSE41);
+        assert_eq!(
+            report.to_string(),
+            "mmx: built in, supported\n\
+             sse4.1: not built in, supported\n\
+             popcnt: not built in, not supported\n\
+             avx2: built in, not supported"
+        );
+    }
+
+    #[test]
+    fn report_warns_about_mismatches() {
+        let report = FeatureReport {
+            builtin: Features::MMX | Features::AVX2,
+            supported: Features::MMX | Features::SSE41,
+        };
+        assert_eq!(
+            report.warnings(),
+            [
+                FeatureWarning::Unsupported(Features::AVX2),
+                FeatureWarning::Unused(Features::SSE41)
+            ]
+        );
+        assert_eq!(
+            report.warnings()[0].to_string(),
+            "built with avx2 but the CPU lacks it"
+        );
+    }
+
+    #[test]
+    fn features_display_their_names() {
+        let features = Features::SSE41 | Features::AVX2;
+        assert_eq!(features.to_string(), "sse4.1 avx2");
+        assert_eq!(Features::empty().to_string(), "");
+    }
+
+    #[test]
+    fn features_debug_lists_their_flags() {
+        assert_eq!(
+            format!("{:?}", Features::SSE41 | Features::AVX2),
+            "Features(SSE41 | AVX2)"
+        );
+        assert_eq!(format!("{:?}", Features::empty()), "Features(empty)");
+    }
+}
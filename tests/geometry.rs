@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::geometry::{
+        CoordinateMap, FitOptions, GeometrySuggestion, Margins, ViewportFit, fit_in_viewport,
+        pixel_resolution, scale_cell_size, suggest_geometry_for_cell,
+    };
+    use libchafa::{
+        Align, CellGeometry, FONT_RATIO_ENV, GeometryRequest, Rect, Tuck, default_font_ratio,
+        set_default_font_ratio,
+    };
+
+    #[test]
+    fn cells_map_to_image_pixels() {
+        // A 10x5 canvas of 10x20 pixel cells, printed at column 2, row 3.
+        let map = CoordinateMap::from_parts((2, 3), CellGeometry { cols: 10, rows: 5 }, (10, 20));
+        assert_eq!(map.pixel_to_cell((25, 61)), (2, 3));
+        assert_eq!(map.cell_to_pixel((2, 3)), (20, 60));
+        assert_eq!(map.canvas_cell((2, 3)), Some((0, 0)));
+        assert_eq!(map.canvas_cell((12, 3)), None);
+
+        // A square image fits a 100x100 area centered in the 100x100 canvas.
+        let area = map.image_area((400, 400), Tuck::Fit, Align::Center, Align::Center);
+        assert_eq!(area, Rect::new(0, 0, 100, 100));
+        let area = map.image_area((50, 25), Tuck::ShrinkToFit, Align::End, Align::Start);
+        assert_eq!(area, Rect::new(50, 0, 50, 25));
+
+        assert_eq!(map.cell_to_image((7, 3), (50, 25), area), Some((5, 10)));
+        assert_eq!(map.cell_to_image((2, 3), (50, 25), area), None);
+        assert_eq!(map.cell_to_image((7, 5), (50, 25), area), None);
+    }
+
+    #[test]
+    fn viewport_fit_keeps_margins_and_aligns() {
+        // A 2:1 image in a room of 20x10 cells of 10x20 pixels takes 20x5 cells.
+        let viewport = Rect::new(5, 2, 24, 14);
+        let mut options = FitOptions {
+            margins: Margins::uniform(2),
+            ..FitOptions::default()
+        };
+        let fit = fit_in_viewport((200, 100), viewport, (10, 20), &options);
+        assert_eq!(
+            fit,
+            ViewportFit {
+                geometry: CellGeometry { cols: 20, rows: 5 },
+                x: 7,
+                y: 4,
+                pixels: (200, 100),
+            }
+        );
+
+        options.valign = Align::Center;
+        assert_eq!(
+            fit_in_viewport((200, 100), viewport, (10, 20), &options).y,
+            6
+        );
+        options.valign = Align::End;
+        assert_eq!(
+            fit_in_viewport((200, 100), viewport, (10, 20), &options).y,
+            9
+        );
+    }
+
+    #[test]
+    fn viewport_fit_without_room_is_empty() {
+        let options = FitOptions {
+            margins: Margins {
+                top: 1,
+                right: 3,
+                bottom: 1,
+                left: 3,
+            },
+            ..FitOptions::default()
+        };
+        let empty = ViewportFit {
+            geometry: CellGeometry::default(),
+            x: 3,
+            y: 1,
+            pixels: (0, 0),
+        };
+        assert_eq!(
+            fit_in_viewport((200, 100), Rect::new(0, 0, 6, 10), (10, 20), &options),
+            empty
+        );
+        assert_eq!(
+            fit_in_viewport((0, 100), Rect::new(0, 0, 20, 10), (10, 20), &options),
+            empty
+        );
+    }
+
+    #[test]
+    fn cell_sizes_scale_to_device_pixels() {
+        assert_eq!(scale_cell_size((10, 20), 2.0), (20, 40));
+        assert_eq!(scale_cell_size((7, 15), 1.5), (11, 23));
+        assert_eq!(scale_cell_size((10, 20), 0.01), (1, 1));
+        assert_eq!(scale_cell_size((10, 20), 0.0), (10, 20));
+        assert_eq!(scale_cell_size((10, 20), -2.0), (10, 20));
+        assert_eq!(scale_cell_size((10, 20), f32::NAN), (10, 20));
+    }
+
+    #[test]
+    fn pixel_resolution_follows_the_cell_size() {
+        let geometry = CellGeometry { cols: 20, rows: 5 };
+        assert_eq!(pixel_resolution(geometry, (10, 20)), (200, 100));
+        assert_eq!(
+            pixel_resolution(geometry, scale_cell_size((10, 20), 2.0)),
+            (400, 200)
+        );
+        assert_eq!(pixel_resolution(geometry, (0, 20)), (0, 0));
+    }
+
+    #[test]
+    fn cells_and_pixels_round_trip() {
+        let map = CoordinateMap::from_parts((2, 3), CellGeometry { cols: 10, rows: 5 }, (10, 20));
+        for cell in [(0, 0), (2, 3), (11, 7), (-1, -2), (79, 23)] {
+            let (x, y) = map.cell_to_pixel(cell);
+            assert_eq!(map.pixel_to_cell((x, y)), cell);
+            assert_eq!(map.pixel_to_cell((x + 9, y + 19)), cell);
+        }
+    }
+
+    #[test]
+    fn canvas_cells_stop_at_the_edges() {
+        let map = CoordinateMap::from_parts((2, 3), CellGeometry { cols: 10, rows: 5 }, (10, 20));
+        assert_eq!(map.canvas_cell((11, 7)), Some((9, 4)));
+        assert_eq!(map.canvas_cell((1, 3)), None);
+        assert_eq!(map.canvas_cell((2, 2)), None);
+        assert_eq!(map.canvas_cell((11, 8)), None);
+    }
+
+    #[test]
+    fn unknown_cell_sizes_count_as_one_pixel() {
+        let map = CoordinateMap::from_parts((0, 0), CellGeometry { cols: 4, rows: 4 }, (0, -5));
+        assert_eq!(map.cell_to_pixel((3, 2)), (3, 2));
+        assert_eq!(map.pixel_to_cell((3, 2)), (3, 2));
+    }
+
+    // The only test here that reads the process-wide font ratio, so changing it can't disturb the others.
+    #[test]
+    fn default_font_ratio_follows_the_setting_then_the_env() {
+        set_default_font_ratio(0.0);
+        unsafe { std::env::remove_var(FONT_RATIO_ENV) };
+        assert_eq!(default_font_ratio(), 0.5);
+
+        unsafe { std::env::set_var(FONT_RATIO_ENV, " 0.45 ") };
+        assert_eq!(default_font_ratio(), 0.45);
+        unsafe { std::env::set_var(FONT_RATIO_ENV, "-1") };
+        assert_eq!(default_font_ratio(), 0.5);
+        unsafe { std::env::set_var(FONT_RATIO_ENV, "wide") };
+        assert_eq!(default_font_ratio(), 0.5);
+
+        unsafe { std::env::set_var(FONT_RATIO_ENV, "0.45") };
+        set_default_font_ratio(0.6);
+        assert_eq!(default_font_ratio(), 0.6);
+        set_default_font_ratio(f32::NAN);
+        assert_eq!(default_font_ratio(), 0.45);
+
+        unsafe { std::env::remove_var(FONT_RATIO_ENV) };
+        assert_eq!(default_font_ratio(), 0.5);
+    }
+
+    #[test]
+    fn suggestions_use_the_cell_aspect() {
+        // A 2:1 image in at most 20x10 cells.
+        let request = GeometryRequest {
+            src: (200, 100),
+            max_cols: Some(20),
+            max_rows: Some(10),
+            font_ratio: 0.5,
+            zoom: false,
+            stretch: false,
+        };
+        assert_eq!(
+            suggest_geometry_for_cell(&request, Some((10, 20))),
+            GeometrySuggestion {
+                geometry: CellGeometry { cols: 20, rows: 5 },
+                cell_px: Some((10, 20)),
+            }
+        );
+        assert_eq!(
+            suggest_geometry_for_cell(&request, Some((10, 10))),
+            GeometrySuggestion {
+                geometry: CellGeometry { cols: 20, rows: 10 },
+                cell_px: Some((10, 10)),
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_cells_fall_back_to_the_request() {
+        let request = GeometryRequest {
+            src: (200, 100),
+            max_cols: Some(20),
+            max_rows: Some(10),
+            font_ratio: 0.5,
+            zoom: false,
+            stretch: false,
+        };
+        let fallback = GeometrySuggestion {
+            geometry: CellGeometry { cols: 20, rows: 5 },
+            cell_px: None,
+        };
+        assert_eq!(suggest_geometry_for_cell(&request, None), fallback);
+        assert_eq!(suggest_geometry_for_cell(&request, Some((0, 20))), fallback);
+    }
+}
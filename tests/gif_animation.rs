@@ -0,0 +1,79 @@
+#![cfg(feature = "gif")]
+
+#[cfg(test)]
+mod tests {
+    use gif::{DisposalMethod, Encoder, Frame, Repeat};
+    use libchafa::{Animation, GifFrames};
+    use std::time::Duration;
+
+    // Palette: 0 = red, 1 = blue.
+    const PALETTE: [u8; 6] = [255, 0, 0, 0, 0, 255];
+
+    fn frame(
+        (left, top, width, height): (u16, u16, u16, u16),
+        pixels: Vec<u8>,
+        dispose: DisposalMethod,
+        delay: u16,
+    ) -> Frame<'static> {
+        let mut frame = Frame::from_indexed_pixels(width, height, pixels, None);
+        frame.left = left;
+        frame.top = top;
+        frame.dispose = dispose;
+        frame.delay = delay;
+        frame
+    }
+
+    fn encode(frames: &[Frame]) -> Vec<u8> {
+        let mut data = Vec::new();
+        {
+            let mut encoder = Encoder::new(&mut data, 2, 2, &PALETTE).unwrap();
+            encoder.set_repeat(Repeat::Finite(2)).unwrap();
+            for frame in frames {
+                encoder.write_frame(frame).unwrap();
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn frames_are_composited_and_disposed() {
+        let data = encode(&[
+            frame((0, 0, 2, 2), vec![0, 0, 0, 0], DisposalMethod::Keep, 5),
+            frame((1, 1, 1, 1), vec![1], DisposalMethod::Background, 0),
+            frame((0, 0, 1, 1), vec![1], DisposalMethod::Keep, 20),
+        ]);
+        let animation = Animation::from_gif(data.as_slice()).unwrap();
+
+        assert_eq!((animation.width(), animation.height()), (2, 2));
+        assert_eq!(animation.len(), 3);
+        assert_eq!(animation.loop_count(), Some(3));
+
+        let frames = animation.frames();
+        assert_eq!(&frames[1].pixels[12..16], &[0, 0, 255, 255]);
+        assert_eq!(&frames[1].pixels[0..4], &[255, 0, 0, 255]);
+        // The blue pixel was restored to the background before the last frame.
+        assert_eq!(&frames[2].pixels[12..16], &[0, 0, 0, 0]);
+        assert_eq!(&frames[2].pixels[0..4], &[0, 0, 255, 255]);
+
+        assert_eq!(frames[0].delay, Duration::from_millis(50));
+        assert_eq!(frames[1].delay, Duration::from_millis(100));
+        assert_eq!(animation.duration(), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn frames_can_be_streamed() {
+        let data = encode(&[
+            frame((0, 0, 2, 2), vec![1, 1, 1, 1], DisposalMethod::Keep, 3),
+            frame((0, 0, 1, 1), vec![0], DisposalMethod::Keep, 4),
+        ]);
+        let mut frames = GifFrames::new(data.as_slice()).unwrap();
+        assert_eq!(frames.loop_count(), Some(3));
+
+        let (first, delay) = frames.next_pixels().unwrap().unwrap();
+        assert_eq!(&first[0..4], &[0, 0, 255, 255]);
+        assert_eq!(delay, Duration::from_millis(30));
+        let (second, _) = frames.next_pixels().unwrap().unwrap();
+        assert_eq!(&second[0..8], &[255, 0, 0, 255, 0, 0, 255, 255]);
+        assert!(frames.next_pixels().unwrap().is_none());
+    }
+}
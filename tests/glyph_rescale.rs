@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::{Glyph, PixelType};
+
+    fn rgb(values: &[u8], width: i32, height: i32, rowstride: i32) -> Glyph {
+        let pixels = values.iter().flat_map(|v| [*v; 3]).collect();
+        Glyph::from_vec(pixels, PixelType::RGB8, width, height, rowstride).unwrap()
+    }
+
+    #[test]
+    fn rescaled_box_filters_down_and_repeats_up() {
+        // 4x2 RGB8: a white column on the left half.
+        let glyph = rgb(&[255, 255, 0, 0, 255, 255, 0, 0], 4, 2, 12);
+
+        let down = glyph.rescaled(PixelType::RGB8, 1, 1).unwrap();
+        assert_eq!(down.as_slice(), &[128, 128, 128]);
+
+        let up = glyph.rescaled(PixelType::RGB8, 8, 4).unwrap();
+        let coverage = up.coverage(PixelType::RGB8);
+        assert_eq!(&coverage[..8], &[255, 255, 255, 255, 0, 0, 0, 0]);
+        assert_eq!(coverage.len(), 32);
+    }
+
+    #[test]
+    fn partly_covered_pixels_are_weighted() {
+        let glyph = rgb(&[0, 90, 180], 3, 1, 9);
+        let down = glyph.rescaled(PixelType::RGB8, 2, 1).unwrap();
+        assert_eq!(down.as_slice(), &[30, 30, 30, 150, 150, 150]);
+    }
+
+    #[test]
+    fn row_padding_is_skipped() {
+        let pixels = vec![
+            10, 10, 10, 30, 30, 30, 99, 99, //
+            50, 50, 50, 70, 70, 70, 99, 99,
+        ];
+        let glyph = Glyph::from_vec(pixels, PixelType::RGB8, 2, 2, 8).unwrap();
+
+        let down = glyph.rescaled(PixelType::RGB8, 1, 1).unwrap();
+        assert_eq!(down.as_slice(), &[40, 40, 40]);
+        assert_eq!(down.rowstride(), 3);
+    }
+
+    #[test]
+    fn axes_are_scaled_independently() {
+        let glyph = rgb(&[0, 200], 2, 1, 6);
+        let scaled = glyph.rescaled(PixelType::RGB8, 1, 2).unwrap();
+        assert_eq!((scaled.width(), scaled.height()), (1, 2));
+        assert_eq!(scaled.as_slice(), &[100; 6]);
+    }
+
+    #[test]
+    fn alpha_is_filtered_like_color() {
+        let pixels = vec![255, 255, 255, 255, 255, 255, 255, 0];
+        let glyph = Glyph::from_vec(pixels, PixelType::RGBA8Unassociated, 2, 1, 8).unwrap();
+        let down = glyph.rescaled(PixelType::RGBA8Unassociated, 1, 1).unwrap();
+        assert_eq!(down.as_slice(), &[255, 255, 255, 128]);
+    }
+
+    #[test]
+    fn sizes_must_be_positive() {
+        let glyph = rgb(&[0], 1, 1, 3);
+        assert!(glyph.rescaled(PixelType::RGB8, 0, 1).is_err());
+        assert!(glyph.rescaled(PixelType::RGB8, 1, -1).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "icc")]
+
+#[cfg(test)]
+mod tests {
+    use libchafa::{Animation, ChafaError, EmbeddedProfile};
+    use std::time::Duration;
+
+    /// Encodes a 1x1 opaque white PNG, with icc_profile embedded if given.
+    #[cfg(feature = "apng")]
+    fn png(icc_profile: Option<&'static [u8]>) -> Vec<u8> {
+        let mut info = png::Info::with_size(1, 1);
+        info.color_type = png::ColorType::Rgba;
+        info.bit_depth = png::BitDepth::Eight;
+        info.icc_profile = icc_profile.map(std::borrow::Cow::Borrowed);
+
+        let mut data = Vec::new();
+        let mut writer = png::Encoder::with_info(&mut data, info)
+            .unwrap()
+            .write_header()
+            .unwrap();
+        writer.write_image_data(&[255; 4]).unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    #[test]
+    fn invalid_profile_is_rejected_and_pixels_kept() {
+        let mut animation = Animation::new(1, 1).unwrap();
+        animation
+            .push_frame(vec![10, 20, 30, 255], Duration::ZERO)
+            .unwrap();
+        assert!(animation.convert_to_srgb(b"not a profile").is_err());
+        assert_eq!(animation.frames()[0].pixels, vec![10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn animations_start_without_a_profile() {
+        let animation = Animation::new(1, 1).unwrap();
+        assert_eq!(animation.embedded_profile(), &EmbeddedProfile::None);
+    }
+
+    #[cfg(feature = "apng")]
+    #[test]
+    fn embedded_profile_status_is_reported() {
+        let plain = Animation::from_apng(std::io::Cursor::new(png(None))).unwrap();
+        assert_eq!(plain.embedded_profile(), &EmbeddedProfile::None);
+
+        let broken =
+            Animation::from_apng(std::io::Cursor::new(png(Some(b"not a profile")))).unwrap();
+        assert!(matches!(
+            broken.embedded_profile(),
+            EmbeddedProfile::Unusable(ChafaError::InvalidInput(_))
+        ));
+        assert_eq!(broken.frames()[0].pixels, vec![255; 4]);
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::{Animation, KittyAnimation};
+    use std::time::Duration;
+
+    fn commands(out: &[u8]) -> Vec<String> {
+        String::from_utf8(out.to_vec())
+            .unwrap()
+            .split("\x1b\\")
+            .filter(|c| !c.is_empty())
+            .map(|c| c.trim_start_matches("\x1b_G").to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn later_frames_are_sent_as_deltas() {
+        let mut animation = Animation::new(4, 4).unwrap();
+        let first = vec![0u8; 4 * 4 * 4];
+        let mut second = first.clone();
+        // Change pixels (1, 2) and (2, 2).
+        second[(2 * 4 + 1) * 4..(2 * 4 + 3) * 4].fill(0xff);
+        animation
+            .push_frame(first, Duration::from_millis(30))
+            .unwrap();
+        animation
+            .push_frame(second, Duration::from_millis(50))
+            .unwrap();
+        animation.set_loop_count(None);
+
+        let out = KittyAnimation::new(7, 10, 5).encode(&animation);
+        let commands = commands(&out);
+        assert_eq!(commands.len(), 4);
+        assert!(commands[0].starts_with("a=T,i=7,f=32,s=4,v=4,c=10,r=5,q=2,m=0;"));
+        assert_eq!(commands[1], "a=a,i=7,r=1,z=30,q=2");
+        assert_eq!(
+            commands[2],
+            "a=f,i=7,f=32,x=1,y=2,s=2,v=1,c=1,X=1,z=50,q=2,m=0;//////////8="
+        );
+        assert_eq!(commands[3], "a=a,i=7,s=3,v=1,q=2");
+    }
+
+    #[test]
+    fn large_payloads_are_chunked() {
+        let mut animation = Animation::new(64, 64).unwrap();
+        animation
+            .push_frame(vec![0x80; 64 * 64 * 4], Duration::from_millis(10))
+            .unwrap();
+
+        let commands = commands(&KittyAnimation::new(1, 8, 4).encode(&animation));
+        // 16384 bytes take 21848 base64 characters, so 6 chunks.
+        assert!(commands[0].contains(",m=1;"));
+        assert!(commands[1..5].iter().all(|c| c.starts_with("m=1;")));
+        assert!(commands[5].starts_with("m=0;"));
+        assert_eq!(commands.last().unwrap(), "a=a,i=1,s=3,v=2,q=2");
+    }
+}
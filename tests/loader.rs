@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::ChafaError;
+    use libchafa::loader::{self, Format};
+    use std::io::ErrorKind;
+
+    #[test]
+    fn formats_are_sniffed_from_contents() {
+        assert_eq!(Format::sniff(b"\x89PNG\r\n\x1a\n...."), Some(Format::Png));
+        assert_eq!(Format::sniff(&[0xff, 0xd8, 0xff, 0xe0]), Some(Format::Jpeg));
+        assert_eq!(Format::sniff(b"GIF89a"), Some(Format::Gif));
+        assert_eq!(Format::sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some(Format::WebP));
+        assert_eq!(Format::sniff(b"RIFF\0\0\0\0WAVEfmt "), None);
+        assert_eq!(Format::sniff(b"BM"), Some(Format::Bmp));
+        assert_eq!(Format::sniff(b""), None);
+    }
+
+    #[test]
+    fn formats_are_named_by_extension_and_mime_type() {
+        assert_eq!(Format::from_path("photo.JPG"), Some(Format::Jpeg));
+        assert_eq!(Format::from_path("anim.webp"), Some(Format::WebP));
+        assert_eq!(Format::from_path("README"), None);
+        assert_eq!(
+            Format::from_mime_type("Image/GIF; charset=binary"),
+            Some(Format::Gif)
+        );
+        assert_eq!(Format::from_mime_type("text/plain"), None);
+        assert_eq!(Format::Bmp.mime_type(), "image/bmp");
+    }
+
+    #[test]
+    fn unknown_data_is_rejected() {
+        assert!(loader::load_bytes(b"not an image").is_err());
+    }
+
+    #[test]
+    fn missing_files_are_io_errors() {
+        let err = loader::load_file("/nonexistent/image.png").err().unwrap();
+        let ChafaError::Io(ref io) = err else {
+            panic!("expected an I/O error, got {:?}", err);
+        };
+        assert_eq!(io.kind(), ErrorKind::NotFound);
+        assert!(err.to_string().contains("/nonexistent/image.png"));
+        assert!(std::error::Error::source(&err).is_some());
+        assert_eq!(err.clone(), err);
+        assert_ne!(err, ChafaError::Io(ErrorKind::NotFound.into()));
+    }
+
+    #[cfg(feature = "bmp")]
+    #[test]
+    fn bmp_decodes_to_one_frame() {
+        // 1x1, 24 bits per pixel, blue-green-red.
+        let mut data = b"BM".to_vec();
+        data.extend_from_slice(&58u32.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&54u32.to_le_bytes());
+        data.extend_from_slice(&40u32.to_le_bytes());
+        data.extend_from_slice(&1i32.to_le_bytes());
+        data.extend_from_slice(&1i32.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&24u16.to_le_bytes());
+        data.extend_from_slice(&[0; 24]);
+        data.extend_from_slice(&[0x30, 0x20, 0x10, 0x00]);
+
+        let animation = loader::load_bytes(&data).unwrap();
+        assert_eq!(animation.len(), 1);
+        assert_eq!(animation.frames()[0].pixels, vec![0x10, 0x20, 0x30, 0xff]);
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn jpeg_is_turned_upright() {
+        let image = image::RgbImage::from_pixel(4, 2, image::Rgb([200, 100, 50]));
+        let mut jpeg = Vec::new();
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+
+        // APP1 segment with a big-endian EXIF IFD holding Orientation = 6 (rotate 90° clockwise).
+        let mut exif = vec![0xff, 0xe1, 0x00, 34];
+        exif.extend_from_slice(b"Exif\0\0MM\0*\0\0\0\x08\0\x01");
+        exif.extend_from_slice(&[0x01, 0x12, 0x00, 0x03, 0, 0, 0, 1, 0x00, 0x06, 0, 0]);
+        exif.extend_from_slice(&[0; 4]);
+        jpeg.splice(2..2, exif);
+
+        let animation = loader::load_bytes(&jpeg).unwrap();
+        assert_eq!((animation.width(), animation.height()), (2, 4));
+    }
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "mmap")]
+
+#[cfg(test)]
+mod tests {
+    use libchafa::canvas::{Canvas, Config};
+    use libchafa::{Frame, Image, PixelType, Placement};
+
+    #[test]
+    fn mapped_frame_outlives_the_image_it_was_placed_with() {
+        let path = std::env::temp_dir().join(format!("libchafa-mmap-{}.raw", std::process::id()));
+        std::fs::write(&path, vec![0x80u8; 16 * 16 * 4]).unwrap();
+
+        let canvas = Canvas::new(&Config::new().unwrap()).unwrap();
+        {
+            let frame =
+                unsafe { Frame::map_file(&path, 0, PixelType::RGBA8Unassociated, 16, 16, 64) }
+                    .unwrap();
+            let image = Image::new().unwrap();
+            image.set_frame(frame);
+            // The image and frame are dropped here; the canvas must keep the map.
+            canvas.set_placement(Placement::new(image, 0).unwrap());
+        }
+        assert!(!canvas.create_string(None).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,19 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::canvas::Montage;
+
+    #[test]
+    fn geometry_counts_gaps_and_caption_rows() {
+        let pixels = vec![0u8; 2 * 2 * 4];
+        let mut montage = Montage::new(3, 10, 5).unwrap().gap(2);
+        for _ in 0..4 {
+            montage.add(&pixels, 2, 2, "").unwrap();
+        }
+        // Two rows of three tiles, without captions.
+        assert_eq!(montage.geometry(), (34, 12));
+
+        montage.add(&pixels, 2, 2, "photo.png").unwrap();
+        assert_eq!(montage.geometry(), (34, 14));
+        assert!(montage.add(&pixels, 3, 2, "").is_err());
+    }
+}
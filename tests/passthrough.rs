@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::canvas::{Passthrough, PixelMode};
+    use libchafa::term::{wrap_passthrough, zellij_pixel_mode};
+
+    #[test]
+    fn tmux_doubles_escapes() {
+        let out = wrap_passthrough(b"\x1b_Ga=q\x1b\\", Passthrough::Tmux);
+        assert_eq!(out, b"\x1bPtmux;\x1b\x1b_Ga=q\x1b\x1b\\\x1b\\");
+    }
+
+    #[test]
+    fn long_data_is_chunked() {
+        let data = vec![b'x'; 5000];
+
+        let tmux = wrap_passthrough(&data, Passthrough::Tmux);
+        assert_eq!(tmux.windows(7).filter(|w| w == b"\x1bPtmux;").count(), 2);
+
+        let screen = wrap_passthrough(&data, Passthrough::Screen);
+        assert_eq!(screen.windows(2).filter(|w| w == b"\x1bP").count(), 10);
+        assert_eq!(screen.len(), 5000 + 10 * 4);
+    }
+
+    #[test]
+    fn none_is_unchanged() {
+        assert_eq!(wrap_passthrough(b"abc", Passthrough::None), b"abc");
+    }
+
+    #[test]
+    fn zellij_falls_back_to_sixels_or_symbols() {
+        assert_eq!(zellij_pixel_mode(PixelMode::Kitty, true), PixelMode::Sixels);
+        assert_eq!(
+            zellij_pixel_mode(PixelMode::Iterm2, false),
+            PixelMode::Symbols
+        );
+        assert_eq!(
+            zellij_pixel_mode(PixelMode::Sixels, true),
+            PixelMode::Sixels
+        );
+        assert_eq!(
+            zellij_pixel_mode(PixelMode::Symbols, true),
+            PixelMode::Symbols
+        );
+    }
+}
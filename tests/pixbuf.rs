@@ -0,0 +1,35 @@
+#![cfg(feature = "gdk-pixbuf")]
+
+#[cfg(test)]
+mod tests {
+    use gdk_pixbuf::{Colorspace, Pixbuf};
+    use libchafa::Frame;
+
+    #[test]
+    fn padded_rows_with_a_short_last_row() {
+        // 3 RGB pixels take 9 bytes, but rows are 12 apart and the last one stops after its pixels.
+        let pixbuf = Pixbuf::from_mut_slice(
+            vec![0x80u8; 12 * 3 + 9],
+            Colorspace::Rgb,
+            false,
+            8,
+            3,
+            4,
+            12,
+        );
+        assert_eq!(pixbuf.rowstride(), 12);
+        let frame = Frame::from_pixbuf(&pixbuf).unwrap();
+        assert_eq!(
+            (frame.width(), frame.height(), frame.rowstride()),
+            (3, 4, 12)
+        );
+    }
+
+    #[test]
+    fn allocated_pixbufs() {
+        let pixbuf = Pixbuf::new(Colorspace::Rgb, true, 8, 5, 2).unwrap();
+        pixbuf.fill(0xff0000ff);
+        let frame = Frame::from_pixbuf(&pixbuf).unwrap();
+        assert_eq!((frame.width(), frame.height()), (5, 2));
+    }
+}
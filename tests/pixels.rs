@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::{PixelType, pixels};
+
+    #[test]
+    fn premultiply_round_trips_and_reports_the_new_type() {
+        let mut data = vec![200, 100, 50, 128, 10, 20, 30, 0, 255, 255, 255, 255];
+        let premultiplied = pixels::premultiply(&mut data, PixelType::RGBA8Unassociated);
+        assert_eq!(premultiplied, PixelType::RGBA8Premultiplied);
+        assert_eq!(data, vec![100, 50, 25, 128, 0, 0, 0, 0, 255, 255, 255, 255]);
+
+        let unassociated = pixels::unpremultiply(&mut data, premultiplied);
+        assert_eq!(unassociated, PixelType::RGBA8Unassociated);
+        assert_eq!(
+            data,
+            vec![199, 100, 50, 128, 0, 0, 0, 0, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn alpha_first_types_and_no_op_cases() {
+        let mut argb = vec![51, 255, 0, 100];
+        assert_eq!(
+            pixels::premultiply(&mut argb, PixelType::ARGB8Unassociated),
+            PixelType::ARGB8Premultiplied
+        );
+        assert_eq!(argb, vec![51, 51, 0, 20]);
+
+        let mut rgb = vec![1, 2, 3];
+        assert_eq!(
+            pixels::premultiply(&mut rgb, PixelType::RGB8),
+            PixelType::RGB8
+        );
+        assert_eq!(
+            pixels::premultiply(&mut argb, PixelType::ARGB8Premultiplied),
+            PixelType::ARGB8Premultiplied
+        );
+        assert_eq!(argb, vec![51, 51, 0, 20]);
+    }
+
+    #[test]
+    fn rotations_and_flips_move_pixels() {
+        // 3x2 RGB8 with one padding byte per row:
+        // 1 2 3
+        // 4 5 6
+        let px = |v: u8| [v, v, v];
+        let mut data = Vec::new();
+        for row in [[1, 2, 3], [4, 5, 6]] {
+            row.iter().for_each(|&v| data.extend_from_slice(&px(v)));
+            data.push(0);
+        }
+        let values = |pixels: Vec<u8>| pixels.chunks(3).map(|p| p[0]).collect::<Vec<_>>();
+
+        let (cw90, w, h) =
+            pixels::rotate(&data, PixelType::RGB8, 3, 2, 10, pixels::Rotation::Cw90).unwrap();
+        assert_eq!((w, h), (2, 3));
+        assert_eq!(values(cw90), vec![4, 1, 5, 2, 6, 3]);
+
+        let (cw270, _, _) =
+            pixels::rotate(&data, PixelType::RGB8, 3, 2, 10, pixels::Rotation::Cw270).unwrap();
+        assert_eq!(values(cw270), vec![3, 6, 2, 5, 1, 4]);
+
+        let (cw180, _, _) =
+            pixels::rotate(&data, PixelType::RGB8, 3, 2, 10, pixels::Rotation::Cw180).unwrap();
+        assert_eq!(values(cw180), vec![6, 5, 4, 3, 2, 1]);
+
+        let flipped = pixels::flip_horizontal(&data, PixelType::RGB8, 3, 2, 10).unwrap();
+        assert_eq!(values(flipped), vec![3, 2, 1, 6, 5, 4]);
+        let flipped = pixels::flip_vertical(&data, PixelType::RGB8, 3, 2, 10).unwrap();
+        assert_eq!(values(flipped), vec![4, 5, 6, 1, 2, 3]);
+
+        assert!(pixels::flip_vertical(&data[..15], PixelType::RGB8, 3, 2, 10).is_err());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn image_color_types_are_mapped_or_expanded() {
+        assert_eq!(
+            PixelType::from_color_type(image::ColorType::Rgba8),
+            Some(PixelType::RGBA8Unassociated)
+        );
+        assert_eq!(PixelType::from_color_type(image::ColorType::L8), None);
+
+        let gray = image::DynamicImage::ImageLumaA8(
+            image::GrayAlphaImage::from_raw(1, 1, vec![7, 9]).unwrap(),
+        );
+        let (data, pixel_type) = pixels::to_supported(&gray);
+        assert_eq!(
+            (&*data, pixel_type),
+            (&[7, 7, 7, 9][..], PixelType::RGBA8Unassociated)
+        );
+
+        let deep = image::DynamicImage::ImageRgb16(
+            image::ImageBuffer::from_raw(1, 1, vec![0xffff, 0, 0x8080]).unwrap(),
+        );
+        let (data, pixel_type) = pixels::to_supported(&deep);
+        assert_eq!((&*data, pixel_type), (&[255, 0, 128][..], PixelType::RGB8));
+    }
+
+    #[test]
+    fn yuv_matches_bt601_reference_colors() {
+        // Black, white, mid gray, red and blue in limited range BT.601.
+        let samples = [
+            ((16, 128, 128), [0, 0, 0]),
+            ((235, 128, 128), [255, 255, 255]),
+            ((126, 128, 128), [128, 128, 128]),
+            ((81, 90, 240), [255, 0, 0]),
+            ((41, 240, 110), [0, 0, 255]),
+        ];
+        for ((y, u, v), rgb) in samples {
+            assert_eq!(
+                pixels::yuv420_to_rgb(&[y], &[u], &[v], [1, 1, 1], 1, 1).unwrap(),
+                rgb,
+                "YUV {} {} {}",
+                y,
+                u,
+                v
+            );
+            assert_eq!(
+                pixels::nv12_to_rgb(&[y], &[u, v], [1, 2], 1, 1).unwrap(),
+                rgb
+            );
+        }
+    }
+
+    #[test]
+    fn yuv_chroma_covers_two_by_two_pixels() {
+        // 3x2 luma, with chroma for columns 0-1 and 2 on padded rows.
+        let y = [16, 235, 81, 0, 235, 16, 81, 0];
+        let u = [128, 90];
+        let v = [128, 240];
+        let rgb = pixels::yuv420_to_rgb(&y, &u, &v, [4, 2, 2], 3, 2).unwrap();
+        assert_eq!(
+            rgb,
+            vec![
+                0, 0, 0, 255, 255, 255, 255, 0, 0, //
+                255, 255, 255, 0, 0, 0, 255, 0, 0,
+            ]
+        );
+        let nv12 = pixels::nv12_to_rgb(&y, &[128, 128, 90, 240], [4, 4], 3, 2).unwrap();
+        assert_eq!(nv12, rgb);
+    }
+
+    #[test]
+    fn short_yuv_planes_are_rejected() {
+        assert!(pixels::yuv420_to_rgb(&[16; 4], &[128], &[], [2, 1, 1], 2, 2).is_err());
+        assert!(pixels::yuv420_to_rgb(&[16; 4], &[128], &[128], [1, 1, 1], 2, 2).is_err());
+        assert!(pixels::nv12_to_rgb(&[16; 4], &[128], [2, 2], 2, 2).is_err());
+        assert!(pixels::nv12_to_rgb(&[16], &[128, 128], [1, 2], 0, 1).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::{Image, Placement};
+
+    #[test]
+    fn automatic_ids_skip_explicit_ones() {
+        let image = Image::new().unwrap();
+        let explicit = Placement::new(image.clone(), 1000).unwrap();
+        assert_eq!(explicit.id(), 1000);
+        let automatic = Placement::fitted(&image).unwrap();
+        assert!(automatic.id() > 1000);
+        assert!(Placement::new(image, 0).unwrap().id() > automatic.id());
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::{PlaybackControl, Repeat, changed_bands};
+
+    #[test]
+    fn control_is_shared_between_clones() {
+        let control = PlaybackControl::default();
+        let handle = control.clone();
+        assert!(!control.is_paused());
+
+        handle.toggle_pause();
+        assert!(control.is_paused());
+        handle.resume();
+        assert!(!control.is_paused());
+
+        handle.stop();
+        assert!(control.is_stopped());
+    }
+
+    #[test]
+    fn plays_once_by_default() {
+        assert_eq!(Repeat::default(), Repeat::Times(1));
+    }
+
+    #[test]
+    fn identical_frames_have_no_changed_bands() {
+        let frame = vec![7; 4 * 3];
+        assert_eq!(changed_bands(&frame, &frame, 4), None);
+    }
+
+    #[test]
+    fn a_single_changed_band_is_found() {
+        let a = vec![0; 4 * 3];
+        let mut b = a.clone();
+        b[5] = 1;
+        assert_eq!(changed_bands(&a, &b, 4), Some((1, 2)));
+    }
+
+    #[test]
+    fn changes_on_the_edges_span_every_band() {
+        let a = vec![0; 4 * 3];
+        let mut b = a.clone();
+        b[0] = 1;
+        assert_eq!(changed_bands(&a, &b, 4), Some((0, 1)));
+        b[11] = 1;
+        assert_eq!(changed_bands(&a, &b, 4), Some((0, 3)));
+        b[0] = 0;
+        assert_eq!(changed_bands(&a, &b, 4), Some((2, 3)));
+    }
+}
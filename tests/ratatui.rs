@@ -0,0 +1,81 @@
+#![cfg(feature = "ratatui")]
+
+#[cfg(test)]
+mod tests {
+    use libchafa::PixelType;
+    use libchafa::canvas::{Config, PixelMode};
+    use libchafa::ratatui::{ChafaImage, ChafaImageState};
+    use ratatui_core::buffer::{Buffer, Cell, CellDiffOption};
+    use ratatui_core::layout::Rect;
+    use ratatui_core::style::Color;
+    use ratatui_core::widgets::{StatefulWidget, Widget};
+
+    const RED: [u8; 16 * 16 * 4] = {
+        let mut pixels = [0u8; 16 * 16 * 4];
+        let mut i = 0;
+        while i < pixels.len() {
+            pixels[i] = 0xff;
+            pixels[i + 3] = 0xff;
+            i += 4;
+        }
+        pixels
+    };
+
+    fn image(config: &Config) -> ChafaImage<'_> {
+        ChafaImage::new(&RED, PixelType::RGBA8Unassociated, 16, 16, 16 * 4).config(config)
+    }
+
+    #[test]
+    fn symbols_are_copied_into_the_buffer() {
+        let config = Config::new().unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 4));
+        Widget::render(image(&config), buf.area, &mut buf);
+
+        let cell = &buf[(0, 0)];
+        assert!(!cell.symbol().is_empty());
+        assert!(
+            matches!(cell.bg, Color::Rgb(r, _, _) if r > 0x80)
+                || matches!(cell.fg, Color::Rgb(r, _, _) if r > 0x80)
+        );
+        assert_eq!(cell.diff_option, CellDiffOption::None);
+    }
+
+    #[test]
+    fn drawing_is_clipped_to_the_buffer() {
+        let config = Config::new().unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 3));
+        // The area reaches past the buffer's right and bottom edges.
+        Widget::render(image(&config), Rect::new(4, 1, 20, 10), &mut buf);
+
+        for y in 0..3 {
+            for x in 0..4 {
+                assert_eq!(buf[(x, y)], Cell::EMPTY);
+            }
+        }
+        assert_eq!(buf[(0, 0)], Cell::EMPTY);
+        assert_ne!(buf[(4, 1)], Cell::EMPTY);
+    }
+
+    #[test]
+    fn pixel_modes_skip_the_image_cells() {
+        let config = Config::new().unwrap();
+        config.set_pixel_mode(PixelMode::Kitty);
+        config.set_cell_geometry(8, 16).unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 4));
+        let mut state = ChafaImageState::new();
+        StatefulWidget::render(image(&config), buf.area, &mut buf, &mut state);
+
+        let (cols, rows) = state.size().unwrap();
+        assert!(buf[(0, 0)].symbol().starts_with('\x1b'));
+        assert_ne!(buf[(0, 0)].diff_option, CellDiffOption::Skip);
+        for y in 0..rows {
+            for x in 0..cols {
+                if (x, y) != (0, 0) {
+                    assert_eq!(buf[(x, y)].diff_option, CellDiffOption::Skip);
+                }
+            }
+        }
+        // Cells outside the image are left alone.
+        assert_eq!(buf[(cols, 0)].diff_option, CellDiffOption::None);
+    }
+}
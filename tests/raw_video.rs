@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::{ChafaError, FrameSource, RawFormat, RawVideoSource};
+    use std::io::{ErrorKind, Read};
+    use std::time::Duration;
+
+    /// Hands out its data, then fails.
+    struct Broken<'a>(&'a [u8]);
+
+    impl Read for Broken<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(ErrorKind::BrokenPipe.into());
+            }
+            let n = buf.len().min(self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn formats_are_named_and_sized_like_ffmpeg() {
+        assert_eq!(RawFormat::from_name("yuv420p"), Some(RawFormat::Yuv420p));
+        assert_eq!(RawFormat::from_name("gray"), None);
+        assert_eq!(RawFormat::Bgra.name(), "bgra");
+        assert_eq!(RawFormat::Rgb24.frame_size(4, 2), 24);
+        // Odd sizes round the chroma planes up.
+        assert_eq!(RawFormat::Yuv420p.frame_size(3, 3), 9 + 2 * 2 * 2);
+        assert_eq!(RawFormat::Nv12.frame_size(4, 2), 8 + 4);
+    }
+
+    #[test]
+    fn bad_parameters_are_rejected() {
+        let source = RawVideoSource::new(std::io::empty(), RawFormat::Rgba, 2, 2, 25.0).unwrap();
+        assert_eq!(source.frame_interval(), Duration::from_millis(40));
+        assert!(RawVideoSource::new(std::io::empty(), RawFormat::Rgba, 0, 2, 25.0).is_err());
+        assert!(RawVideoSource::new(std::io::empty(), RawFormat::Rgba, 2, 2, 0.0).is_err());
+    }
+
+    #[test]
+    fn frames_end_with_the_input() {
+        // One whole 2x1 frame and half of another.
+        let data = [0u8; 6 + 3];
+        let mut source = RawVideoSource::new(&data[..], RawFormat::Rgb24, 2, 1, 25.0).unwrap();
+        let (frame, delay) = source.next_frame().unwrap().unwrap();
+        assert_eq!((frame.width(), frame.height()), (2, 1));
+        assert_eq!(delay, Duration::from_millis(40));
+        assert!(source.next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn read_errors_are_returned() {
+        let data = [0u8; 6];
+        let mut source = RawVideoSource::new(Broken(&data), RawFormat::Rgb24, 2, 1, 25.0).unwrap();
+        assert!(source.next_frame().unwrap().is_some());
+        match source.next_frame() {
+            Err(ChafaError::Io(e)) => assert_eq!(e.kind(), ErrorKind::BrokenPipe),
+            other => panic!(
+                "expected a read error, got {:?}",
+                other.map(|f| f.is_some())
+            ),
+        }
+    }
+}
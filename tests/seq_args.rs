@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::ChafaError;
+    use libchafa::term::{CHAFA_TERM_SEQ_ARGS_MAX, ColorDirect, CursorPos, Info, Seq, SeqArgs};
+
+    #[test]
+    fn args_keep_chafa_order() {
+        let mut args = [0u32; CHAFA_TERM_SEQ_ARGS_MAX as usize];
+
+        let n = CursorPos { x: 3, y: 7 }.fill_args(&mut args);
+        assert_eq!(&args[..n], &[3, 7]);
+
+        let n = ColorDirect { r: 1, g: 2, b: 255 }.fill_args(&mut args);
+        assert_eq!(&args[..n], &[1, 2, 255]);
+
+        assert!(ColorDirect::SEQS.contains(&Seq::SetColorBgDirect));
+        assert!(!ColorDirect::SEQS.contains(&Seq::CursorToPos));
+    }
+
+    #[test]
+    fn emit_errors_are_chafa_errors() {
+        let info = Info::new().unwrap();
+        let mut out = Vec::new();
+        assert!(matches!(
+            info.emit_seq(Seq::CursorToPos, &[1], &mut out),
+            Err(ChafaError::InvalidInput(_))
+        ));
+        // An empty term info has no sequences at all.
+        assert!(matches!(
+            info.emit_seq(Seq::CursorToPos, &[1, 2], &mut out),
+            Err(ChafaError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            info.emit_args(Seq::CursorToPos, &ColorDirect::default(), &mut out),
+            Err(ChafaError::InvalidInput(_))
+        ));
+        assert!(out.is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use libchafa::canvas::{CanvasMode, PixelMode};
+    use libchafa::{Align, Features, Repeat, SymbolTags, Tuck};
+    use serde::Deserialize;
+    use serde::de::value::{Error, StrDeserializer};
+
+    fn from_str<'de, T: Deserialize<'de>>(s: &'de str) -> Result<T, Error> {
+        T::deserialize(StrDeserializer::<Error>::new(s))
+    }
+
+    #[test]
+    fn enums_use_chafa_names_and_cli_aliases() {
+        assert_eq!(from_str::<PixelMode>("iterm2").unwrap(), PixelMode::Iterm2);
+        assert_eq!(from_str::<PixelMode>("iterm").unwrap(), PixelMode::Iterm2);
+        assert_eq!(
+            from_str::<CanvasMode>("indexed-16-8").unwrap(),
+            CanvasMode::Indexed168
+        );
+        assert_eq!(
+            from_str::<CanvasMode>("full").unwrap(),
+            CanvasMode::TrueColor
+        );
+        assert_eq!(
+            from_str::<Tuck>("shrink-to-fit").unwrap(),
+            Tuck::ShrinkToFit
+        );
+        assert_eq!(from_str::<Align>("left").unwrap(), Align::Start);
+        assert!(from_str::<PixelMode>("max").is_err());
+        assert_eq!(
+            from_str::<Repeat>("from-animation").unwrap(),
+            Repeat::FromAnimation
+        );
+    }
+
+    #[test]
+    fn flags_parse_from_strings() {
+        assert_eq!(
+            from_str::<SymbolTags>("block,border-diagonal").unwrap(),
+            SymbolTags::Block | SymbolTags::Border
+        );
+        assert_eq!(
+            from_str::<Features>("MMX avx2").unwrap(),
+            Features::MMX | Features::AVX2
+        );
+        assert!(from_str::<Features>("neon").is_err());
+    }
+}
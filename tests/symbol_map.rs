@@ -0,0 +1,363 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::{
+        ChafaError, Glyph, MapPreset, PixelType, SYMBOL_HEIGHT_PIXELS, SYMBOL_PIXELS,
+        SYMBOL_WIDTH_PIXELS, SymbolMap, SymbolMapChange, SymbolTags,
+    };
+
+    fn glyph(value: u8) -> Glyph {
+        Glyph::from_vec(
+            vec![value; 8 * 8 * 4],
+            PixelType::RGBA8Unassociated,
+            8,
+            8,
+            8 * 4,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn glyph_rowstride_is_checked_against_the_pixel_format() {
+        let pixels = vec![0; 8 * 8 * 3];
+        assert!(Glyph::from_vec(pixels.clone(), PixelType::RGB8, 8, 8, 8 * 3).is_ok());
+        assert!(matches!(
+            Glyph::from_vec(pixels.clone(), PixelType::RGBA8Unassociated, 8, 8, 8 * 3),
+            Err(ChafaError::InvalidInput(_))
+        ));
+        assert!(Glyph::from_vec(pixels, PixelType::RGB8, 8, 9, 8 * 3).is_err());
+    }
+
+    #[test]
+    fn repeated_changes_are_folded() {
+        let map = SymbolMap::new().unwrap();
+        for _ in 0..100 {
+            map.add_by_tags(SymbolTags::Block);
+            map.add_glyph(0x41, PixelType::RGBA8Unassociated, &glyph(0xff))
+                .unwrap();
+            map.set_allow_builtin_glyphs(false);
+        }
+        map.set_allow_builtin_glyphs(true);
+        map.add_glyph(0x41, PixelType::RGBA8Unassociated, &glyph(0x80))
+            .unwrap();
+
+        assert_eq!(
+            map.changes(),
+            vec![
+                SymbolMapChange::AddTags(SymbolTags::Block),
+                SymbolMapChange::Glyph(0x41, PixelType::RGBA8Unassociated, glyph(0x80)),
+                SymbolMapChange::AllowBuiltinGlyphs(true),
+            ]
+        );
+        assert_eq!(map.imported_code_points(), vec![0x41]);
+    }
+
+    #[test]
+    fn adjacent_ranges_are_merged() {
+        let map = SymbolMap::new().unwrap();
+        for c in 'a'..='z' {
+            map.add_by_range(c, c);
+        }
+        map.add_by_range('0', '9');
+        map.remove_by_range('b', 'c');
+        map.remove_by_range('c', 'f');
+
+        assert_eq!(
+            map.changes(),
+            vec![
+                SymbolMapChange::AddRange('a', 'z'),
+                SymbolMapChange::AddRange('0', '9'),
+                SymbolMapChange::RemoveRange('b', 'f'),
+            ]
+        );
+    }
+
+    #[test]
+    fn order_of_additions_and_removals_is_kept() {
+        let map = SymbolMap::new().unwrap();
+        map.add_by_tags(SymbolTags::Block);
+        map.remove_by_tags(SymbolTags::Block);
+        map.add_by_tags(SymbolTags::Block);
+
+        assert_eq!(map.changes().len(), 3);
+    }
+
+    #[test]
+    fn clearing_selectors_drop_earlier_selection() {
+        let map = SymbolMap::new().unwrap();
+        map.add_by_tags(SymbolTags::Braille);
+        map.add_glyph(0x41, PixelType::RGBA8Unassociated, &glyph(0xff))
+            .unwrap();
+        map.add_by_range('a', 'z');
+        map.apply_selectors("block").unwrap();
+        map.apply_selectors("+border").unwrap();
+
+        assert_eq!(
+            map.changes(),
+            vec![
+                SymbolMapChange::Glyph(0x41, PixelType::RGBA8Unassociated, glyph(0xff)),
+                SymbolMapChange::Selectors(String::from("block")),
+                SymbolMapChange::Selectors(String::from("+border")),
+            ]
+        );
+    }
+
+    #[test]
+    fn folded_changes_replay_to_the_same_map() {
+        let map = SymbolMap::new().unwrap();
+        for c in 'a'..='f' {
+            map.add_by_range(c, c);
+        }
+        map.add_by_tags(SymbolTags::Block);
+        map.add_by_tags(SymbolTags::Block);
+        map.remove_by_range('c', 'c');
+
+        let replayed = SymbolMap::new().unwrap();
+        for change in map.changes() {
+            replayed.apply_change(&change).unwrap();
+        }
+        assert_eq!(
+            map.changes(),
+            vec![
+                SymbolMapChange::AddRange('a', 'f'),
+                SymbolMapChange::AddTags(SymbolTags::Block),
+                SymbolMapChange::RemoveRange('c', 'c'),
+            ]
+        );
+        assert_eq!(replayed.changes(), map.changes());
+    }
+
+    #[test]
+    fn union_adds_the_other_selection() {
+        let a = SymbolMap::new().unwrap();
+        a.add_by_tags(SymbolTags::Block);
+        let b = SymbolMap::new().unwrap();
+        b.apply_selectors("braille").unwrap();
+
+        assert_eq!(
+            a.union(&b).unwrap().changes(),
+            vec![
+                SymbolMapChange::AddTags(SymbolTags::Block),
+                SymbolMapChange::Selectors(String::from("+braille")),
+            ]
+        );
+
+        a.remove_by_tags(SymbolTags::Vhalf);
+        b.remove_by_tags(SymbolTags::Hhalf);
+        assert!(a.union(&b).is_err());
+    }
+
+    #[test]
+    fn intersection_removes_outside_ranges() {
+        let a = SymbolMap::new().unwrap();
+        a.add_by_tags(SymbolTags::Block);
+        let b = SymbolMap::new().unwrap();
+        b.add_by_range('\u{2580}', '\u{259f}');
+
+        assert_eq!(
+            a.intersection(&b).unwrap().changes(),
+            vec![
+                SymbolMapChange::AddTags(SymbolTags::Block),
+                SymbolMapChange::RemoveRange('\0', '\u{257f}'),
+                SymbolMapChange::RemoveRange('\u{25a0}', '\u{10ffff}'),
+            ]
+        );
+        assert!(a.intersection(&a).is_err());
+    }
+
+    #[test]
+    fn difference_negates_each_term() {
+        let a = SymbolMap::new().unwrap();
+        a.add_by_tags(SymbolTags::Block);
+        let b = SymbolMap::new().unwrap();
+        b.apply_selectors("braille,u+2580..U+259f+ascii").unwrap();
+        b.add_by_range('a', 'z');
+
+        assert_eq!(
+            a.difference(&b).unwrap().changes(),
+            vec![
+                SymbolMapChange::AddTags(SymbolTags::Block),
+                SymbolMapChange::Selectors(String::from("-braille-u+2580..U+259f-ascii")),
+                SymbolMapChange::RemoveRange('a', 'z'),
+            ]
+        );
+
+        b.apply_selectors("-ascii").unwrap();
+        assert!(a.difference(&b).is_err());
+    }
+
+    #[test]
+    fn best_matches_stop_when_the_map_runs_out() {
+        let map = SymbolMap::new().unwrap();
+        map.add_by_range('█', '█');
+
+        let matches = map.best_matches(&[0xff; SYMBOL_PIXELS], 5).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].symbol, '█');
+        assert_eq!(matches[0].score, 1.0);
+
+        let empty = SymbolMap::new().unwrap();
+        assert!(
+            empty
+                .best_matches(&[0xff; SYMBOL_PIXELS], 5)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn best_matches_are_ordered_and_distinct() {
+        let map = SymbolMap::new().unwrap();
+        map.add_by_range(' ', ' ');
+        map.add_by_range('█', '█');
+
+        let matches = map.best_matches(&[0; SYMBOL_PIXELS], 5).unwrap();
+        let symbols: Vec<char> = matches.iter().map(|m| m.symbol).collect();
+        assert_eq!(symbols, vec![' ', '█']);
+        assert!(matches[0].score >= matches[1].score);
+    }
+
+    fn sized_glyph(width: u32, height: u32) -> Glyph {
+        let (width, height) = (width as i32, height as i32);
+        Glyph::from_vec(
+            vec![0xff; (width * height * 4) as usize],
+            PixelType::RGBA8Unassociated,
+            width,
+            height,
+            width * 4,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn wide_glyphs_are_imported() {
+        let map = SymbolMap::new().unwrap();
+        let wide = sized_glyph(2 * SYMBOL_WIDTH_PIXELS, SYMBOL_HEIGHT_PIXELS);
+        map.add_wide_glyph('漢', PixelType::RGBA8Unassociated, &wide)
+            .unwrap();
+        assert_eq!(map.imported_code_points(), vec![u32::from('漢')]);
+    }
+
+    #[test]
+    fn wide_glyphs_must_be_two_cells() {
+        let map = SymbolMap::new().unwrap();
+        for (width, height) in [
+            (SYMBOL_WIDTH_PIXELS, SYMBOL_HEIGHT_PIXELS),
+            (2 * SYMBOL_WIDTH_PIXELS, 2 * SYMBOL_HEIGHT_PIXELS),
+        ] {
+            assert!(matches!(
+                map.add_wide_glyph(
+                    '漢',
+                    PixelType::RGBA8Unassociated,
+                    &sized_glyph(width, height)
+                ),
+                Err(ChafaError::InvalidInput(_))
+            ));
+        }
+        assert!(map.imported_code_points().is_empty());
+    }
+
+    #[test]
+    fn wide_glyphs_need_a_wide_character() {
+        let map = SymbolMap::new().unwrap();
+        let wide = sized_glyph(2 * SYMBOL_WIDTH_PIXELS, SYMBOL_HEIGHT_PIXELS);
+        assert!(matches!(
+            map.add_wide_glyph('A', PixelType::RGBA8Unassociated, &wide),
+            Err(ChafaError::InvalidInput(_))
+        ));
+        assert!(map.imported_code_points().is_empty());
+    }
+
+    #[test]
+    fn retained_chars_keep_their_code_points() {
+        let map = SymbolMap::new().unwrap();
+        map.add_by_tags(SymbolTags::Block);
+        map.retain_chars(['b', 'a', 'c', '█']);
+
+        assert_eq!(
+            map.changes(),
+            vec![
+                SymbolMapChange::AddTags(SymbolTags::Block),
+                SymbolMapChange::RemoveRange('\0', '`'),
+                SymbolMapChange::RemoveRange('d', '\u{2587}'),
+                SymbolMapChange::RemoveRange('\u{2589}', '\u{1ffff}'),
+            ]
+        );
+    }
+
+    #[test]
+    fn retaining_nothing_removes_everything() {
+        let map = SymbolMap::new().unwrap();
+        map.retain_chars([]);
+        assert_eq!(
+            map.changes(),
+            vec![SymbolMapChange::RemoveRange('\0', '\u{1ffff}')]
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn glyph_images_show_the_coverage() {
+        let mut pixels = vec![0; 8 * 8 * 4];
+        pixels[..8 * 4 * 4].fill(0xff);
+        let half = Glyph::from_vec(pixels, PixelType::RGBA8Unassociated, 8, 8, 8 * 4).unwrap();
+        let map = SymbolMap::new().unwrap();
+        map.add_glyph(0x41, PixelType::RGBA8Unassociated, &half)
+            .unwrap();
+
+        let image = map.get_glyph_image(0x41).unwrap();
+        assert_eq!(image.dimensions(), (8, 8));
+        assert_eq!(image.get_pixel(0, 0)[3], 0xff);
+        assert_eq!(image.get_pixel(7, 7)[3], 0);
+
+        let gray = map.get_glyph_gray_image(0x41).unwrap();
+        assert_eq!(gray.dimensions(), (8, 8));
+        assert_eq!(gray.get_pixel(0, 0)[0], 0xff);
+        assert_eq!(gray.get_pixel(7, 7)[0], 0);
+
+        assert!(map.get_glyph_image(0x42).is_none());
+    }
+
+    fn removed(map: &SymbolMap, c: char) -> bool {
+        map.changes().iter().any(|change| match change {
+            SymbolMapChange::Selectors(selectors) => selectors
+                .trim_start_matches('-')
+                .split(',')
+                .filter_map(|term| term.split_once(".."))
+                .any(|(first, last)| {
+                    let parse = |s: &str| u32::from_str_radix(&s[2..], 16).unwrap();
+                    (parse(first)..=parse(last)).contains(&u32::from(c))
+                }),
+            _ => false,
+        })
+    }
+
+    #[test]
+    fn width_unsafe_code_points_are_removed_from_presets() {
+        let map = SymbolMap::preset(MapPreset::Blocks).unwrap();
+        let preset_changes = map.changes().len();
+        map.remove_width_unsafe(false);
+
+        assert_eq!(map.changes().len(), preset_changes + 1);
+        assert!(matches!(
+            &map.changes()[preset_changes],
+            SymbolMapChange::Selectors(s) if s.starts_with("-U+0000..U+001F,")
+        ));
+        assert!(removed(&map, '\u{301}'), "combining marks are zero-width");
+        assert!(removed(&map, '⌚'), "emoji were made wide");
+        assert!(!removed(&map, ' '));
+        assert!(!removed(&map, '█'));
+        assert!(!removed(&map, 'A'));
+    }
+
+    #[test]
+    fn east_asian_width_removes_ambiguous_blocks() {
+        let map = SymbolMap::preset(MapPreset::Blocks).unwrap();
+        map.remove_width_unsafe(true);
+
+        assert!(removed(&map, '█'));
+        assert!(removed(&map, '▀'));
+        assert!(!removed(&map, '▐'));
+        assert!(!removed(&map, ' '));
+        assert!(!removed(&map, 'A'));
+    }
+}
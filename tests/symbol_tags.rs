@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::SymbolTags;
+
+    #[test]
+    fn parses_selector_syntax() {
+        let tags: SymbolTags = "block,border-dot,stipple".parse().unwrap();
+        assert_eq!(
+            tags,
+            (SymbolTags::Block | SymbolTags::Border) - SymbolTags::Dot - SymbolTags::Stipple
+        );
+        assert_eq!("VHALF".parse::<SymbolTags>().unwrap(), SymbolTags::Vhalf);
+        assert!("block,nope".parse::<SymbolTags>().is_err());
+    }
+
+    #[test]
+    fn formats_selector_names() {
+        assert_eq!(SymbolTags::empty().to_string(), "none");
+        assert_eq!(SymbolTags::All.to_string(), "all");
+        assert_eq!(
+            (SymbolTags::Hhalf | SymbolTags::Vhalf | SymbolTags::Braille).to_string(),
+            "half,braille"
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let tags = SymbolTags::Block | SymbolTags::Sextant | SymbolTags::Imported;
+        assert_eq!(tags.to_string().parse::<SymbolTags>().unwrap(), tags);
+    }
+
+    #[test]
+    fn unnamed_bits_round_trip() {
+        let unnamed = (0..32)
+            .map(|bit| SymbolTags::from_bits_retain(1 << bit))
+            .find(|bit| bit.to_string().starts_with("0x"))
+            .unwrap();
+        let tags = SymbolTags::Block | unnamed;
+
+        let text = tags.to_string();
+        assert!(text.starts_with("block,0x"), "{}", text);
+        assert_eq!(text.parse::<SymbolTags>().unwrap(), tags);
+        assert_eq!(unnamed.to_string().parse::<SymbolTags>().unwrap(), unnamed);
+        assert!("0xnope".parse::<SymbolTags>().is_err());
+    }
+}
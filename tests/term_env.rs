@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::term::{Db, Env, Seq, detect_multiplexer, in_zellij, tmux_allows_passthrough};
+    use std::collections::HashMap;
+
+    #[test]
+    fn env_is_built_from_pairs() {
+        let mut env = Env::new()
+            .var("TERM", "xterm-kitty")
+            .var("COLORTERM", "truecolor");
+        assert_eq!(env.get("TERM"), Some("xterm-kitty"));
+        assert!(env.contains("COLORTERM"));
+        assert_eq!(env.remove("COLORTERM").as_deref(), Some("truecolor"));
+        assert!(!env.contains("COLORTERM"));
+
+        let map = HashMap::from([(String::from("TERM"), String::from("xterm-kitty"))]);
+        assert_eq!(Env::from(map), env);
+        assert_eq!(env.iter().collect::<Vec<_>>(), [("TERM", "xterm-kitty")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_unicode_values_are_kept() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let value = OsStr::from_bytes(b"caf\xe9");
+        let env = Env::new().var("TERM", "xterm").var("LANGUAGE", value);
+        assert_eq!(env.get("LANGUAGE"), None);
+        assert_eq!(env.get_os("LANGUAGE"), Some(value));
+        assert_eq!(env.iter().count(), 1);
+        assert_eq!(env.iter_os().count(), 2);
+    }
+
+    #[test]
+    fn multiplexers_are_detected_from_the_given_env() {
+        let zellij = Env::new().var("ZELLIJ", "0");
+        assert!(in_zellij(&zellij));
+        assert!(!in_zellij(&Env::new()));
+        assert_eq!(tmux_allows_passthrough(&Env::new()), None);
+        assert!(detect_multiplexer(&Env::new()).is_none());
+    }
+
+    #[test]
+    fn terminals_are_detected_from_the_given_env() {
+        let db = Db::default().unwrap();
+        let kitty = db
+            .detect_env(&Env::new().var("TERM", "xterm-kitty"))
+            .unwrap();
+        assert!(kitty.have_seq(Seq::BeginKittyImmediateImageV1));
+
+        let vt100 = db.detect_env(&Env::new().var("TERM", "vt100")).unwrap();
+        assert!(!vt100.have_seq(Seq::BeginKittyImmediateImageV1));
+    }
+}
@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::term::{Key, Seq, TermEvent, Token};
+
+    #[test]
+    fn tokens_become_events() {
+        assert_eq!(
+            TermEvent::from_token(Token::Seq(Seq::UpCtrlKey, vec![])),
+            vec![TermEvent::Key {
+                key: Key::Up,
+                ctrl: true,
+                shift: false
+            }]
+        );
+        assert_eq!(
+            TermEvent::from_token(Token::Seq(Seq::CellSizePx, vec![16, 8])),
+            vec![TermEvent::CellSizePx {
+                width: 8,
+                height: 16
+            }]
+        );
+        assert_eq!(
+            TermEvent::from_token(Token::Seq(Seq::SetDefaultBg, vec![0xffff, 0x8000, 0])),
+            vec![TermEvent::DefaultBg(0xff8000)]
+        );
+        assert_eq!(
+            TermEvent::from_token(Token::Bytes("hé".as_bytes().to_vec())),
+            vec![TermEvent::Char('h'), TermEvent::Char('é')]
+        );
+    }
+}
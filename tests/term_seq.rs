@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::term::{Info, Seq};
+
+    #[test]
+    fn seq_names_round_trip() {
+        assert_eq!(Seq::CursorToPos.to_string(), "cursor_to_pos");
+        assert_eq!("cursor-to-pos".parse::<Seq>(), Ok(Seq::CursorToPos));
+        assert!("max".parse::<Seq>().is_err());
+
+        for seq in Seq::all() {
+            assert_eq!(seq.to_string().parse::<Seq>(), Ok(seq));
+            assert_eq!(Seq::from_raw(u32::from(seq)), Some(seq));
+        }
+        assert_eq!(Seq::from_raw(u32::from(Seq::Max) + 1), None);
+    }
+
+    #[test]
+    fn try_clone_copies_deeply() {
+        let info = Info::new().unwrap();
+        info.set_seq(Seq::ResetAttributes, Some("\x1b[0m")).unwrap();
+
+        let copy = info.try_clone().unwrap();
+        copy.set_seq(Seq::ResetAttributes, None).unwrap();
+        assert!(info.have_seq(Seq::ResetAttributes));
+        assert!(!copy.have_seq(Seq::ResetAttributes));
+    }
+
+    #[test]
+    fn synchronized_update_support_is_carried_along() {
+        let info = Info::new().unwrap();
+        assert!(!info.supports_synchronized_update());
+
+        info.set_synchronized_update(true);
+        assert!(info.try_clone().unwrap().supports_synchronized_update());
+
+        let outer = Info::new().unwrap();
+        assert!(
+            Info::chain(&outer, &info)
+                .unwrap()
+                .supports_synchronized_update()
+        );
+        assert!(
+            !Info::chain(&info, &outer)
+                .unwrap()
+                .supports_synchronized_update()
+        );
+    }
+
+    #[test]
+    fn definition_escapes_are_bytes() {
+        let info = Info::from_definition_string("begin_sixels=\\eP\\xc3\\xa9q").unwrap();
+        assert_eq!(info.get_seq(Seq::BeginSixels).as_deref(), Some("\x1bPéq"));
+        assert!(Info::from_definition_string("begin_sixels=\\xff").is_err());
+    }
+
+    #[test]
+    fn definitions_keep_inherit_flags() {
+        let info = Info::new().unwrap();
+        info.set_seq(Seq::ResetAttributes, Some("\x1b[0m")).unwrap();
+        for seq in Seq::all() {
+            info.set_inherit_seq(seq, seq == Seq::ResetAttributes);
+        }
+
+        let text = info.to_definition_string();
+        assert!(text.contains("inherit=reset_attributes\n"), "{}", text);
+        let loaded = Info::from_definition_string(&text).unwrap();
+        for seq in Seq::all() {
+            assert_eq!(loaded.get_inherit_seq(seq), seq == Seq::ResetAttributes);
+        }
+        assert_eq!(loaded.to_definition_string(), text);
+    }
+}
@@ -0,0 +1,42 @@
+#![cfg(all(unix, feature = "termion"))]
+
+#[cfg(test)]
+mod tests {
+    use libchafa::termion::{ansi, from_goto, rgb, to_goto};
+    use termion::color::Rgb;
+    use termion::cursor::Goto;
+
+    fn cell(goto: Goto) -> (u16, u16) {
+        (goto.0, goto.1)
+    }
+
+    #[test]
+    fn goto_counts_from_one() {
+        assert_eq!(cell(to_goto(0, 0)), (1, 1));
+        assert_eq!(cell(to_goto(4, 9)), (5, 10));
+        assert_eq!(from_goto(Goto(5, 10)), (4, 9));
+        assert_eq!(from_goto(to_goto(12, 3)), (12, 3));
+    }
+
+    #[test]
+    fn goto_is_clamped() {
+        assert_eq!(cell(to_goto(-3, -1)), (1, 1));
+        assert_eq!(cell(to_goto(i32::MAX, 0)), (u16::MAX, 1));
+        assert_eq!(from_goto(Goto(0, 0)), (0, 0));
+    }
+
+    #[test]
+    fn rgb_unpacks_colors() {
+        assert_eq!(rgb(0x12ab34), Some(Rgb(0x12, 0xab, 0x34)));
+        assert_eq!(rgb(0), Some(Rgb(0, 0, 0)));
+        assert_eq!(rgb(-1), None);
+    }
+
+    #[test]
+    fn ansi_takes_palette_pens() {
+        assert_eq!(ansi(0).map(|pen| pen.0), Some(0));
+        assert_eq!(ansi(255).map(|pen| pen.0), Some(255));
+        assert!(ansi(256).is_none());
+        assert!(ansi(-1).is_none());
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use libchafa::canvas::Config;
+    use libchafa::{ChafaError, Frame, PixelType, Rect};
+
+    fn frame_error(len: usize, width: i32, height: i32, rowstride: i32) -> bool {
+        matches!(
+            Frame::new(
+                &vec![0; len],
+                PixelType::RGBA8Unassociated,
+                width,
+                height,
+                rowstride
+            ),
+            Err(ChafaError::InvalidInput(_))
+        )
+    }
+
+    #[test]
+    fn empty_images_are_rejected() {
+        assert!(frame_error(64, 0, 4, 16));
+        assert!(frame_error(64, 4, 0, 16));
+        assert!(frame_error(64, -4, 4, 16));
+    }
+
+    #[test]
+    fn short_and_negative_rowstrides_are_rejected() {
+        assert!(frame_error(64, 4, 4, 15));
+        assert!(frame_error(64, 4, 4, 0));
+        assert!(frame_error(64, 4, 4, -16));
+    }
+
+    #[test]
+    fn short_buffers_are_rejected() {
+        // The last row may stop after its pixels, but not before.
+        assert!(frame_error(16 * 3 + 15, 4, 4, 16));
+        assert!(frame_error(32 * 3 + 15, 4, 4, 32));
+    }
+
+    #[test]
+    fn short_last_row_is_padded() {
+        let frame = Frame::new(&[0x80; 32 * 3 + 16], PixelType::RGBA8Unassociated, 4, 4, 32);
+        assert_eq!(frame.unwrap().rowstride(), 32);
+    }
+
+    #[test]
+    fn geometry_must_be_positive() {
+        let config = Config::new().unwrap();
+        assert!(matches!(
+            config.set_geometry(0, 10),
+            Err(ChafaError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            config.set_cell_geometry(8, -1),
+            Err(ChafaError::InvalidInput(_))
+        ));
+        assert!(config.set_geometry(10, 5).is_ok());
+    }
+
+    fn crop_error(rowstride: i32, region: Rect) -> bool {
+        matches!(
+            Frame::cropped(
+                &[0; 64],
+                PixelType::RGBA8Unassociated,
+                4,
+                4,
+                rowstride,
+                region
+            ),
+            Err(ChafaError::InvalidInput(_))
+        )
+    }
+
+    #[test]
+    fn regions_must_lie_inside_the_image() {
+        assert!(crop_error(16, Rect::new(-1, 0, 2, 2)));
+        assert!(crop_error(16, Rect::new(3, 0, 2, 2)));
+        assert!(crop_error(16, Rect::new(0, 0, 0, 2)));
+        assert!(crop_error(16, Rect::new(1, 1, i32::MAX, 1)));
+        assert!(crop_error(16, Rect::new(0, i32::MAX, 1, i32::MAX)));
+    }
+
+    #[test]
+    fn regions_need_a_sane_rowstride() {
+        assert!(crop_error(-16, Rect::new(0, 0, 2, 2)));
+        assert!(crop_error(8, Rect::new(0, 0, 2, 2)));
+        assert!(crop_error(i32::MAX, Rect::new(0, 1, 2, 2)));
+    }
+}